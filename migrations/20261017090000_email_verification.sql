@@ -0,0 +1,16 @@
+-- Track when a user proved ownership of their email address
+ALTER TABLE users_auth ADD COLUMN email_verified_at TIMESTAMPTZ;
+
+-- Accounts created before verification existed are considered verified
+UPDATE users_auth SET email_verified_at = created_at WHERE email_verified_at IS NULL;
+
+CREATE TABLE email_verification_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users_auth(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_email_verification_tokens_user_id ON email_verification_tokens(user_id);
@@ -0,0 +1,10 @@
+-- Device metadata so users can see and manage where they are signed in
+ALTER TABLE refresh_tokens
+    ADD COLUMN user_agent VARCHAR(512),
+    ADD COLUMN ip_address VARCHAR(45),
+    ADD COLUMN device_name VARCHAR(100),
+    ADD COLUMN last_used_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+UPDATE refresh_tokens SET last_used_at = created_at;
+
+CREATE INDEX idx_refresh_tokens_user_id_active ON refresh_tokens(user_id) WHERE used = FALSE;
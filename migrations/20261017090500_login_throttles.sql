@@ -0,0 +1,12 @@
+-- Failed login tracking for progressive backoff and temporary lockout.
+-- Keyed by 'user:<uuid>' for existing accounts and 'email:<address>' for unknown addresses,
+-- so lockout behaves the same whether or not an account exists.
+CREATE TABLE login_throttles (
+    throttle_key VARCHAR(320) PRIMARY KEY,
+    user_id UUID REFERENCES users_auth(id) ON DELETE CASCADE,
+    failed_attempts INT NOT NULL DEFAULT 0,
+    locked_until TIMESTAMPTZ,
+    last_failed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_login_throttles_last_failed_at ON login_throttles(last_failed_at);
@@ -0,0 +1,7 @@
+-- Self-service account deletion: accounts are soft-deleted first and purged after a grace period
+ALTER TABLE users_auth ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ NULL;
+
+-- Accounts deleted before this column existed start their grace period now
+UPDATE users_auth SET deleted_at = NOW() WHERE is_deleted = TRUE AND deleted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_users_auth_deleted_at ON users_auth(deleted_at) WHERE is_deleted = TRUE;
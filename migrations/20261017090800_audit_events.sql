@@ -0,0 +1,17 @@
+-- Audit trail of security-relevant actions, starting with admin user management
+CREATE TABLE IF NOT EXISTS audit_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_type VARCHAR(64) NOT NULL,
+    -- Who performed the action (NULL for anonymous requests or purged accounts)
+    actor_user_id UUID REFERENCES users_auth(id) ON DELETE SET NULL,
+    -- Whose account the action concerns
+    subject_user_id UUID REFERENCES users_auth(id) ON DELETE SET NULL,
+    ip_address VARCHAR(45),
+    user_agent VARCHAR(512),
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_events_subject ON audit_events(subject_user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_events_actor ON audit_events(actor_user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_events_created_at ON audit_events(created_at);
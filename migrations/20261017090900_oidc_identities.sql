@@ -0,0 +1,32 @@
+-- Accounts created through an external sign-in provider have no password until one is set
+ALTER TABLE users_auth ALTER COLUMN password_hash DROP NOT NULL;
+
+-- Links an account to a subject at an OpenID Connect provider
+CREATE TABLE IF NOT EXISTS user_identities (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users_auth(id) ON DELETE CASCADE,
+    provider VARCHAR(50) NOT NULL,
+    -- The provider's stable `sub` claim; emails can change, this cannot
+    subject VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_login_at TIMESTAMPTZ,
+    UNIQUE (provider, subject),
+    UNIQUE (user_id, provider)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_identities_user_id ON user_identities(user_id);
+
+-- Pending authorization-code requests, keyed by the hash of the `state` parameter
+CREATE TABLE IF NOT EXISTS oidc_auth_requests (
+    state_hash VARCHAR(64) PRIMARY KEY,
+    provider VARCHAR(50) NOT NULL,
+    code_verifier VARCHAR(128) NOT NULL,
+    nonce VARCHAR(128) NOT NULL,
+    -- Set when a signed-in user is linking the provider to their account
+    link_user_id UUID REFERENCES users_auth(id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_oidc_auth_requests_expires_at ON oidc_auth_requests(expires_at);
@@ -0,0 +1,2 @@
+-- Access tokens embed the version current at issuance; bumping it revokes all of them at once
+ALTER TABLE users_auth ADD COLUMN IF NOT EXISTS token_version INTEGER NOT NULL DEFAULT 0;
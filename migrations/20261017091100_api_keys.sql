@@ -0,0 +1,17 @@
+-- Long-lived personal API keys for scripts and integrations
+CREATE TABLE IF NOT EXISTS api_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users_auth(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    -- Leading characters of the key, shown so users can tell their keys apart
+    prefix VARCHAR(16) NOT NULL,
+    -- SHA-256 of the full key; the key itself is only shown once at creation
+    secret_hash VARCHAR(64) NOT NULL UNIQUE,
+    scopes TEXT[] NOT NULL,
+    last_used_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_user_id ON api_keys(user_id) WHERE revoked_at IS NULL;
@@ -0,0 +1,30 @@
+-- Passkeys (WebAuthn credentials) a user can sign in with instead of a password
+CREATE TABLE IF NOT EXISTS webauthn_credentials (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users_auth(id) ON DELETE CASCADE,
+    credential_id BYTEA NOT NULL UNIQUE,
+    -- COSE_Key as sent by the authenticator at registration
+    public_key BYTEA NOT NULL,
+    algorithm INTEGER NOT NULL,
+    -- Last signature counter; a counter that does not increase hints at a cloned authenticator
+    sign_count BIGINT NOT NULL DEFAULT 0,
+    transports TEXT[] NOT NULL DEFAULT '{}',
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_webauthn_credentials_user_id ON webauthn_credentials(user_id);
+
+-- Outstanding registration and sign-in challenges, keyed by the hash of the challenge
+CREATE TABLE IF NOT EXISTS webauthn_challenges (
+    challenge_hash VARCHAR(64) PRIMARY KEY,
+    -- 'registration' or 'authentication'
+    purpose VARCHAR(20) NOT NULL,
+    -- Set for registrations, which are started by a signed-in user
+    user_id UUID REFERENCES users_auth(id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webauthn_challenges_expires_at ON webauthn_challenges(expires_at);
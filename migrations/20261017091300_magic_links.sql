@@ -0,0 +1,23 @@
+-- Single-use sign-in links sent by email
+CREATE TABLE IF NOT EXISTS magic_link_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users_auth(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_magic_link_tokens_user_id ON magic_link_tokens(user_id);
+
+-- Recent link requests for rate limiting, recorded whether or not an account exists
+-- so limits do not reveal which addresses are registered. The address is stored hashed.
+CREATE TABLE IF NOT EXISTS magic_link_requests (
+    id BIGSERIAL PRIMARY KEY,
+    email_hash VARCHAR(64) NOT NULL,
+    ip_address VARCHAR(45),
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_magic_link_requests_email ON magic_link_requests(email_hash, requested_at);
+CREATE INDEX IF NOT EXISTS idx_magic_link_requests_ip ON magic_link_requests(ip_address, requested_at);
@@ -0,0 +1,36 @@
+-- Canonical form of the address (lowercase, punycode domain, optional provider rules),
+-- used for lookups and uniqueness. The address as entered stays in `email` for delivery.
+ALTER TABLE users_auth ADD COLUMN IF NOT EXISTS email_normalized VARCHAR(255);
+
+-- Set on an account whose address normalizes to one already owned by another account.
+-- It cannot sign in by email until support resolves the duplicate.
+ALTER TABLE users_auth ADD COLUMN IF NOT EXISTS email_conflict_with UUID
+    REFERENCES users_auth(id) ON DELETE SET NULL;
+
+-- Existing accounts that differ only in letter case cannot all keep their address:
+-- the most recently active one gets it, the others are flagged as conflicting with it.
+-- The application re-normalizes stored addresses with its full rules on startup.
+CREATE TEMPORARY TABLE email_owners ON COMMIT DROP AS
+SELECT DISTINCT ON (LOWER(TRIM(u.email)))
+    LOWER(TRIM(u.email)) AS email_normalized,
+    u.id AS user_id
+FROM users_auth u
+LEFT JOIN (
+    SELECT user_id, MAX(last_used_at) AS last_used_at
+    FROM refresh_tokens
+    GROUP BY user_id
+) t ON t.user_id = u.id
+ORDER BY LOWER(TRIM(u.email)), GREATEST(u.updated_at, t.last_used_at) DESC, u.created_at DESC, u.id;
+
+UPDATE users_auth u
+SET email_normalized = o.email_normalized
+FROM email_owners o
+WHERE o.user_id = u.id;
+
+UPDATE users_auth u
+SET email_conflict_with = o.user_id
+FROM email_owners o
+WHERE o.email_normalized = LOWER(TRIM(u.email))
+  AND o.user_id <> u.id;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_auth_email_normalized ON users_auth(email_normalized);
@@ -0,0 +1,3 @@
+-- Distinguishes tokens revoked by a sign-out from tokens consumed by rotation: only replay of a
+-- rotated token is treated as theft. Tokens used before this migration count as rotated.
+ALTER TABLE refresh_tokens ADD COLUMN IF NOT EXISTS revoked_at TIMESTAMPTZ;
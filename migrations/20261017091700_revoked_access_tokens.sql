@@ -0,0 +1,9 @@
+-- Access tokens revoked one at a time (e.g. on logout), by `jti`. An entry is only needed
+-- until the token itself expires.
+CREATE TABLE IF NOT EXISTS revoked_access_tokens (
+    jti VARCHAR(64) PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users_auth(id) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_revoked_access_tokens_user_id ON revoked_access_tokens(user_id, expires_at);
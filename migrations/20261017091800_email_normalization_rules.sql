@@ -0,0 +1,10 @@
+-- Normalization rules the stored addresses were last brought in line with (a single row),
+-- so startup only revisits every address when the rules change
+CREATE TABLE IF NOT EXISTS email_normalization_state (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    provider_rules BOOLEAN NOT NULL
+);
+
+-- Addresses still waiting to be normalized; accounts flagged as conflicting are left to support
+CREATE INDEX IF NOT EXISTS idx_users_auth_email_unnormalized ON users_auth(id)
+    WHERE email_normalized IS NULL AND email_conflict_with IS NULL;
@@ -1,5 +1,11 @@
+use crate::constant::auth::{DEFAULT_ACCOUNT_DELETION_GRACE_DAYS, DEFAULT_JWT_LEEWAY_SECONDS};
+use crate::utils::jwt_keys::{JwtKeys, PublicKeyPem};
+use argon2::Params;
+use jsonwebtoken::Algorithm;
+use reqwest::Url;
 use std::env;
 use std::fmt;
+use std::path::PathBuf;
 
 #[derive(Debug)]
 pub enum ConfigError {
@@ -22,6 +28,7 @@ impl fmt::Display for ConfigError {
 
 impl std::error::Error for ConfigError {}
 
+#[derive(Clone)]
 pub struct R2Config {
     pub account_id: String,
     pub access_key_id: String,
@@ -30,19 +37,335 @@ pub struct R2Config {
     pub public_url: String,
 }
 
+/// How outgoing mail is delivered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MailTransport {
+    /// Write the message to the application log (local development)
+    Log,
+    /// Write each message as an .eml file into the outbox directory
+    File,
+}
+
+pub struct MailConfig {
+    pub transport: MailTransport,
+    pub from_address: String,
+    pub outbox_dir: String,
+}
+
+/// How our own tokens are signed and which deployment they belong to
+pub struct JwtConfig {
+    pub keys: JwtKeys,
+    /// `iss` set at issuance and required on decode
+    pub issuer: String,
+    /// `aud` set at issuance and required on decode
+    pub audience: String,
+    /// Tolerated clock drift when checking `exp` and `nbf`
+    pub leeway_seconds: u64,
+}
+
+/// How passwords are hashed. Stored hashes with other settings are upgraded on the next login.
+#[derive(Clone)]
+pub struct PasswordHashConfig {
+    /// Argon2id memory (KiB), iterations and parallelism for new hashes
+    pub params: Params,
+    /// Secret mixed into every new hash; kept out of the database so a leaked dump
+    /// cannot be cracked on its own. Hashes made with it no longer verify once it is removed.
+    pub pepper: Option<String>,
+}
+
+/// The relying party passkeys are registered for
+pub struct WebauthnConfig {
+    /// Domain the passkeys are bound to; the frontend must be served from it or a subdomain
+    pub rp_id: String,
+    /// Name shown by the browser when creating a passkey
+    pub rp_name: String,
+    /// Origins (scheme, host and port) the ceremonies may be performed from
+    pub origins: Vec<String>,
+}
+
+/// An OpenID Connect provider users can sign in with
+#[derive(Debug, Clone)]
+pub struct OidcProviderConfig {
+    /// Short name used in URLs and stored with linked identities, e.g. `google`
+    pub name: String,
+    /// Issuer URL; provider metadata is discovered from `{issuer}/.well-known/openid-configuration`
+    pub issuer: String,
+    pub client_id: String,
+    /// None for public clients that rely on PKCE alone
+    pub client_secret: Option<String>,
+    /// Frontend page the provider redirects back to with `code` and `state`
+    pub redirect_uri: String,
+    pub scopes: String,
+}
+
 pub struct Config {
     pub database_url: String,
-    pub jwt_secret: String,
+    pub jwt: JwtConfig,
+    pub password_hash: PasswordHashConfig,
+    /// Local copy of a breach corpus as k-anonymity range files that new passwords are
+    /// checked against. Without it only the strength rules apply.
+    pub breached_passwords_dir: Option<PathBuf>,
     pub cors_origins: Vec<String>,
+    /// Whether addresses that a known provider delivers to the same mailbox
+    /// (`+tags`, dots in Gmail addresses) count as the same account
+    pub email_provider_rules: bool,
+    /// Base URL of the frontend, used to build links sent by email
+    pub app_base_url: String,
+    /// Domain of the CSRF cookie, so frontends on sibling subdomains can read it.
+    /// Without it the cookie is host-only.
+    pub csrf_cookie_domain: Option<String>,
+    /// Issuer name shown in authenticator apps for TOTP enrolment
+    pub totp_issuer: String,
+    /// Days a deleted account can still be restored before it is purged
+    pub account_deletion_grace_days: i64,
     pub r2: R2Config,
+    pub mail: MailConfig,
+    pub oidc_providers: Vec<OidcProviderConfig>,
+    pub webauthn: WebauthnConfig,
+}
+
+/// Reads ARGON2_MEMORY_KIB, ARGON2_ITERATIONS and ARGON2_PARALLELISM (defaulting to the
+/// OWASP-recommended Argon2id settings) and the optional PASSWORD_PEPPER
+fn load_password_hash() -> Result<PasswordHashConfig, ConfigError> {
+    let read = |name: &str, default: u32| match env::var(name) {
+        Ok(value) => value.trim().parse::<u32>().map_err(|_| {
+            ConfigError::InvalidConfig(format!(
+                "{} must be a positive number, got '{}'",
+                name, value
+            ))
+        }),
+        Err(_) => Ok(default),
+    };
+
+    let params = Params::new(
+        read("ARGON2_MEMORY_KIB", Params::DEFAULT_M_COST)?,
+        read("ARGON2_ITERATIONS", Params::DEFAULT_T_COST)?,
+        read("ARGON2_PARALLELISM", Params::DEFAULT_P_COST)?,
+        None,
+    )
+    .map_err(|e| ConfigError::InvalidConfig(format!("Invalid Argon2 parameters: {}", e)))?;
+
+    let pepper = env::var("PASSWORD_PEPPER")
+        .ok()
+        .filter(|v| !v.trim().is_empty());
+
+    Ok(PasswordHashConfig { params, pepper })
+}
+
+/// Reads the providers listed in OIDC_PROVIDERS (comma separated).
+/// Each provider `name` is configured through OIDC_{NAME}_ISSUER, OIDC_{NAME}_CLIENT_ID,
+/// and optionally OIDC_{NAME}_CLIENT_SECRET, OIDC_{NAME}_REDIRECT_URI and OIDC_{NAME}_SCOPES.
+fn load_oidc_providers(app_base_url: &str) -> Result<Vec<OidcProviderConfig>, ConfigError> {
+    let names = env::var("OIDC_PROVIDERS").unwrap_or_default();
+    let mut providers: Vec<OidcProviderConfig> = Vec::new();
+
+    for name in names.split(',').map(|n| n.trim().to_lowercase()) {
+        if name.is_empty() {
+            continue;
+        }
+        if !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+        {
+            return Err(ConfigError::InvalidConfig(format!(
+                "OIDC provider name '{}' may only contain letters, digits, '-' and '_'",
+                name
+            )));
+        }
+        if providers.iter().any(|p| p.name == name) {
+            return Err(ConfigError::InvalidConfig(format!(
+                "OIDC provider '{}' is listed twice",
+                name
+            )));
+        }
+
+        let prefix = format!("OIDC_{}", name.to_uppercase().replace('-', "_"));
+        let required = |suffix: &str| {
+            let var = format!("{}_{}", prefix, suffix);
+            env::var(&var).map_err(|_| ConfigError::EnvVarMissing(var))
+        };
+        let optional = |suffix: &str| {
+            env::var(format!("{}_{}", prefix, suffix))
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        providers.push(OidcProviderConfig {
+            issuer: required("ISSUER")?.trim().trim_end_matches('/').to_string(),
+            client_id: required("CLIENT_ID")?.trim().to_string(),
+            client_secret: optional("CLIENT_SECRET"),
+            redirect_uri: optional("REDIRECT_URI")
+                .unwrap_or_else(|| format!("{}/auth/callback/{}", app_base_url, name)),
+            scopes: optional("SCOPES").unwrap_or_else(|| "openid email profile".to_string()),
+            name,
+        });
+    }
+
+    Ok(providers)
+}
+
+/// Reads the passkey relying party. WEBAUTHN_RP_ID defaults to the host of APP_BASE_URL and
+/// WEBAUTHN_ORIGINS (comma separated) to APP_BASE_URL itself.
+fn load_webauthn(app_base_url: &str) -> Result<WebauthnConfig, ConfigError> {
+    let optional = |var: &str| {
+        env::var(var)
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    let rp_id = match optional("WEBAUTHN_RP_ID") {
+        Some(rp_id) => rp_id.to_lowercase(),
+        None => Url::parse(app_base_url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .ok_or_else(|| {
+                ConfigError::InvalidConfig(format!(
+                    "Cannot derive WEBAUTHN_RP_ID from APP_BASE_URL '{}'",
+                    app_base_url
+                ))
+            })?,
+    };
+
+    let origins: Vec<String> = match optional("WEBAUTHN_ORIGINS") {
+        Some(origins) => origins
+            .split(',')
+            .map(|origin| origin.trim().trim_end_matches('/').to_string())
+            .filter(|origin| !origin.is_empty())
+            .collect(),
+        None => vec![app_base_url.to_string()],
+    };
+
+    // Browsers only allow an origin to use an RP ID equal to its host or a parent domain of it
+    for origin in &origins {
+        let host = Url::parse(origin)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .ok_or_else(|| {
+                ConfigError::InvalidConfig(format!("Invalid WEBAUTHN_ORIGINS entry '{}'", origin))
+            })?;
+        if host != rp_id && !host.ends_with(&format!(".{}", rp_id)) {
+            return Err(ConfigError::InvalidConfig(format!(
+                "WebAuthn origin '{}' is not on the relying party domain '{}'",
+                origin, rp_id
+            )));
+        }
+    }
+
+    Ok(WebauthnConfig {
+        rp_id,
+        rp_name: optional("WEBAUTHN_RP_NAME").unwrap_or_else(|| "web_be".to_string()),
+        origins,
+    })
+}
+
+/// Reads the token signing keys selected by JWT_ALGORITHM (HS256, RS256 or EdDSA).
+/// HS256 signs with JWT_SECRET. RS256/EdDSA sign with the PEM file at JWT_PRIVATE_KEY_PATH under
+/// the key ID JWT_SIGNING_KEY_ID, and verify with JWT_PUBLIC_KEYS (comma separated `kid=path` pairs).
+/// A JWT_SECRET set alongside an asymmetric algorithm only keeps older HS256 tokens valid.
+fn load_jwt_keys() -> Result<JwtKeys, ConfigError> {
+    let secret = env::var("JWT_SECRET").ok().filter(|s| !s.is_empty());
+    let algorithm = env::var("JWT_ALGORITHM").unwrap_or_else(|_| "HS256".to_string());
+
+    let algorithm = match algorithm.trim().to_uppercase().as_str() {
+        "HS256" => {
+            let secret =
+                secret.ok_or_else(|| ConfigError::EnvVarMissing("JWT_SECRET".to_string()))?;
+            return Ok(JwtKeys::hs256(&secret));
+        }
+        "RS256" => Algorithm::RS256,
+        "EDDSA" => Algorithm::EdDSA,
+        other => {
+            return Err(ConfigError::InvalidConfig(format!(
+                "Unknown JWT_ALGORITHM '{}'. Expected 'HS256', 'RS256' or 'EdDSA'",
+                other
+            )));
+        }
+    };
+
+    let required = |var: &str| {
+        env::var(var)
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| ConfigError::EnvVarMissing(var.to_string()))
+    };
+    let read_pem = |path: &str| {
+        std::fs::read(path).map_err(|e| {
+            ConfigError::InvalidConfig(format!("Cannot read key file '{}': {}", path, e))
+        })
+    };
+
+    let signing_kid = required("JWT_SIGNING_KEY_ID")?;
+    let private_pem = read_pem(&required("JWT_PRIVATE_KEY_PATH")?)?;
+
+    let mut public_keys = Vec::new();
+    for entry in required("JWT_PUBLIC_KEYS")?.split(',').map(str::trim) {
+        if entry.is_empty() {
+            continue;
+        }
+        let (kid, path) = entry
+            .split_once('=')
+            .map(|(kid, path)| (kid.trim(), path.trim()))
+            .filter(|(kid, path)| !kid.is_empty() && !path.is_empty())
+            .ok_or_else(|| {
+                ConfigError::InvalidConfig(format!(
+                    "JWT_PUBLIC_KEYS entry '{}' must have the form kid=path",
+                    entry
+                ))
+            })?;
+        public_keys.push(PublicKeyPem {
+            kid: kid.to_string(),
+            pem: read_pem(path)?,
+        });
+    }
+
+    JwtKeys::asymmetric(
+        algorithm,
+        &signing_kid,
+        &private_pem,
+        &public_keys,
+        secret.as_deref(),
+    )
+    .map_err(ConfigError::InvalidConfig)
 }
 
 impl Config {
     pub fn init() -> Result<Config, ConfigError> {
         let database_url = env::var("DATABASE_URL")
             .map_err(|_| ConfigError::EnvVarMissing("DATABASE_URL".to_string()))?;
-        let jwt_secret = env::var("JWT_SECRET")
-            .map_err(|_| ConfigError::EnvVarMissing("JWT_SECRET".to_string()))?;
+        let jwt_leeway_seconds = match env::var("JWT_LEEWAY_SECONDS") {
+            Ok(value) => value.trim().parse::<u64>().map_err(|_| {
+                ConfigError::InvalidConfig(format!(
+                    "JWT_LEEWAY_SECONDS must be a non-negative number of seconds, got '{}'",
+                    value
+                ))
+            })?,
+            Err(_) => DEFAULT_JWT_LEEWAY_SECONDS,
+        };
+        let jwt = JwtConfig {
+            keys: load_jwt_keys()?,
+            issuer: env::var("JWT_ISSUER").unwrap_or_else(|_| "web_be".to_string()),
+            audience: env::var("JWT_AUDIENCE").unwrap_or_else(|_| "web_be".to_string()),
+            leeway_seconds: jwt_leeway_seconds,
+        };
+
+        let password_hash = load_password_hash()?;
+
+        let breached_passwords_dir = match env::var("BREACHED_PASSWORDS_DIR") {
+            Ok(value) if !value.trim().is_empty() => {
+                let dir = PathBuf::from(value.trim());
+                if !dir.is_dir() {
+                    return Err(ConfigError::InvalidConfig(format!(
+                        "BREACHED_PASSWORDS_DIR '{}' is not a directory",
+                        dir.display()
+                    )));
+                }
+                Some(dir)
+            }
+            _ => None,
+        };
 
         let cors_origins = env::var("CORS_ORIGINS")
             .unwrap_or_else(|_| "http://localhost:3000".to_string())
@@ -62,6 +385,42 @@ impl Config {
             }
         }
 
+        let app_base_url = env::var("APP_BASE_URL")
+            .unwrap_or_else(|_| "http://localhost:3000".to_string())
+            .trim_end_matches('/')
+            .to_string();
+
+        let email_provider_rules = match env::var("EMAIL_PROVIDER_RULES") {
+            Ok(value) => match value.trim().to_lowercase().as_str() {
+                "true" | "1" => true,
+                "false" | "0" => false,
+                _ => {
+                    return Err(ConfigError::InvalidConfig(format!(
+                        "EMAIL_PROVIDER_RULES must be 'true' or 'false', got '{}'",
+                        value
+                    )));
+                }
+            },
+            Err(_) => false,
+        };
+
+        let csrf_cookie_domain = env::var("CSRF_COOKIE_DOMAIN")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+
+        let totp_issuer = env::var("TOTP_ISSUER").unwrap_or_else(|_| "web_be".to_string());
+
+        let account_deletion_grace_days = match env::var("ACCOUNT_DELETION_GRACE_DAYS") {
+            Ok(value) => value.trim().parse::<i64>().ok().filter(|d| *d >= 0).ok_or_else(|| {
+                ConfigError::InvalidConfig(format!(
+                    "ACCOUNT_DELETION_GRACE_DAYS must be a non-negative number of days, got '{}'",
+                    value
+                ))
+            })?,
+            Err(_) => DEFAULT_ACCOUNT_DELETION_GRACE_DAYS,
+        };
+
         let r2 = R2Config {
             account_id: env::var("R2_ACCOUNT_ID")
                 .map_err(|_| ConfigError::EnvVarMissing("R2_ACCOUNT_ID".to_string()))?,
@@ -75,11 +434,50 @@ impl Config {
                 .map_err(|_| ConfigError::EnvVarMissing("R2_PUBLIC_URL".to_string()))?,
         };
 
+        let transport = match env::var("MAIL_TRANSPORT")
+            .unwrap_or_else(|_| "log".to_string())
+            .to_lowercase()
+            .as_str()
+        {
+            "log" => MailTransport::Log,
+            "file" => MailTransport::File,
+            other => {
+                return Err(ConfigError::InvalidConfig(format!(
+                    "Unknown MAIL_TRANSPORT '{}'. Expected 'log' or 'file'",
+                    other
+                )));
+            }
+        };
+
+        let mail = MailConfig {
+            transport,
+            from_address: env::var("MAIL_FROM")
+                .unwrap_or_else(|_| "no-reply@localhost".to_string()),
+            outbox_dir: env::var("MAIL_OUTBOX_DIR").unwrap_or_else(|_| "mail_outbox".to_string()),
+        };
+
+        let oidc_providers = load_oidc_providers(&app_base_url)?;
+        let webauthn = load_webauthn(&app_base_url)?;
+
         Ok(Config {
             database_url,
-            jwt_secret,
+            jwt,
+            password_hash,
+            breached_passwords_dir,
             cors_origins,
+            email_provider_rules,
+            app_base_url,
+            csrf_cookie_domain,
+            totp_issuer,
+            account_deletion_grace_days,
             r2,
+            mail,
+            oidc_providers,
+            webauthn,
         })
     }
+
+    pub fn oidc_provider(&self, name: &str) -> Option<&OidcProviderConfig> {
+        self.oidc_providers.iter().find(|p| p.name == name)
+    }
 }
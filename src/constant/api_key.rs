@@ -0,0 +1,6 @@
+pub const API_KEY_HEADER: &str = "x-api-key";
+pub const API_KEY_PREFIX: &str = "wbk_"; // Makes leaked keys easy to recognize in logs and secret scanners
+pub const API_KEY_DISPLAY_PREFIX_LENGTH: usize = 12; // Characters of the key kept for display
+pub const MAX_API_KEYS_PER_USER: i64 = 20;
+pub const MAX_API_KEY_LIFETIME_DAYS: i64 = 365;
+pub const API_KEY_LAST_USED_RESOLUTION_SECONDS: i64 = 60; // Avoids a write on every request
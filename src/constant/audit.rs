@@ -0,0 +1,2 @@
+pub const AUDIT_EVENT_RETENTION_DAYS: i64 = 365;
+pub const AUDIT_QUEUE_CAPACITY: usize = 1024; // Events beyond this are dropped instead of blocking requests
@@ -1,4 +1,38 @@
 pub const MIN_PASSWORD_LENGTH: usize = 8;
+pub const MIN_PASSWORD_SCORE: u8 = 3; // zxcvbn score (0-4): at least 10^8 guesses to crack
 pub const REFRESH_TOKEN_DURATION_DAYS: i64 = 7;
+pub const ACCESS_TOKEN_DURATION_MINUTES: i64 = 60;
 pub const ACCESS_TOKEN_COOKIE_NAME: &str = "access_token";
 pub const REFRESH_TOKEN_COOKIE_NAME: &str = "refresh_token";
+pub const CSRF_COOKIE_NAME: &str = "csrf_token"; // Readable by scripts, echoed back in CSRF_HEADER
+pub const CSRF_HEADER: &str = "x-csrf-token";
+pub const CLIENT_TYPE_HEADER: &str = "x-client-type";
+pub const NATIVE_CLIENT_TYPE: &str = "native"; // Tokens are returned in the body instead of cookies
+pub const REFRESH_TOKEN_HEADER: &str = "x-refresh-token"; // Identifies a native client's session on account requests
+pub const EMAIL_VERIFICATION_TOKEN_DURATION_HOURS: i64 = 24;
+pub const PASSWORD_RESET_TOKEN_DURATION_MINUTES: i64 = 30;
+pub const MAGIC_LINK_TOKEN_DURATION_MINUTES: i64 = 15;
+pub const MAGIC_LINK_RATE_LIMIT_WINDOW_MINUTES: i64 = 60;
+pub const MAGIC_LINK_MAX_REQUESTS_PER_EMAIL: i64 = 3; // Per window, whether or not an account exists
+pub const MAGIC_LINK_MAX_REQUESTS_PER_IP: i64 = 10; // Per window
+pub const TOTP_DIGITS: u32 = 6;
+pub const TOTP_STEP_SECONDS: u64 = 30;
+pub const TOTP_SKEW_STEPS: u64 = 1; // Accept codes from one step before/after to tolerate clock drift
+pub const MFA_PENDING_TOKEN_DURATION_MINUTES: i64 = 5;
+pub const MFA_RECOVERY_CODE_COUNT: usize = 10;
+pub const LOGIN_LOCKOUT_THRESHOLD: i32 = 5; // Failed attempts allowed before backoff starts
+pub const LOGIN_LOCKOUT_BASE_SECONDS: i64 = 30; // Doubles with every further failure
+pub const LOGIN_LOCKOUT_MAX_SECONDS: i64 = 60 * 60;
+pub const LOGIN_FAILURE_WINDOW_HOURS: i64 = 24; // Failures older than this are forgotten
+pub const USER_STATUS_CACHE_TTL_SECONDS: u64 = 30; // How long a suspension may take to reach other instances
+pub const USER_STATUS_CACHE_MAX_ENTRIES: usize = 10_000;
+pub const DEFAULT_ACCOUNT_DELETION_GRACE_DAYS: i64 = 30;
+pub const ACCOUNT_RESTORE_TOKEN_DURATION_MINUTES: i64 = 10;
+pub const ACCOUNT_DELETION_TOKEN_DURATION_MINUTES: i64 = 15; // Confirms deletion of an account without a password
+pub const OIDC_AUTH_REQUEST_DURATION_MINUTES: i64 = 10; // Time allowed to complete the provider's sign-in page
+pub const OIDC_METADATA_CACHE_TTL_SECONDS: u64 = 60 * 60; // Discovery documents and signing keys
+pub const OIDC_HTTP_TIMEOUT_SECONDS: u64 = 10;
+pub const DEFAULT_JWT_LEEWAY_SECONDS: u64 = 60; // Tolerated clock drift for our own tokens
+pub const OIDC_ID_TOKEN_LEEWAY_SECONDS: u64 = 60; // Tolerated clock drift against the provider
+pub const WEBAUTHN_CHALLENGE_DURATION_MINUTES: i64 = 5; // Also sent to the browser as the ceremony timeout
+pub const MAX_PASSKEYS_PER_USER: i64 = 10;
@@ -0,0 +1,3 @@
+pub const EXPORT_PREFIX: &str = "exports"; // Private prefix: never served through the public bucket URL
+pub const EXPORT_RETENTION_DAYS: i64 = 7;
+pub const EXPORT_DOWNLOAD_URL_EXPIRY_SECONDS: u64 = 15 * 60;
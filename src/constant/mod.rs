@@ -1,2 +1,5 @@
+pub mod api_key;
+pub mod audit;
 pub mod auth;
+pub mod export;
 pub mod image;
@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::role::Role;
+
+#[derive(Debug, Deserialize)]
+pub struct SearchUsersQuery {
+    /// Matched against email and full name
+    pub q: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminUserSummary {
+    pub id: Uuid,
+    pub email: String,
+    pub role: String,
+    pub is_active: bool,
+    pub is_deleted: bool,
+    pub full_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaginatedUsersResponse {
+    pub data: Vec<AdminUserSummary>,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FriendCountsResponse {
+    pub friends: i64,
+    pub pending_received: i64,
+    pub pending_sent: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminUserDetailsResponse {
+    pub id: Uuid,
+    pub email: String,
+    pub role: String,
+    pub is_active: bool,
+    pub is_deleted: bool,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    /// Set when another account owns this account's normalized address
+    pub email_conflict_with: Option<Uuid>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub full_name: Option<String>,
+    pub bio: Option<String>,
+    pub avatar_url: Option<String>,
+    pub friend_counts: FriendCountsResponse,
+    pub active_sessions: usize,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeRoleRequest {
+    pub role: Role,
+}
@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    constant::api_key::MAX_API_KEY_LIFETIME_DAYS,
+    models::api_key::{ApiKeyModel, ApiKeyScope},
+};
+
+#[derive(Deserialize, Validate)]
+pub struct CreateApiKeyRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: String,
+    #[validate(length(min = 1, message = "At least one scope is required"))]
+    pub scopes: Vec<ApiKeyScope>,
+    /// Days until the key stops working; keys without one never expire
+    #[validate(range(
+        min = 1,
+        max = MAX_API_KEY_LIFETIME_DAYS,
+        message = "Expiry must be between 1 and 365 days"
+    ))]
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiKeyResponse {
+    pub id: Uuid,
+    pub name: String,
+    /// Leading characters of the key, to tell keys apart
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<ApiKeyModel> for ApiKeyResponse {
+    fn from(key: ApiKeyModel) -> Self {
+        Self {
+            id: key.id,
+            name: key.name,
+            prefix: key.prefix,
+            scopes: key.scopes,
+            created_at: key.created_at,
+            last_used_at: key.last_used_at,
+            expires_at: key.expires_at,
+        }
+    }
+}
+
+/// Returned once when a key is created; the secret cannot be retrieved later
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKeyResponse {
+    #[serde(flatten)]
+    pub key: ApiKeyResponse,
+    pub secret: String,
+}
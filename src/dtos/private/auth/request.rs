@@ -19,4 +19,129 @@ pub struct LoginRequest {
     pub password: String,
     #[serde(default)]
     pub remember_me: bool,
+    /// Optional human-readable name for this session (e.g. "Work laptop")
+    #[validate(length(max = 100, message = "Device name must not exceed 100 characters"))]
+    pub device_name: Option<String>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct VerifyEmailRequest {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ResendVerificationRequest {
+    #[validate(email(message = "Invalid email format"))]
+    pub email: String,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ForgotPasswordRequest {
+    #[validate(email(message = "Invalid email format"))]
+    pub email: String,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct MagicLinkRequest {
+    #[validate(email(message = "Invalid email format"))]
+    pub email: String,
+}
+
+/// Query of `GET /magic-link/consume`, which only leads to the confirmation page
+#[derive(Deserialize, Validate)]
+pub struct MagicLinkQuery {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+}
+
+/// Body of `POST /magic-link/consume`, sent when the user confirms on the page the emailed link opens
+#[derive(Deserialize, Validate)]
+pub struct ConsumeMagicLinkRequest {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+    #[serde(default)]
+    pub remember_me: bool,
+    #[validate(length(max = 100, message = "Device name must not exceed 100 characters"))]
+    pub device_name: Option<String>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ResetPasswordRequest {
+    #[validate(length(min = 1, message = "Token is required"))]
+    pub token: String,
+    #[validate(custom(function = "validate_password_strength"))]
+    pub new_password: String,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct MfaLoginRequest {
+    #[validate(length(min = 1, message = "MFA token is required"))]
+    pub mfa_token: String,
+    /// A TOTP code from the authenticator app or an unused recovery code
+    #[validate(length(min = 1, max = 32, message = "Authentication code is required"))]
+    pub code: String,
+    #[serde(default)]
+    pub remember_me: bool,
+    #[validate(length(max = 100, message = "Device name must not exceed 100 characters"))]
+    pub device_name: Option<String>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct RestoreAccountRequest {
+    #[validate(length(min = 1, message = "Restore token is required"))]
+    pub restore_token: String,
+    #[serde(default)]
+    pub remember_me: bool,
+    #[validate(length(max = 100, message = "Device name must not exceed 100 characters"))]
+    pub device_name: Option<String>,
+}
+
+/// Native clients send their refresh token in the body instead of a cookie
+#[derive(Deserialize, Validate)]
+pub struct RefreshTokenRequest {
+    #[validate(length(min = 1, message = "Refresh token is required"))]
+    pub refresh_token: String,
+}
+
+/// Sent by the frontend page the provider redirected back to
+#[derive(Deserialize, Validate)]
+pub struct OidcCallbackRequest {
+    #[validate(length(min = 1, max = 2048, message = "Authorization code is required"))]
+    pub code: String,
+    #[validate(length(min = 1, max = 256, message = "State is required"))]
+    pub state: String,
+    #[serde(default)]
+    pub remember_me: bool,
+    #[validate(length(max = 100, message = "Device name must not exceed 100 characters"))]
+    pub device_name: Option<String>,
+}
+
+/// The authenticator's answer, as produced by `PublicKeyCredential.toJSON()`
+#[derive(Deserialize)]
+pub struct AssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    #[serde(rename = "authenticatorData")]
+    pub authenticator_data: String,
+    pub signature: String,
+    #[serde(rename = "userHandle")]
+    pub user_handle: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AssertionCredential {
+    /// Base64url credential ID
+    #[serde(rename = "rawId")]
+    pub raw_id: String,
+    pub response: AssertionResponse,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct PasskeyLoginRequest {
+    pub credential: AssertionCredential,
+    #[serde(default)]
+    pub remember_me: bool,
+    #[validate(length(max = 100, message = "Device name must not exceed 100 characters"))]
+    pub device_name: Option<String>,
 }
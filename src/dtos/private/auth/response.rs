@@ -1,7 +1,69 @@
-use crate::models::user::UserModel;
+use crate::{dtos::private::passkey::CredentialDescriptorResponse, models::user::UserModel};
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 
 #[derive(Serialize)]
 pub struct AuthResponse {
     pub user: UserModel,
 }
+
+/// Returned to native clients instead of auth cookies
+#[derive(Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: &'static str,
+    /// Seconds until the access token expires
+    pub expires_in: i64,
+    pub user: UserModel,
+}
+
+/// New access token for the current session, returned to native clients after a change
+/// that revoked the user's access tokens
+#[derive(Debug, Serialize)]
+pub struct AccessTokenResponse {
+    pub access_token: String,
+    pub token_type: &'static str,
+    /// Seconds until the access token expires
+    pub expires_in: i64,
+}
+
+/// Returned by login instead of auth cookies when a second factor is required
+#[derive(Serialize)]
+pub struct MfaRequiredResponse {
+    pub mfa_required: bool,
+    pub mfa_token: String,
+}
+
+/// Returned by login instead of auth cookies when the account is pending deletion
+#[derive(Serialize)]
+pub struct AccountRestoreResponse {
+    pub restore_available: bool,
+    pub restore_token: String,
+    pub purge_at: DateTime<Utc>,
+}
+
+/// Provider page the browser should be sent to
+#[derive(Serialize)]
+pub struct OidcAuthorizationResponse {
+    pub authorization_url: String,
+}
+
+#[derive(Serialize)]
+pub struct OidcProviderResponse {
+    pub name: String,
+}
+
+/// `PublicKeyCredentialRequestOptions` in the JSON form accepted by
+/// `PublicKeyCredential.parseRequestOptionsFromJSON()`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyLoginOptionsResponse {
+    pub challenge: String,
+    pub rp_id: String,
+    /// Milliseconds
+    pub timeout: i64,
+    pub user_verification: &'static str,
+    /// Always empty: the browser offers the discoverable passkeys it holds for the site
+    pub allow_credentials: Vec<CredentialDescriptorResponse>,
+}
@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::data_export::DataExportModel;
+
+#[derive(Debug, Serialize)]
+pub struct DataExportResponse {
+    pub id: Uuid,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Time-limited link to the archive, present once the export has completed
+    pub download_url: Option<String>,
+}
+
+impl DataExportResponse {
+    pub fn new(export: DataExportModel, download_url: Option<String>) -> Self {
+        Self {
+            id: export.id,
+            status: export.status,
+            created_at: export.created_at,
+            completed_at: export.completed_at,
+            expires_at: export.expires_at,
+            download_url,
+        }
+    }
+}
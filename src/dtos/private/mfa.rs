@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Serialize)]
+pub struct MfaStatusResponse {
+    pub enabled: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TotpSetupResponse {
+    /// Base32 secret for manual entry into an authenticator app
+    pub secret: String,
+    /// `otpauth://` URI, usually rendered as a QR code by the client
+    pub otpauth_uri: String,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ConfirmTotpRequest {
+    #[validate(length(min = 1, max = 32, message = "Authentication code is required"))]
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoveryCodesResponse {
+    /// Shown only once; each code can be used a single time instead of a TOTP code
+    pub recovery_codes: Vec<String>,
+}
+
+/// Disabling requires re-authentication with either a fresh code or the password
+#[derive(Deserialize, Validate)]
+pub struct DisableTotpRequest {
+    #[validate(length(min = 1, max = 32, message = "Authentication code must not be empty"))]
+    pub code: Option<String>,
+    #[validate(length(min = 1, message = "Password must not be empty"))]
+    pub password: Option<String>,
+}
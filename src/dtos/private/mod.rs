@@ -1,2 +1,7 @@
+pub mod admin;
+pub mod api_key;
 pub mod auth;
+pub mod export;
+pub mod mfa;
+pub mod passkey;
 pub mod user;
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::webauthn::WebauthnCredentialModel;
+
+/// Relying party of `PublicKeyCredentialCreationOptions`
+#[derive(Debug, Serialize)]
+pub struct RelyingPartyResponse {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyUserResponse {
+    /// Base64url user handle
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CredentialParameterResponse {
+    #[serde(rename = "type")]
+    pub credential_type: &'static str,
+    pub alg: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CredentialDescriptorResponse {
+    #[serde(rename = "type")]
+    pub credential_type: &'static str,
+    /// Base64url credential ID
+    pub id: String,
+    pub transports: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorSelectionResponse {
+    pub resident_key: &'static str,
+    pub require_resident_key: bool,
+    pub user_verification: &'static str,
+}
+
+/// `PublicKeyCredentialCreationOptions` in the JSON form accepted by
+/// `PublicKeyCredential.parseCreationOptionsFromJSON()`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyRegistrationOptionsResponse {
+    pub challenge: String,
+    pub rp: RelyingPartyResponse,
+    pub user: PasskeyUserResponse,
+    pub pub_key_cred_params: Vec<CredentialParameterResponse>,
+    /// Milliseconds
+    pub timeout: i64,
+    pub exclude_credentials: Vec<CredentialDescriptorResponse>,
+    pub authenticator_selection: AuthenticatorSelectionResponse,
+    pub attestation: &'static str,
+}
+
+/// The authenticator's answer, as produced by `PublicKeyCredential.toJSON()`
+#[derive(Deserialize)]
+pub struct AttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    #[serde(rename = "attestationObject")]
+    pub attestation_object: String,
+    #[serde(default)]
+    pub transports: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RegistrationCredential {
+    pub response: AttestationResponse,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct RegisterPasskeyRequest {
+    /// Defaults to "Passkey"
+    #[validate(length(max = 100, message = "Name must not exceed 100 characters"))]
+    pub name: Option<String>,
+    pub credential: RegistrationCredential,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct RenamePasskeyRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PasskeyResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub transports: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<WebauthnCredentialModel> for PasskeyResponse {
+    fn from(credential: WebauthnCredentialModel) -> Self {
+        Self {
+            id: credential.id,
+            name: credential.name,
+            transports: credential.transports,
+            created_at: credential.created_at,
+            last_used_at: credential.last_used_at,
+        }
+    }
+}
@@ -1,6 +1,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    dtos::private::auth::response::AccessTokenResponse,
+    utils::validation::validate_password_strength,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserMeResponse {
@@ -21,3 +27,89 @@ pub struct UpdateProfileResponse {
     pub avatar_url: Option<String>,
     pub updated_at: DateTime<Utc>,
 }
+
+#[derive(Deserialize, Validate)]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 1, message = "Current password is required"))]
+    pub current_password: String,
+    #[validate(custom(function = "validate_password_strength"))]
+    pub new_password: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionResponse {
+    pub id: Uuid,
+    pub device_name: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// True for the session making this request
+    pub current: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevokeSessionsResponse {
+    pub revoked: u64,
+    /// New access token of the current session, for native clients
+    #[serde(flatten)]
+    pub access_token: Option<AccessTokenResponse>,
+}
+
+/// Accounts with a password send it; accounts without one send the token from the
+/// confirmation email instead
+#[derive(Deserialize, Validate)]
+pub struct DeleteAccountRequest {
+    #[validate(length(min = 1, message = "Password is required"))]
+    pub password: Option<String>,
+    #[validate(length(min = 1, message = "Confirmation token is required"))]
+    pub confirmation_token: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountDeletionResponse {
+    /// The account can be restored by signing in again until this time
+    pub purge_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SecurityEventsQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SecurityEventResponse {
+    pub id: Uuid,
+    pub event_type: String,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    /// True when someone else, e.g. an admin, acted on this account
+    pub by_admin: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaginatedSecurityEventsResponse {
+    pub data: Vec<SecurityEventResponse>,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct LinkIdentityRequest {
+    #[validate(length(min = 1, max = 2048, message = "Authorization code is required"))]
+    pub code: String,
+    #[validate(length(min = 1, max = 256, message = "State is required"))]
+    pub state: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IdentityResponse {
+    pub provider: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_login_at: Option<DateTime<Utc>>,
+}
@@ -1,7 +1,12 @@
-use axum::{Json, http::StatusCode, response::IntoResponse};
+use axum::{
+    Json,
+    http::{HeaderValue, StatusCode, header},
+    response::IntoResponse,
+};
 use serde::Serialize;
 use std::borrow::Cow;
 use thiserror::Error;
+use uuid::Uuid;
 
 #[derive(Debug, Error)]
 pub enum AuthError {
@@ -23,6 +28,55 @@ pub enum AuthError {
     InvalidTokenType,
     #[error("Validation error: {0}")]
     ValidationError(String),
+    #[error("Email not verified")]
+    EmailNotVerified,
+    #[error("Invalid or expired token")]
+    InvalidOrExpiredToken,
+    #[error("Incorrect password")]
+    IncorrectPassword,
+    #[error("Invalid authentication code")]
+    InvalidMfaCode,
+    #[error("Two-factor authentication is already enabled")]
+    MfaAlreadyEnabled,
+    #[error("Two-factor authentication is not enabled")]
+    MfaNotEnabled,
+    /// The refresh token is malformed, unknown, expired or belongs to a revoked session
+    #[error("Invalid refresh token")]
+    InvalidRefreshToken,
+    /// A rotated refresh token was presented again; its family has been revoked
+    #[error("Refresh token reuse detected")]
+    RefreshTokenReused { user_id: Uuid },
+    #[error("Account suspended")]
+    AccountSuspended,
+    #[error("Account deleted")]
+    AccountDeleted,
+    #[error("Too many failed login attempts, retry after {retry_after_secs} seconds")]
+    TooManyLoginAttempts { retry_after_secs: u64 },
+    #[error("Too many requests, retry after {retry_after_secs} seconds")]
+    TooManyRequests { retry_after_secs: u64 },
+    #[error("Mail delivery error: {0}")]
+    MailDeliveryError(String),
+    #[error("Unknown sign-in provider")]
+    UnknownOidcProvider,
+    /// The provider could not be reached or returned something that failed verification
+    #[error("Sign-in provider error: {0}")]
+    OidcProviderError(String),
+    #[error("Provider did not confirm the email address")]
+    OidcEmailNotVerified,
+    /// A password account with the provider's email exists but is not linked
+    #[error("Account exists for provider email")]
+    OidcAccountExists,
+    #[error("Identity already linked")]
+    IdentityAlreadyLinked,
+    #[error("Identity not linked")]
+    IdentityNotLinked,
+    #[error("Cannot remove the last sign-in method")]
+    LastSignInMethod,
+    /// The passkey response did not verify, or was answered to an unknown or used challenge
+    #[error("Invalid passkey")]
+    InvalidPasskey,
+    #[error("Passkey not found")]
+    PasskeyNotFound,
 }
 
 impl From<sqlx::Error> for AuthError {
@@ -58,19 +112,109 @@ impl IntoResponse for AuthError {
                 StatusCode::UNAUTHORIZED,
                 "Invalid email or password".to_string(),
             ),
+            // Reuse is reported like any other unusable token
+            AuthError::InvalidRefreshToken | AuthError::RefreshTokenReused { .. } => (
+                StatusCode::UNAUTHORIZED,
+                "Invalid or expired refresh token".to_string(),
+            ),
             AuthError::InvalidTokenType => {
                 (StatusCode::UNAUTHORIZED, "Invalid token type".to_string())
             }
             AuthError::ValidationError(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
+            AuthError::EmailNotVerified => (
+                StatusCode::FORBIDDEN,
+                "Please verify your email address before signing in".to_string(),
+            ),
+            AuthError::InvalidOrExpiredToken => (
+                StatusCode::BAD_REQUEST,
+                "Invalid or expired token".to_string(),
+            ),
+            AuthError::IncorrectPassword => (
+                StatusCode::BAD_REQUEST,
+                "Current password is incorrect".to_string(),
+            ),
+            AuthError::InvalidMfaCode => (
+                StatusCode::UNAUTHORIZED,
+                "Invalid authentication code".to_string(),
+            ),
+            AuthError::MfaAlreadyEnabled => (
+                StatusCode::CONFLICT,
+                "Two-factor authentication is already enabled".to_string(),
+            ),
+            AuthError::MfaNotEnabled => (
+                StatusCode::BAD_REQUEST,
+                "Two-factor authentication is not enabled".to_string(),
+            ),
+            AuthError::AccountSuspended => (
+                StatusCode::FORBIDDEN,
+                "This account has been suspended".to_string(),
+            ),
+            AuthError::AccountDeleted => (
+                StatusCode::FORBIDDEN,
+                "This account has been deleted".to_string(),
+            ),
+            AuthError::TooManyLoginAttempts { .. } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "Too many failed login attempts. Please try again later".to_string(),
+            ),
+            AuthError::TooManyRequests { .. } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "Too many requests. Please try again later".to_string(),
+            ),
+            AuthError::UnknownOidcProvider => (
+                StatusCode::NOT_FOUND,
+                "Unknown sign-in provider".to_string(),
+            ),
+            AuthError::OidcProviderError(_) => (
+                StatusCode::BAD_GATEWAY,
+                "Sign-in with this provider failed. Please try again".to_string(),
+            ),
+            AuthError::OidcEmailNotVerified => (
+                StatusCode::BAD_REQUEST,
+                "The sign-in provider did not confirm your email address".to_string(),
+            ),
+            AuthError::OidcAccountExists => (
+                StatusCode::CONFLICT,
+                "An account with this email already exists. Sign in and link the provider from your account settings".to_string(),
+            ),
+            AuthError::IdentityAlreadyLinked => (
+                StatusCode::CONFLICT,
+                "This provider account is already linked".to_string(),
+            ),
+            AuthError::IdentityNotLinked => (
+                StatusCode::NOT_FOUND,
+                "This provider is not linked to your account".to_string(),
+            ),
+            AuthError::LastSignInMethod => (
+                StatusCode::BAD_REQUEST,
+                "Set a password or link another provider before removing this one".to_string(),
+            ),
+            AuthError::InvalidPasskey => (
+                StatusCode::UNAUTHORIZED,
+                "Passkey sign-in failed".to_string(),
+            ),
+            AuthError::PasskeyNotFound => (StatusCode::NOT_FOUND, "Passkey not found".to_string()),
             AuthError::DatabaseError(_)
             | AuthError::HashingError(_)
-            | AuthError::TokenCreationError(_) => (
+            | AuthError::TokenCreationError(_)
+            | AuthError::MailDeliveryError(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Internal server error".to_string(),
             ),
         };
 
-        (status, Json(ErrorResponse { error: message })).into_response()
+        let mut response = (status, Json(ErrorResponse { error: message })).into_response();
+
+        // Tell clients when they may retry after a lockout or rate limit
+        if let AuthError::TooManyLoginAttempts { retry_after_secs }
+        | AuthError::TooManyRequests { retry_after_secs } = self
+        {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+        }
+
+        response
     }
 }
 
@@ -84,6 +228,8 @@ pub enum AppError {
     BadRequest(Cow<'static, str>),
     #[error("Unauthorized: {0}")]
     Unauthorized(Cow<'static, str>),
+    #[error("Forbidden: {0}")]
+    Forbidden(Cow<'static, str>),
 }
 
 impl IntoResponse for AppError {
@@ -92,6 +238,7 @@ impl IntoResponse for AppError {
             AppError::InternalError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.into_owned()),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.into_owned()),
             AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg.into_owned()),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg.into_owned()),
         };
         (status, Json(ErrorResponse { error: message })).into_response()
     }
@@ -0,0 +1,374 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::cookie::CookieJar;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    constant::auth::ACCESS_TOKEN_DURATION_MINUTES,
+    dtos::private::{
+        auth::response::AccessTokenResponse,
+        user::{
+            AccountDeletionResponse, ChangePasswordRequest, DeleteAccountRequest,
+            PaginatedSecurityEventsResponse, RevokeSessionsResponse, SecurityEventResponse,
+            SecurityEventsQuery, SessionResponse,
+        },
+    },
+    error::{AppError, AuthError},
+    services::{
+        audit_service::{self, AuditEvent, AuditRecord},
+        auth::{
+            account_deletion_service::{self, DeletionProof},
+            auth_service, password_service, session_service,
+        },
+    },
+    state::AppState,
+    utils::{
+        client::{ClientType, session_refresh_token},
+        cookies::{create_access_cookie, remove_auth_cookies},
+        cursor::{decode_cursor, encode_cursor},
+        device::DeviceInfo,
+        jwt::Claims,
+        validation::format_validation_errors,
+    },
+};
+
+/// Helper to extract the authenticated user's ID from JWT claims
+fn user_id_from_claims(claims: &Claims) -> Result<Uuid, AppError> {
+    Uuid::parse_str(&claims.sub).map_err(|_| AppError::Unauthorized("Invalid user ID".into()))
+}
+
+fn access_token_response(access_token: String) -> AccessTokenResponse {
+    AccessTokenResponse {
+        access_token,
+        token_type: "Bearer",
+        expires_in: ACCESS_TOKEN_DURATION_MINUTES * 60,
+    }
+}
+
+/// Hands the current session the access token that replaces its revoked one:
+/// as a cookie for browsers, or instead of `body` for native clients
+fn with_access_token(
+    jar: CookieJar,
+    client: ClientType,
+    access_token: Option<String>,
+    body: Response,
+) -> Response {
+    match (access_token, client) {
+        (None, _) => body,
+        (Some(token), ClientType::Browser) => {
+            (jar.add(create_access_cookie(token)), body).into_response()
+        }
+        (Some(token), ClientType::Native) => Json(access_token_response(token)).into_response(),
+    }
+}
+
+/// New access token for the session making the request, once it has revoked all access tokens
+async fn reissue_access_token(
+    state: &AppState,
+    user_id: Uuid,
+    current_refresh_token: Option<&str>,
+) -> Result<Option<String>, AppError> {
+    auth_service::reissue_access_token(
+        &state.pool,
+        user_id,
+        current_refresh_token,
+        &state.config.jwt,
+    )
+    .await
+    .map_err(|e| AppError::InternalError(e.to_string().into()))
+}
+
+pub async fn change_password_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    jar: CookieJar,
+    client: ClientType,
+    headers: HeaderMap,
+    device: DeviceInfo,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> impl IntoResponse {
+    let user_id = match user_id_from_claims(&claims) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(),
+    };
+
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+
+    // The refresh token of the current device is kept so this session stays signed in
+    let current_refresh_token = session_refresh_token(client, &jar, &headers);
+
+    match password_service::change_password(
+        &state.pool,
+        &state.user_status_cache,
+        user_id,
+        &payload.current_password,
+        payload.new_password.trim(),
+        current_refresh_token,
+        &state.config.password_hash,
+        state.config.breached_passwords_dir.as_deref(),
+    )
+    .await
+    {
+        Ok(()) => {
+            state
+                .audit
+                .record(AuditRecord::new(AuditEvent::PasswordChanged, &device).user(user_id));
+        }
+        Err(e) => return e.into_response(),
+    }
+
+    // Changing the password revoked every access token, this session's included
+    match reissue_access_token(&state, user_id, current_refresh_token).await {
+        Ok(access_token) => with_access_token(
+            jar.clone(),
+            client,
+            access_token,
+            (StatusCode::OK, "Password changed").into_response(),
+        ),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn list_sessions_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    jar: CookieJar,
+    client: ClientType,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user_id_from_claims(&claims)?;
+
+    let current_session_id = session_service::current_session_id(
+        &state.pool,
+        user_id,
+        session_refresh_token(client, &jar, &headers),
+    )
+    .await?;
+
+    let sessions = session_service::list_sessions(&state.pool, user_id).await?;
+
+    let response: Vec<SessionResponse> = sessions
+        .into_iter()
+        .map(|s| SessionResponse {
+            current: Some(s.family_id) == current_session_id,
+            id: s.family_id,
+            device_name: s.device_name,
+            user_agent: s.user_agent,
+            ip_address: s.ip_address,
+            created_at: s.created_at,
+            last_used_at: s.last_used_at,
+            expires_at: s.expires_at,
+        })
+        .collect();
+
+    Ok(Json(response))
+}
+
+pub async fn revoke_session_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    jar: CookieJar,
+    client: ClientType,
+    headers: HeaderMap,
+    Path(session_id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let user_id = user_id_from_claims(&claims)?;
+
+    session_service::revoke_session(&state.pool, &state.user_status_cache, user_id, session_id)
+        .await?;
+
+    // Nothing is reissued if the session signed out was this one
+    let access_token = reissue_access_token(
+        &state,
+        user_id,
+        session_refresh_token(client, &jar, &headers),
+    )
+    .await?;
+
+    Ok(with_access_token(
+        jar,
+        client,
+        access_token,
+        Json("Session signed out").into_response(),
+    ))
+}
+
+pub async fn revoke_other_sessions_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    jar: CookieJar,
+    client: ClientType,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let user_id = user_id_from_claims(&claims)?;
+
+    let current_refresh_token = session_refresh_token(client, &jar, &headers);
+    let revoked = session_service::revoke_other_sessions(
+        &state.pool,
+        &state.user_status_cache,
+        user_id,
+        current_refresh_token,
+    )
+    .await?;
+
+    let access_token = reissue_access_token(&state, user_id, current_refresh_token).await?;
+    Ok(match (access_token, client) {
+        (Some(token), ClientType::Browser) => (
+            jar.clone().add(create_access_cookie(token)),
+            Json(RevokeSessionsResponse {
+                revoked,
+                access_token: None,
+            }),
+        )
+            .into_response(),
+        (access_token, _) => Json(RevokeSessionsResponse {
+            revoked,
+            access_token: access_token.map(access_token_response),
+        })
+        .into_response(),
+    })
+}
+
+/// Starts the deletion of an account without a password by emailing a confirmation link
+pub async fn request_deletion_confirmation_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    let user_id = match user_id_from_claims(&claims) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(),
+    };
+
+    match account_deletion_service::request_deletion_confirmation(
+        &state.pool,
+        state.mailer.as_ref(),
+        &state.config.app_base_url,
+        user_id,
+    )
+    .await
+    {
+        Ok(()) => (
+            StatusCode::ACCEPTED,
+            "A confirmation link has been sent to your email address",
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn delete_account_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    jar: CookieJar,
+    device: DeviceInfo,
+    Json(payload): Json<DeleteAccountRequest>,
+) -> impl IntoResponse {
+    let user_id = match user_id_from_claims(&claims) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(),
+    };
+
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+    let proof = match (&payload.password, &payload.confirmation_token) {
+        (Some(password), _) => DeletionProof::Password(password),
+        (None, Some(token)) => DeletionProof::EmailConfirmation(token),
+        (None, None) => {
+            return AuthError::ValidationError(
+                "Password or confirmation token is required".to_string(),
+            )
+            .into_response();
+        }
+    };
+
+    match account_deletion_service::delete_account(
+        &state.pool,
+        &state.user_status_cache,
+        user_id,
+        proof,
+        state.config.account_deletion_grace_days,
+        &state.config.password_hash,
+    )
+    .await
+    {
+        Ok(purge_at) => {
+            state.audit.record(
+                AuditRecord::new(AuditEvent::AccountDeleted, &device)
+                    .user(user_id)
+                    .details(json!({ "purge_at": purge_at })),
+            );
+
+            // Every session was revoked, so clear this device's cookies as well
+            let mut updated_jar = jar;
+            for cookie in remove_auth_cookies(state.config.csrf_cookie_domain.as_deref()) {
+                updated_jar = updated_jar.add(cookie);
+            }
+            (
+                StatusCode::OK,
+                updated_jar,
+                Json(AccountDeletionResponse { purge_at }),
+            )
+                .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn security_events_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Query(params): Query<SecurityEventsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user_id_from_claims(&claims)?;
+
+    let cursor = match params.cursor.as_deref().map(decode_cursor).transpose()? {
+        Some((created_at, id)) => {
+            let created_at = DateTime::parse_from_rfc3339(&created_at)
+                .map_err(|_| AppError::BadRequest("Invalid cursor format".into()))?
+                .with_timezone(&Utc);
+            Some((created_at, id))
+        }
+        None => None,
+    };
+
+    // Default limit to 20, max 100
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+
+    let events = audit_service::list_user_events(&state.pool, user_id, cursor, limit + 1).await?;
+
+    let has_more = events.len() > limit as usize;
+    let data: Vec<SecurityEventResponse> = events
+        .into_iter()
+        .take(limit as usize)
+        .map(|event| SecurityEventResponse {
+            by_admin: event.actor_user_id.is_some_and(|actor| actor != user_id),
+            id: event.id,
+            event_type: event.event_type,
+            ip_address: event.ip_address,
+            user_agent: event.user_agent,
+            details: event.details,
+            created_at: event.created_at,
+        })
+        .collect();
+
+    let next_cursor = match data.last() {
+        Some(last) if has_more => Some(encode_cursor(&last.created_at.to_rfc3339(), last.id)),
+        _ => None,
+    };
+
+    Ok(Json(PaginatedSecurityEventsResponse {
+        data,
+        next_cursor,
+        has_more,
+    }))
+}
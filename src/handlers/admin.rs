@@ -0,0 +1,198 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    dtos::private::{
+        admin::{
+            AdminUserDetailsResponse, AdminUserSummary, ChangeRoleRequest, FriendCountsResponse,
+            PaginatedUsersResponse, SearchUsersQuery,
+        },
+        user::RevokeSessionsResponse,
+    },
+    error::AppError,
+    services::admin_service::{self, AdminActor},
+    state::AppState,
+    utils::{
+        cursor::{decode_cursor, encode_cursor},
+        device::DeviceInfo,
+        jwt::Claims,
+    },
+};
+
+/// Identifies the admin making the request, for the audit log
+fn actor_from(claims: &Claims, device: DeviceInfo) -> Result<AdminActor, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Unauthorized("Invalid user ID".into()))?;
+    Ok(AdminActor { user_id, device })
+}
+
+pub async fn search_users_handler(
+    State(state): State<AppState>,
+    Query(params): Query<SearchUsersQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let cursor = params.cursor.as_deref().map(decode_cursor).transpose()?;
+
+    // Default limit to 20, max 100
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+
+    let rows =
+        admin_service::search_users(&state.pool, params.q.as_deref(), cursor, limit + 1).await?;
+
+    let has_more = rows.len() > limit as usize;
+    let data: Vec<AdminUserSummary> = rows
+        .into_iter()
+        .take(limit as usize)
+        .map(|row| AdminUserSummary {
+            id: row.id,
+            email: row.email,
+            role: row.role,
+            is_active: row.is_active,
+            is_deleted: row.is_deleted,
+            full_name: row.full_name,
+            created_at: row.created_at,
+        })
+        .collect();
+
+    let next_cursor = match data.last() {
+        Some(last) if has_more => Some(encode_cursor(&last.email, last.id)),
+        _ => None,
+    };
+
+    Ok(Json(PaginatedUsersResponse {
+        data,
+        next_cursor,
+        has_more,
+    }))
+}
+
+pub async fn get_user_handler(
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let details = admin_service::get_user_details(&state.pool, user_id).await?;
+    let (user, profile) = (details.user, details.profile);
+
+    Ok(Json(AdminUserDetailsResponse {
+        id: user.id,
+        email: user.email,
+        role: user.role,
+        is_active: user.is_active,
+        is_deleted: user.is_deleted,
+        email_verified_at: user.email_verified_at,
+        email_conflict_with: user.email_conflict_with,
+        deleted_at: user.deleted_at,
+        full_name: profile.as_ref().and_then(|p| p.full_name.clone()),
+        bio: profile.as_ref().and_then(|p| p.bio.clone()),
+        avatar_url: profile.as_ref().and_then(|p| p.avatar_url.clone()),
+        friend_counts: FriendCountsResponse {
+            friends: details.friend_counts.friends,
+            pending_received: details.friend_counts.pending_received,
+            pending_sent: details.friend_counts.pending_sent,
+        },
+        active_sessions: details.active_sessions,
+        created_at: user.created_at,
+        updated_at: user.updated_at,
+    }))
+}
+
+pub async fn suspend_user_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    device: DeviceInfo,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let actor = actor_from(&claims, device)?;
+    admin_service::set_suspended(
+        &state.pool,
+        &state.audit,
+        &state.user_status_cache,
+        &actor,
+        user_id,
+        true,
+    )
+    .await?;
+    Ok(Json("User suspended"))
+}
+
+pub async fn unsuspend_user_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    device: DeviceInfo,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let actor = actor_from(&claims, device)?;
+    admin_service::set_suspended(
+        &state.pool,
+        &state.audit,
+        &state.user_status_cache,
+        &actor,
+        user_id,
+        false,
+    )
+    .await?;
+    Ok(Json("User unsuspended"))
+}
+
+pub async fn force_logout_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    device: DeviceInfo,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let actor = actor_from(&claims, device)?;
+    let revoked = admin_service::force_logout(
+        &state.pool,
+        &state.audit,
+        &state.user_status_cache,
+        &actor,
+        user_id,
+    )
+    .await?;
+    Ok(Json(RevokeSessionsResponse {
+        revoked,
+        access_token: None,
+    }))
+}
+
+pub async fn change_role_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    device: DeviceInfo,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<ChangeRoleRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let actor = actor_from(&claims, device)?;
+    admin_service::change_role(
+        &state.pool,
+        &state.audit,
+        &state.user_status_cache,
+        &actor,
+        user_id,
+        payload.role,
+    )
+    .await?;
+    Ok(Json("Role updated"))
+}
+
+pub async fn remove_avatar_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    device: DeviceInfo,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let actor = actor_from(&claims, device)?;
+    admin_service::remove_avatar(
+        &state.pool,
+        &state.audit,
+        &state.s3_client,
+        &state.config.r2.bucket_name,
+        &actor,
+        user_id,
+    )
+    .await?;
+    Ok(Json("Avatar removed"))
+}
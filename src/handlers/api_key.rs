@@ -0,0 +1,91 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    dtos::private::api_key::{ApiKeyResponse, CreateApiKeyRequest, CreatedApiKeyResponse},
+    error::AppError,
+    services::{
+        api_key_service,
+        audit_service::{AuditEvent, AuditRecord},
+    },
+    state::AppState,
+    utils::{device::DeviceInfo, jwt::Claims, validation::format_validation_errors},
+};
+
+fn user_id_from_claims(claims: &Claims) -> Result<Uuid, AppError> {
+    Uuid::parse_str(&claims.sub).map_err(|_| AppError::Unauthorized("Invalid user ID".into()))
+}
+
+pub async fn create_api_key_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    device: DeviceInfo,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user_id_from_claims(&claims)?;
+
+    payload
+        .validate()
+        .map_err(|errors| AppError::BadRequest(format_validation_errors(errors).into()))?;
+
+    let (key, secret) = api_key_service::create_api_key(
+        &state.pool,
+        user_id,
+        payload.name.trim(),
+        &payload.scopes,
+        payload.expires_in_days,
+    )
+    .await?;
+
+    state.audit.record(
+        AuditRecord::new(AuditEvent::ApiKeyCreated, &device)
+            .user(user_id)
+            .details(json!({ "key_id": key.id, "name": key.name, "scopes": key.scopes })),
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedApiKeyResponse {
+            key: key.into(),
+            secret,
+        }),
+    ))
+}
+
+pub async fn list_api_keys_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user_id_from_claims(&claims)?;
+
+    let keys = api_key_service::list_api_keys(&state.pool, user_id).await?;
+    let response: Vec<ApiKeyResponse> = keys.into_iter().map(ApiKeyResponse::from).collect();
+
+    Ok(Json(response))
+}
+
+pub async fn revoke_api_key_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    device: DeviceInfo,
+    Path(key_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = user_id_from_claims(&claims)?;
+
+    api_key_service::revoke_api_key(&state.pool, user_id, key_id).await?;
+
+    state.audit.record(
+        AuditRecord::new(AuditEvent::ApiKeyRevoked, &device)
+            .user(user_id)
+            .details(json!({ "key_id": key_id })),
+    );
+
+    Ok(Json("API key revoked"))
+}
@@ -1,73 +1,261 @@
 use crate::{
-    constant::auth::REFRESH_TOKEN_COOKIE_NAME,
+    config::JwtConfig,
+    constant::auth::{
+        ACCESS_TOKEN_COOKIE_NAME, ACCESS_TOKEN_DURATION_MINUTES, REFRESH_TOKEN_COOKIE_NAME,
+    },
     dtos::private::auth::{
-        request::{LoginRequest, RegisterRequest},
-        response::AuthResponse,
+        request::{
+            ConsumeMagicLinkRequest, ForgotPasswordRequest, LoginRequest, MagicLinkQuery,
+            MagicLinkRequest, MfaLoginRequest, RefreshTokenRequest, RegisterRequest,
+            ResendVerificationRequest, ResetPasswordRequest, RestoreAccountRequest,
+            VerifyEmailRequest,
+        },
+        response::{AccountRestoreResponse, AuthResponse, MfaRequiredResponse, TokenResponse},
     },
     error::AuthError,
-    services::auth::auth_service,
+    models::user::UserModel,
+    services::{
+        audit_service::{AuditEvent, AuditRecord},
+        auth::{
+            auth_service::{self, LoginOutcome},
+            magic_link_service, password_service, verification_service,
+        },
+        mail::mail_service,
+    },
     state::AppState,
-    utils::cookies::{create_auth_cookies, remove_auth_cookies},
+    utils::{
+        client::{ClientIp, ClientType, bearer_token},
+        cookies::{create_auth_cookies, remove_auth_cookies},
+        device::DeviceInfo,
+        email::normalize_email,
+        jwt::{TokenType, decode_jwt_with_type},
+        validation::format_validation_errors,
+    },
+};
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect, Response},
 };
-use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
 use axum_extra::extract::cookie::CookieJar;
+use serde_json::json;
+use uuid::Uuid;
 use validator::Validate;
 
-/// Helper function to format validation errors into a readable message
-fn format_validation_errors(errors: validator::ValidationErrors) -> String {
-    errors
-        .field_errors()
-        .iter()
-        .flat_map(|(field, errors)| {
-            errors.iter().map(move |e| {
-                e.message
-                    .clone()
-                    .map(|m| m.to_string())
-                    .unwrap_or_else(|| format!("Invalid {}", field))
-            })
-        })
-        .collect::<Vec<_>>()
-        .join(", ")
+/// Normalizes the optional client-supplied session name (blank means no name)
+pub(crate) fn clean_device_name(device_name: Option<&str>) -> Option<String> {
+    device_name
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
+
+/// Canonical form of a submitted address; accounts are always looked up by it
+fn normalized_email(state: &AppState, email: &str) -> Result<String, AuthError> {
+    normalize_email(email, state.config.email_provider_rules)
+        .map_err(|_| AuthError::ValidationError("Invalid email format".to_string()))
+}
+
+/// Whether a failed sign-in should be audited; internal errors are not caused by the attempt
+pub(crate) fn is_auth_failure(error: &AuthError) -> bool {
+    !matches!(
+        error,
+        AuthError::DatabaseError(_)
+            | AuthError::HashingError(_)
+            | AuthError::TokenCreationError(_)
+            | AuthError::MailDeliveryError(_)
+            | AuthError::OidcProviderError(_)
+    )
+}
+
+/// Subject of a signed token of the given type, if it is valid
+fn token_subject(token: &str, jwt: &JwtConfig, token_type: TokenType) -> Option<Uuid> {
+    decode_jwt_with_type(token, jwt, token_type)
+        .ok()
+        .and_then(|claims| Uuid::parse_str(&claims.sub).ok())
+}
+
+/// Hands a newly issued session to the client:
+/// as cookies for browsers, in the response body for native clients
+pub(crate) fn session_response(
+    jar: CookieJar,
+    client: ClientType,
+    csrf_cookie_domain: Option<&str>,
+    access_token: String,
+    refresh_token: String,
+    user: UserModel,
+    remember_me: bool,
+) -> Response {
+    match client {
+        ClientType::Browser => {
+            let cookies =
+                create_auth_cookies(access_token, refresh_token, csrf_cookie_domain, remember_me);
+            let mut updated_jar = jar;
+            for cookie in cookies {
+                updated_jar = updated_jar.add(cookie);
+            }
+            (StatusCode::OK, updated_jar, Json(AuthResponse { user })).into_response()
+        }
+        ClientType::Native => (
+            StatusCode::OK,
+            Json(TokenResponse {
+                access_token,
+                refresh_token,
+                token_type: "Bearer",
+                expires_in: ACCESS_TOKEN_DURATION_MINUTES * 60,
+                user,
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// Turns the result of a password login into a session or a follow-up challenge
+pub(crate) fn login_outcome_response(
+    jar: CookieJar,
+    client: ClientType,
+    csrf_cookie_domain: Option<&str>,
+    outcome: LoginOutcome,
+    remember_me: bool,
+) -> Response {
+    match outcome {
+        LoginOutcome::Authenticated {
+            access_token,
+            refresh_token,
+            user,
+        } => session_response(
+            jar,
+            client,
+            csrf_cookie_domain,
+            access_token,
+            refresh_token,
+            user,
+            remember_me,
+        ),
+        // No cookies yet: the client must submit a second factor to /login/mfa
+        LoginOutcome::MfaRequired { mfa_token } => (
+            StatusCode::OK,
+            Json(MfaRequiredResponse {
+                mfa_required: true,
+                mfa_token,
+            }),
+        )
+            .into_response(),
+        // No cookies yet: the client may offer to undo the deletion via /restore-account
+        LoginOutcome::RestoreAvailable {
+            restore_token,
+            purge_at,
+        } => (
+            StatusCode::OK,
+            Json(AccountRestoreResponse {
+                restore_available: true,
+                restore_token,
+                purge_at,
+            }),
+        )
+            .into_response(),
+    }
 }
 
 pub async fn register_handler(
     State(state): State<AppState>,
-    jar: CookieJar,
+    device: DeviceInfo,
     Json(payload): Json<RegisterRequest>,
 ) -> impl IntoResponse {
     // Validate request using validator derive macros
     if let Err(errors) = payload.validate() {
         return AuthError::ValidationError(format_validation_errors(errors)).into_response();
     }
+    let email_normalized = match normalized_email(&state, &payload.email) {
+        Ok(email) => email,
+        Err(e) => return e.into_response(),
+    };
 
     match auth_service::register_user(
         &state.pool,
         payload.email.trim(),
+        &email_normalized,
         payload.password.trim(),
-        &state.config.jwt_secret,
+        &state.config.password_hash,
+        state.config.breached_passwords_dir.as_deref(),
     )
     .await
     {
-        Ok((token, refresh_token, user)) => {
-            let cookies = create_auth_cookies(token, refresh_token, true);
-            let mut updated_jar = jar;
-            for cookie in cookies {
-                updated_jar = updated_jar.add(cookie);
-            }
-            (
-                StatusCode::CREATED,
-                updated_jar,
-                Json(AuthResponse { user }),
+        Ok(user) => {
+            state
+                .audit
+                .record(AuditRecord::new(AuditEvent::Registered, &device).user(user.id));
+
+            // No session is issued until the email is verified.
+            // A delivery failure is not fatal: the user can request a new link.
+            if let Err(e) = verification_service::send_verification(
+                &state.pool,
+                state.mailer.as_ref(),
+                &state.config.app_base_url,
+                &user,
             )
-                .into_response()
+            .await
+            {
+                tracing::error!("Failed to send verification email: {:?}", e);
+            }
+
+            (StatusCode::CREATED, Json(AuthResponse { user })).into_response()
         }
         Err(e) => e.into_response(),
     }
 }
 
+pub async fn verify_email_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+
+    match verification_service::verify_email(&state.pool, payload.token.trim()).await {
+        Ok(()) => (StatusCode::OK, "Email verified").into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn resend_verification_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<ResendVerificationRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+    let email = match normalized_email(&state, &payload.email) {
+        Ok(email) => email,
+        Err(e) => return e.into_response(),
+    };
+
+    // Same response whether or not the account exists, to prevent email enumeration
+    if let Err(e) = verification_service::resend_verification(
+        &state.pool,
+        state.mailer.as_ref(),
+        &state.config.app_base_url,
+        &email,
+    )
+    .await
+    {
+        tracing::error!("Failed to resend verification email: {:?}", e);
+    }
+
+    (
+        StatusCode::ACCEPTED,
+        "If the account exists and is not yet verified, a verification email has been sent",
+    )
+        .into_response()
+}
+
 pub async fn login_handler(
     State(state): State<AppState>,
     jar: CookieJar,
+    client: ClientType,
+    mut device: DeviceInfo,
     Json(payload): Json<LoginRequest>,
 ) -> impl IntoResponse {
     // Validate request using validator derive macros
@@ -75,57 +263,403 @@ pub async fn login_handler(
         return AuthError::ValidationError(format_validation_errors(errors)).into_response();
     }
 
-    match auth_service::login_user(
+    let email = match normalized_email(&state, &payload.email) {
+        Ok(email) => email,
+        Err(e) => return e.into_response(),
+    };
+
+    device.device_name = clean_device_name(payload.device_name.as_deref());
+
+    let result = auth_service::login_user(
         &state.pool,
-        payload.email.trim(),
+        &email,
         payload.password.trim(),
-        &state.config.jwt_secret,
+        &device,
+        &state.config.jwt,
+        &state.config.password_hash,
+        state.config.account_deletion_grace_days,
+    )
+    .await;
+
+    match &result {
+        Ok(LoginOutcome::Authenticated { user, .. }) => state.audit.record(
+            AuditRecord::new(AuditEvent::LoginSucceeded, &device)
+                .user(user.id)
+                .details(json!({ "method": "password" })),
+        ),
+        Ok(_) => {}
+        Err(e) if is_auth_failure(e) => state.audit.record(
+            AuditRecord::new(AuditEvent::LoginFailed, &device)
+                .email(&email)
+                .details(json!({ "reason": e.to_string() })),
+        ),
+        Err(_) => {}
+    }
+
+    match result {
+        Ok(outcome) => login_outcome_response(
+            jar,
+            client,
+            state.config.csrf_cookie_domain.as_deref(),
+            outcome,
+            payload.remember_me,
+        ),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn login_mfa_handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    client: ClientType,
+    mut device: DeviceInfo,
+    Json(payload): Json<MfaLoginRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+
+    device.device_name = clean_device_name(payload.device_name.as_deref());
+
+    match auth_service::complete_mfa_login(
+        &state.pool,
+        payload.mfa_token.trim(),
+        payload.code.trim(),
+        &device,
+        &state.config.jwt,
     )
     .await
     {
         Ok((token, refresh_token, user)) => {
-            let cookies = create_auth_cookies(token, refresh_token, payload.remember_me);
-            let mut updated_jar = jar;
-            for cookie in cookies {
-                updated_jar = updated_jar.add(cookie);
+            state.audit.record(
+                AuditRecord::new(AuditEvent::LoginSucceeded, &device)
+                    .user(user.id)
+                    .details(json!({ "method": "mfa" })),
+            );
+
+            session_response(
+                jar,
+                client,
+                state.config.csrf_cookie_domain.as_deref(),
+                token,
+                refresh_token,
+                user,
+                payload.remember_me,
+            )
+        }
+        Err(e) => {
+            // The password step already identified the account
+            let user_id = token_subject(
+                payload.mfa_token.trim(),
+                &state.config.jwt,
+                TokenType::MfaPending,
+            );
+            if let Some(user_id) = user_id.filter(|_| is_auth_failure(&e)) {
+                state.audit.record(
+                    AuditRecord::new(AuditEvent::LoginFailed, &device)
+                        .user(user_id)
+                        .details(json!({ "reason": e.to_string(), "method": "mfa" })),
+                );
             }
-            (StatusCode::OK, updated_jar, Json(AuthResponse { user })).into_response()
+            e.into_response()
         }
+    }
+}
+
+pub async fn restore_account_handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    client: ClientType,
+    mut device: DeviceInfo,
+    Json(payload): Json<RestoreAccountRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+
+    device.device_name = clean_device_name(payload.device_name.as_deref());
+
+    match auth_service::restore_deleted_account(
+        &state.pool,
+        &state.user_status_cache,
+        payload.restore_token.trim(),
+        &device,
+        &state.config.jwt,
+        state.config.account_deletion_grace_days,
+    )
+    .await
+    {
+        Ok(outcome) => {
+            if let LoginOutcome::Authenticated { user, .. } = &outcome {
+                state.audit.record(
+                    AuditRecord::new(AuditEvent::LoginSucceeded, &device)
+                        .user(user.id)
+                        .details(json!({ "method": "restore" })),
+                );
+            }
+            login_outcome_response(
+                jar,
+                client,
+                state.config.csrf_cookie_domain.as_deref(),
+                outcome,
+                payload.remember_me,
+            )
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn request_magic_link_handler(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    Json(payload): Json<MagicLinkRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+    let email = match normalized_email(&state, &payload.email) {
+        Ok(email) => email,
+        Err(e) => return e.into_response(),
+    };
+
+    // Same response whether or not the account exists, to prevent email enumeration
+    match magic_link_service::request_magic_link(
+        &state.pool,
+        state.mailer.as_ref(),
+        &state.config.app_base_url,
+        &email,
+        client_ip.map(|ip| ip.to_string()).as_deref(),
+    )
+    .await
+    {
+        Ok(()) => {}
+        Err(e @ AuthError::TooManyRequests { .. }) => return e.into_response(),
+        Err(e) => tracing::error!("Failed to send magic link email: {:?}", e),
+    }
+
+    (
+        StatusCode::ACCEPTED,
+        "If an account exists for this email, a sign-in link has been sent",
+    )
+        .into_response()
+}
+
+/// Opening a sign-in link must not use it up, since mail scanners and link previews fetch it too.
+/// The link is sent on to the confirmation page, which consumes it with a POST.
+pub async fn magic_link_confirmation_handler(
+    State(state): State<AppState>,
+    Query(query): Query<MagicLinkQuery>,
+) -> impl IntoResponse {
+    if let Err(errors) = query.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+
+    Redirect::to(&mail_service::magic_link_url(
+        &state.config.app_base_url,
+        query.token.trim(),
+    ))
+    .into_response()
+}
+
+pub async fn consume_magic_link_handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    client: ClientType,
+    mut device: DeviceInfo,
+    Json(payload): Json<ConsumeMagicLinkRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+
+    device.device_name = clean_device_name(payload.device_name.as_deref());
+
+    let result = magic_link_service::consume_magic_link(
+        &state.pool,
+        payload.token.trim(),
+        &device,
+        &state.config.jwt,
+        state.config.account_deletion_grace_days,
+    )
+    .await;
+
+    match &result {
+        Ok(LoginOutcome::Authenticated { user, .. }) => state.audit.record(
+            AuditRecord::new(AuditEvent::LoginSucceeded, &device)
+                .user(user.id)
+                .details(json!({ "method": "magic_link" })),
+        ),
+        Ok(_) => {}
+        Err(e) if is_auth_failure(e) => state.audit.record(
+            AuditRecord::new(AuditEvent::LoginFailed, &device)
+                .details(json!({ "reason": e.to_string(), "method": "magic_link" })),
+        ),
+        Err(_) => {}
+    }
+
+    match result {
+        Ok(outcome) => login_outcome_response(
+            jar,
+            client,
+            state.config.csrf_cookie_domain.as_deref(),
+            outcome,
+            payload.remember_me,
+        ),
         Err(e) => e.into_response(),
     }
 }
 
+pub async fn forgot_password_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+    let email = match normalized_email(&state, &payload.email) {
+        Ok(email) => email,
+        Err(e) => return e.into_response(),
+    };
+
+    // Same response whether or not the account exists, to prevent email enumeration
+    if let Err(e) = password_service::request_password_reset(
+        &state.pool,
+        state.mailer.as_ref(),
+        &state.config.app_base_url,
+        &email,
+    )
+    .await
+    {
+        tracing::error!("Failed to send password reset email: {:?}", e);
+    }
+
+    (
+        StatusCode::ACCEPTED,
+        "If an account exists for this email, a password reset link has been sent",
+    )
+        .into_response()
+}
+
+pub async fn reset_password_handler(
+    State(state): State<AppState>,
+    device: DeviceInfo,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+
+    match password_service::reset_password(
+        &state.pool,
+        &state.user_status_cache,
+        payload.token.trim(),
+        payload.new_password.trim(),
+        &state.config.password_hash,
+        state.config.breached_passwords_dir.as_deref(),
+    )
+    .await
+    {
+        Ok(user_id) => {
+            state
+                .audit
+                .record(AuditRecord::new(AuditEvent::PasswordReset, &device).user(user_id));
+            (StatusCode::OK, "Password has been reset").into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// The refresh token presented by the client: the cookie for browsers, the body for native clients
+fn presented_refresh_token(
+    jar: &CookieJar,
+    client: ClientType,
+    body: Option<&RefreshTokenRequest>,
+) -> Option<String> {
+    match client {
+        ClientType::Browser => jar
+            .get(REFRESH_TOKEN_COOKIE_NAME)
+            .map(|c| c.value().to_string()),
+        ClientType::Native => body.map(|b| b.refresh_token.trim().to_string()),
+    }
+    .filter(|token| !token.is_empty())
+}
+
 pub async fn refresh_token_handler(
     State(state): State<AppState>,
     jar: CookieJar,
+    client: ClientType,
+    device: DeviceInfo,
+    payload: Option<Json<RefreshTokenRequest>>,
 ) -> impl IntoResponse {
-    let refresh_token = match jar.get(REFRESH_TOKEN_COOKIE_NAME) {
-        Some(cookie) => cookie.value(),
-        None => return (StatusCode::UNAUTHORIZED, "Refresh token not found").into_response(),
+    let Some(refresh_token) = presented_refresh_token(&jar, client, payload.as_deref()) else {
+        return (StatusCode::UNAUTHORIZED, "Refresh token not found").into_response();
     };
 
-    match auth_service::refresh_access_token(&state.pool, refresh_token, &state.config.jwt_secret)
-        .await
+    match auth_service::refresh_access_token(
+        &state.pool,
+        &refresh_token,
+        &device,
+        &state.config.jwt,
+    )
+    .await
     {
-        Ok((token, refresh_token, user)) => {
-            let cookies = create_auth_cookies(token, refresh_token, true);
-            let mut updated_jar = jar;
-            for cookie in cookies {
-                updated_jar = updated_jar.add(cookie);
+        Ok((token, refresh_token, user)) => session_response(
+            jar,
+            client,
+            state.config.csrf_cookie_domain.as_deref(),
+            token,
+            refresh_token,
+            user,
+            true,
+        ),
+        Err(e) => {
+            if let AuthError::RefreshTokenReused { user_id } = e {
+                state.audit.record(
+                    AuditRecord::new(AuditEvent::RefreshTokenReused, &device).user(user_id),
+                );
             }
-            (StatusCode::OK, updated_jar, Json(AuthResponse { user })).into_response()
+            e.into_response()
         }
-        Err(e) => e.into_response(),
     }
 }
 
-pub async fn logout_handler(State(state): State<AppState>, jar: CookieJar) -> impl IntoResponse {
+pub async fn logout_handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    client: ClientType,
+    device: DeviceInfo,
+    headers: HeaderMap,
+    payload: Option<Json<RefreshTokenRequest>>,
+) -> impl IntoResponse {
     // Invalidate refresh token in database if present
-    if let Some(refresh_cookie) = jar.get(REFRESH_TOKEN_COOKIE_NAME) {
-        let _ = auth_service::invalidate_refresh_token(&state.pool, refresh_cookie.value()).await;
+    if let Some(refresh_token) = presented_refresh_token(&jar, client, payload.as_deref()) {
+        let _ = auth_service::invalidate_refresh_token(&state.pool, &refresh_token).await;
+
+        if let Some(user_id) = token_subject(&refresh_token, &state.config.jwt, TokenType::Refresh)
+        {
+            state
+                .audit
+                .record(AuditRecord::new(AuditEvent::LoggedOut, &device).user(user_id));
+        }
+    }
+
+    // Revoke the access token too, so a copy of it stops working now rather than at expiry.
+    // Only this token is revoked; the user's other sessions are not affected.
+    let access_token = match client {
+        ClientType::Browser => jar.get(ACCESS_TOKEN_COOKIE_NAME).map(|c| c.value()),
+        ClientType::Native => bearer_token(&headers),
+    };
+    if let Some(claims) = access_token
+        .and_then(|token| decode_jwt_with_type(token, &state.config.jwt, TokenType::Access).ok())
+        && let Ok(user_id) = Uuid::parse_str(&claims.sub)
+    {
+        let _ = state
+            .user_status_cache
+            .revoke_access_token(&state.pool, user_id, &claims)
+            .await;
     }
 
-    let cookies = remove_auth_cookies();
+    let cookies = remove_auth_cookies(state.config.csrf_cookie_domain.as_deref());
     let mut updated_jar = jar;
     for cookie in cookies {
         updated_jar = updated_jar.add(cookie);
@@ -0,0 +1,55 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    dtos::private::export::DataExportResponse, error::AppError, services::export_service,
+    state::AppState, utils::jwt::Claims,
+};
+
+/// Starts a personal data export. The archive is built in the background;
+/// poll the status endpoint for the download link.
+pub async fn request_export_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Unauthorized("Invalid user ID".into()))?;
+
+    let export = export_service::request_export(
+        &state.pool,
+        &state.s3_client,
+        &state.config.r2.bucket_name,
+        user_id,
+    )
+    .await?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(DataExportResponse::new(export, None)),
+    ))
+}
+
+pub async fn export_status_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(export_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Unauthorized("Invalid user ID".into()))?;
+
+    let (export, download_url) = export_service::get_export(
+        &state.pool,
+        &state.s3_client,
+        &state.config.r2.bucket_name,
+        user_id,
+        export_id,
+    )
+    .await?;
+
+    Ok(Json(DataExportResponse::new(export, download_url)))
+}
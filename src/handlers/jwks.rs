@@ -0,0 +1,10 @@
+use crate::state::AppState;
+use axum::{Json, extract::State, http::header, response::IntoResponse};
+
+/// Public keys sibling services use to verify our access tokens
+pub async fn jwks_handler(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(state.config.jwt.keys.jwks()),
+    )
+}
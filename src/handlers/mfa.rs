@@ -0,0 +1,123 @@
+use axum::{Extension, Json, extract::State, http::StatusCode, response::IntoResponse};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    dtos::private::mfa::{
+        ConfirmTotpRequest, DisableTotpRequest, MfaStatusResponse, RecoveryCodesResponse,
+        TotpSetupResponse,
+    },
+    error::{AppError, AuthError},
+    services::{
+        audit_service::{AuditEvent, AuditRecord},
+        auth::mfa_service,
+    },
+    state::AppState,
+    utils::{device::DeviceInfo, jwt::Claims, validation::format_validation_errors},
+};
+
+pub async fn mfa_status_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    let user_id = match Uuid::parse_str(&claims.sub) {
+        Ok(id) => id,
+        Err(_) => return AppError::Unauthorized("Invalid user ID".into()).into_response(),
+    };
+
+    match mfa_service::get_status(&state.pool, user_id).await {
+        Ok(status) => Json(MfaStatusResponse {
+            enabled: status.enabled,
+            recovery_codes_remaining: status.recovery_codes_remaining,
+        })
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn totp_setup_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    let user_id = match Uuid::parse_str(&claims.sub) {
+        Ok(id) => id,
+        Err(_) => return AppError::Unauthorized("Invalid user ID".into()).into_response(),
+    };
+
+    match mfa_service::start_totp_enrollment(&state.pool, user_id, &state.config.totp_issuer).await
+    {
+        Ok((secret, otpauth_uri)) => (
+            StatusCode::OK,
+            Json(TotpSetupResponse {
+                secret,
+                otpauth_uri,
+            }),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn totp_confirm_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    device: DeviceInfo,
+    Json(payload): Json<ConfirmTotpRequest>,
+) -> impl IntoResponse {
+    let user_id = match Uuid::parse_str(&claims.sub) {
+        Ok(id) => id,
+        Err(_) => return AppError::Unauthorized("Invalid user ID".into()).into_response(),
+    };
+
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+
+    match mfa_service::confirm_totp_enrollment(&state.pool, user_id, payload.code.trim()).await {
+        Ok(recovery_codes) => {
+            state
+                .audit
+                .record(AuditRecord::new(AuditEvent::MfaEnabled, &device).user(user_id));
+            (
+                StatusCode::OK,
+                Json(RecoveryCodesResponse { recovery_codes }),
+            )
+                .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn totp_disable_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    device: DeviceInfo,
+    Json(payload): Json<DisableTotpRequest>,
+) -> impl IntoResponse {
+    let user_id = match Uuid::parse_str(&claims.sub) {
+        Ok(id) => id,
+        Err(_) => return AppError::Unauthorized("Invalid user ID".into()).into_response(),
+    };
+
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+
+    match mfa_service::disable_totp(
+        &state.pool,
+        user_id,
+        payload.code.as_deref().map(str::trim),
+        payload.password.as_deref(),
+        &state.config.password_hash,
+    )
+    .await
+    {
+        Ok(()) => {
+            state
+                .audit
+                .record(AuditRecord::new(AuditEvent::MfaDisabled, &device).user(user_id));
+            (StatusCode::OK, "Two-factor authentication disabled").into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
@@ -1,3 +1,11 @@
+pub mod account;
+pub mod admin;
+pub mod api_key;
 pub mod auth;
+pub mod export;
 pub mod friend;
+pub mod jwks;
+pub mod mfa;
+pub mod oidc;
+pub mod passkey;
 pub mod profile;
@@ -0,0 +1,271 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use axum_extra::extract::cookie::CookieJar;
+use serde_json::json;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    config::OidcProviderConfig,
+    dtos::private::{
+        auth::{
+            request::OidcCallbackRequest,
+            response::{OidcAuthorizationResponse, OidcProviderResponse},
+        },
+        user::{IdentityResponse, LinkIdentityRequest},
+    },
+    error::AuthError,
+    handlers::auth::{clean_device_name, is_auth_failure, login_outcome_response},
+    services::{
+        audit_service::{AuditEvent, AuditRecord},
+        auth::{auth_service::LoginOutcome, oidc_service},
+    },
+    state::AppState,
+    utils::{
+        client::ClientType, device::DeviceInfo, jwt::Claims, validation::format_validation_errors,
+    },
+};
+
+fn provider_config<'a>(
+    state: &'a AppState,
+    provider: &str,
+) -> Result<&'a OidcProviderConfig, AuthError> {
+    state
+        .config
+        .oidc_provider(provider)
+        .ok_or(AuthError::UnknownOidcProvider)
+}
+
+fn user_id_from_claims(claims: &Claims) -> Result<Uuid, AuthError> {
+    Uuid::parse_str(&claims.sub).map_err(|_| AuthError::InvalidCredentials)
+}
+
+pub async fn list_providers_handler(State(state): State<AppState>) -> impl IntoResponse {
+    let providers: Vec<OidcProviderResponse> = state
+        .config
+        .oidc_providers
+        .iter()
+        .map(|provider| OidcProviderResponse {
+            name: provider.name.clone(),
+        })
+        .collect();
+
+    Json(providers)
+}
+
+pub async fn authorize_handler(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+) -> impl IntoResponse {
+    let config = match provider_config(&state, &provider) {
+        Ok(config) => config,
+        Err(e) => return e.into_response(),
+    };
+
+    match oidc_service::start_authorization(&state.pool, &state.oidc, config, None).await {
+        Ok(authorization_url) => (
+            StatusCode::OK,
+            Json(OidcAuthorizationResponse { authorization_url }),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn callback_handler(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    jar: CookieJar,
+    client: ClientType,
+    mut device: DeviceInfo,
+    Json(payload): Json<OidcCallbackRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+    let config = match provider_config(&state, &provider) {
+        Ok(config) => config,
+        Err(e) => return e.into_response(),
+    };
+
+    device.device_name = clean_device_name(payload.device_name.as_deref());
+
+    let result = oidc_service::login_with_provider(
+        &state.pool,
+        &state.oidc,
+        &state.s3_client,
+        &state.config.r2,
+        config,
+        payload.code.trim(),
+        payload.state.trim(),
+        &device,
+        &state.config.jwt,
+        state.config.account_deletion_grace_days,
+        state.config.email_provider_rules,
+    )
+    .await;
+
+    match result {
+        Ok(login) => {
+            if login.new_account {
+                state.audit.record(
+                    AuditRecord::new(AuditEvent::Registered, &device)
+                        .user(login.user_id)
+                        .details(json!({ "provider": config.name })),
+                );
+            }
+            if let LoginOutcome::Authenticated { .. } = &login.outcome {
+                state.audit.record(
+                    AuditRecord::new(AuditEvent::LoginSucceeded, &device)
+                        .user(login.user_id)
+                        .details(json!({ "method": "oidc", "provider": config.name })),
+                );
+            }
+            login_outcome_response(
+                jar,
+                client,
+                state.config.csrf_cookie_domain.as_deref(),
+                login.outcome,
+                payload.remember_me,
+            )
+        }
+        Err(e) => {
+            if is_auth_failure(&e) {
+                let details =
+                    json!({ "reason": e.to_string(), "method": "oidc", "provider": config.name });
+                state
+                    .audit
+                    .record(AuditRecord::new(AuditEvent::LoginFailed, &device).details(details));
+            }
+            e.into_response()
+        }
+    }
+}
+
+pub async fn list_identities_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    let user_id = match user_id_from_claims(&claims) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(),
+    };
+
+    match oidc_service::list_identities(&state.pool, user_id).await {
+        Ok(identities) => {
+            let response: Vec<IdentityResponse> = identities
+                .into_iter()
+                .map(|identity| IdentityResponse {
+                    provider: identity.provider,
+                    email: identity.email,
+                    created_at: identity.created_at,
+                    last_login_at: identity.last_login_at,
+                })
+                .collect();
+            Json(response).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn start_link_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(provider): Path<String>,
+) -> impl IntoResponse {
+    let user_id = match user_id_from_claims(&claims) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(),
+    };
+    let config = match provider_config(&state, &provider) {
+        Ok(config) => config,
+        Err(e) => return e.into_response(),
+    };
+
+    match oidc_service::start_authorization(&state.pool, &state.oidc, config, Some(user_id)).await {
+        Ok(authorization_url) => (
+            StatusCode::OK,
+            Json(OidcAuthorizationResponse { authorization_url }),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn complete_link_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(provider): Path<String>,
+    device: DeviceInfo,
+    Json(payload): Json<LinkIdentityRequest>,
+) -> impl IntoResponse {
+    let user_id = match user_id_from_claims(&claims) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+    let config = match provider_config(&state, &provider) {
+        Ok(config) => config,
+        Err(e) => return e.into_response(),
+    };
+
+    match oidc_service::link_identity(
+        &state.pool,
+        &state.oidc,
+        config,
+        user_id,
+        payload.code.trim(),
+        payload.state.trim(),
+    )
+    .await
+    {
+        Ok(identity) => {
+            state.audit.record(
+                AuditRecord::new(AuditEvent::IdentityLinked, &device)
+                    .user(user_id)
+                    .details(json!({ "provider": identity.provider })),
+            );
+            (
+                StatusCode::OK,
+                Json(IdentityResponse {
+                    provider: identity.provider,
+                    email: identity.email,
+                    created_at: identity.created_at,
+                    last_login_at: identity.last_login_at,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn unlink_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(provider): Path<String>,
+    device: DeviceInfo,
+) -> impl IntoResponse {
+    let user_id = match user_id_from_claims(&claims) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(),
+    };
+
+    match oidc_service::unlink_identity(&state.pool, user_id, &provider).await {
+        Ok(()) => {
+            state.audit.record(
+                AuditRecord::new(AuditEvent::IdentityUnlinked, &device)
+                    .user(user_id)
+                    .details(json!({ "provider": provider })),
+            );
+            (StatusCode::OK, "Provider unlinked").into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
@@ -0,0 +1,282 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use axum_extra::extract::cookie::CookieJar;
+use base64::{Engine as _, engine::general_purpose};
+use serde_json::json;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    constant::auth::WEBAUTHN_CHALLENGE_DURATION_MINUTES,
+    dtos::private::{
+        auth::{request::PasskeyLoginRequest, response::PasskeyLoginOptionsResponse},
+        passkey::{
+            AuthenticatorSelectionResponse, CredentialDescriptorResponse,
+            CredentialParameterResponse, PasskeyRegistrationOptionsResponse, PasskeyResponse,
+            PasskeyUserResponse, RegisterPasskeyRequest, RelyingPartyResponse,
+            RenamePasskeyRequest,
+        },
+    },
+    error::AuthError,
+    handlers::auth::{clean_device_name, is_auth_failure, session_response},
+    services::{
+        audit_service::{AuditEvent, AuditRecord},
+        auth::passkey_service::{self, PasskeyAssertion},
+    },
+    state::AppState,
+    utils::{
+        client::ClientType, device::DeviceInfo, jwt::Claims, validation::format_validation_errors,
+        webauthn::SUPPORTED_ALGORITHMS,
+    },
+};
+
+const PUBLIC_KEY_CREDENTIAL: &str = "public-key";
+/// Passkeys replace the password, so the authenticator must verify the user (PIN, biometrics)
+const USER_VERIFICATION: &str = "required";
+
+fn user_id_from_claims(claims: &Claims) -> Result<Uuid, AuthError> {
+    Uuid::parse_str(&claims.sub).map_err(|_| AuthError::InvalidCredentials)
+}
+
+fn ceremony_timeout_ms() -> i64 {
+    WEBAUTHN_CHALLENGE_DURATION_MINUTES * 60 * 1000
+}
+
+pub async fn passkey_login_options_handler(State(state): State<AppState>) -> impl IntoResponse {
+    match passkey_service::start_authentication(&state.pool).await {
+        Ok(challenge) => Json(PasskeyLoginOptionsResponse {
+            challenge,
+            rp_id: state.config.webauthn.rp_id.clone(),
+            timeout: ceremony_timeout_ms(),
+            user_verification: USER_VERIFICATION,
+            allow_credentials: Vec::new(),
+        })
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn passkey_login_handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    client: ClientType,
+    mut device: DeviceInfo,
+    Json(payload): Json<PasskeyLoginRequest>,
+) -> impl IntoResponse {
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+
+    device.device_name = clean_device_name(payload.device_name.as_deref());
+
+    let credential = &payload.credential;
+    let assertion = PasskeyAssertion {
+        credential_id: &credential.raw_id,
+        client_data_json: &credential.response.client_data_json,
+        authenticator_data: &credential.response.authenticator_data,
+        signature: &credential.response.signature,
+        user_handle: credential.response.user_handle.as_deref(),
+    };
+
+    match passkey_service::finish_authentication(
+        &state.pool,
+        &state.config.webauthn,
+        &assertion,
+        &device,
+        &state.config.jwt,
+    )
+    .await
+    {
+        Ok((token, refresh_token, user)) => {
+            state.audit.record(
+                AuditRecord::new(AuditEvent::LoginSucceeded, &device)
+                    .user(user.id)
+                    .details(json!({ "method": "passkey" })),
+            );
+
+            session_response(
+                jar,
+                client,
+                state.config.csrf_cookie_domain.as_deref(),
+                token,
+                refresh_token,
+                user,
+                payload.remember_me,
+            )
+        }
+        Err(e) => {
+            if is_auth_failure(&e) {
+                let details = json!({ "reason": e.to_string(), "method": "passkey" });
+                state
+                    .audit
+                    .record(AuditRecord::new(AuditEvent::LoginFailed, &device).details(details));
+            }
+            e.into_response()
+        }
+    }
+}
+
+pub async fn passkey_registration_options_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    let user_id = match user_id_from_claims(&claims) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(),
+    };
+
+    match passkey_service::start_registration(&state.pool, user_id).await {
+        Ok(options) => Json(PasskeyRegistrationOptionsResponse {
+            challenge: options.challenge,
+            rp: RelyingPartyResponse {
+                id: state.config.webauthn.rp_id.clone(),
+                name: state.config.webauthn.rp_name.clone(),
+            },
+            user: PasskeyUserResponse {
+                id: passkey_service::user_handle(options.user.id),
+                name: options.user.email.clone(),
+                display_name: options.user.email,
+            },
+            pub_key_cred_params: SUPPORTED_ALGORITHMS
+                .iter()
+                .map(|&alg| CredentialParameterResponse {
+                    credential_type: PUBLIC_KEY_CREDENTIAL,
+                    alg,
+                })
+                .collect(),
+            timeout: ceremony_timeout_ms(),
+            exclude_credentials: options
+                .existing
+                .into_iter()
+                .map(|credential| CredentialDescriptorResponse {
+                    credential_type: PUBLIC_KEY_CREDENTIAL,
+                    id: general_purpose::URL_SAFE_NO_PAD.encode(&credential.credential_id),
+                    transports: credential.transports,
+                })
+                .collect(),
+            // Discoverable credentials allow signing in without typing an email first
+            authenticator_selection: AuthenticatorSelectionResponse {
+                resident_key: "required",
+                require_resident_key: true,
+                user_verification: USER_VERIFICATION,
+            },
+            attestation: "none",
+        })
+        .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn register_passkey_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    device: DeviceInfo,
+    Json(payload): Json<RegisterPasskeyRequest>,
+) -> impl IntoResponse {
+    let user_id = match user_id_from_claims(&claims) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+
+    let name = payload
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or("Passkey");
+    let response = &payload.credential.response;
+
+    match passkey_service::finish_registration(
+        &state.pool,
+        &state.config.webauthn,
+        user_id,
+        &response.client_data_json,
+        &response.attestation_object,
+        &response.transports,
+        name,
+    )
+    .await
+    {
+        Ok(passkey) => {
+            state.audit.record(
+                AuditRecord::new(AuditEvent::PasskeyAdded, &device)
+                    .user(user_id)
+                    .details(json!({ "passkey_id": passkey.id })),
+            );
+            (StatusCode::CREATED, Json(PasskeyResponse::from(passkey))).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn list_passkeys_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+) -> impl IntoResponse {
+    let user_id = match user_id_from_claims(&claims) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(),
+    };
+
+    match passkey_service::list_passkeys(&state.pool, user_id).await {
+        Ok(passkeys) => {
+            let response: Vec<PasskeyResponse> =
+                passkeys.into_iter().map(PasskeyResponse::from).collect();
+            Json(response).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn rename_passkey_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(passkey_id): Path<Uuid>,
+    Json(payload): Json<RenamePasskeyRequest>,
+) -> impl IntoResponse {
+    let user_id = match user_id_from_claims(&claims) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(errors) = payload.validate() {
+        return AuthError::ValidationError(format_validation_errors(errors)).into_response();
+    }
+
+    match passkey_service::rename_passkey(&state.pool, user_id, passkey_id, payload.name.trim())
+        .await
+    {
+        Ok(passkey) => Json(PasskeyResponse::from(passkey)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub async fn remove_passkey_handler(
+    State(state): State<AppState>,
+    Extension(claims): Extension<Claims>,
+    Path(passkey_id): Path<Uuid>,
+    device: DeviceInfo,
+) -> impl IntoResponse {
+    let user_id = match user_id_from_claims(&claims) {
+        Ok(id) => id,
+        Err(e) => return e.into_response(),
+    };
+
+    match passkey_service::remove_passkey(&state.pool, user_id, passkey_id).await {
+        Ok(()) => {
+            state.audit.record(
+                AuditRecord::new(AuditEvent::PasskeyRemoved, &device)
+                    .user(user_id)
+                    .details(json!({ "passkey_id": passkey_id })),
+            );
+            (StatusCode::OK, "Passkey removed").into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
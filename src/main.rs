@@ -1,12 +1,21 @@
-use axum::{Router, http::Method};
+use axum::{
+    Router,
+    http::{HeaderName, Method},
+};
 use sqlx::postgres::PgPoolOptions;
 use std::{sync::Arc, time::Duration};
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use web_be::{
     config::Config,
-    routes::{private_routes, public_routes},
+    constant::auth::CSRF_HEADER,
+    routes::{private_routes, public_routes, well_known_routes},
+    services::{
+        audit_service::AuditService,
+        auth::{account_status_service::UserStatusCache, auth_service, oidc_service::OidcClient},
+        mail::sender::build_mail_sender,
+    },
     state::AppState,
-    utils::s3::get_r2_client,
+    utils::{client::CLIENT_IP_KEY_EXTRACTOR, s3::get_r2_client},
 };
 
 #[tokio::main]
@@ -29,6 +38,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .await?;
     sqlx::migrate!().run(&pool).await?;
 
+    // Lookups by email only work if stored addresses follow the configured normalization rules
+    let renormalized =
+        auth_service::normalize_stored_emails(&pool, config_arc.email_provider_rules).await?;
+    if renormalized > 0 {
+        println!("Updated {} normalized email addresses", renormalized);
+    }
+
     // Warm up database connections
     let _ = sqlx::query("SELECT 1").fetch_one(&pool).await?;
 
@@ -48,6 +64,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             axum::http::header::ACCEPT,
             axum::http::header::ORIGIN,
             axum::http::header::COOKIE,
+            HeaderName::from_static(CSRF_HEADER),
         ])
         .allow_credentials(true);
 
@@ -58,18 +75,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Allow bursts with up to 5 requests per IP and replenishes at 1 request per second
     let rate_limit_config = std::sync::Arc::new(
         tower_governor::governor::GovernorConfigBuilder::default()
-            .key_extractor(tower_governor::key_extractor::SmartIpKeyExtractor)
+            .key_extractor(CLIENT_IP_KEY_EXTRACTOR)
             .per_second(1)
             .burst_size(5)
             .finish()
             .expect("Failed to build rate limit config"),
     );
 
+    let mailer = build_mail_sender(&config_arc.mail);
+
     let app_state = AppState {
         pool: pool.clone(),
         config: config_arc.clone(),
         s3_client,
         rate_limit_config,
+        mailer,
+        user_status_cache: UserStatusCache::new(),
+        audit: AuditService::start(pool.clone()),
+        oidc: OidcClient::new()?,
     };
 
     // Setup Axum router
@@ -78,12 +101,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "/api",
             public_routes(app_state.clone()).merge(private_routes(app_state.clone())),
         )
+        // Served outside /api at the path verifiers expect
+        .nest("/.well-known", well_known_routes(app_state.clone()))
         .layer(cors)
         //TODO: Remove logging layer in production
         .layer(TraceLayer::new_for_http());
 
     // Initialize and start scheduler
-    let sched = web_be::services::scheduler::init_scheduler(pool)
+    let sched = web_be::services::scheduler::init_scheduler(app_state.clone())
         .await
         .map_err(|e| format!("Failed to initialize scheduler: {}", e))?;
 
@@ -1,15 +1,48 @@
 use crate::{
-    constant::auth::ACCESS_TOKEN_COOKIE_NAME,
+    constant::{api_key::API_KEY_HEADER, auth::ACCESS_TOKEN_COOKIE_NAME},
+    error::AuthError,
+    services::{api_key_service, auth::account_status_service::AccountStatus},
     state::AppState,
-    utils::jwt::{TokenType, decode_jwt_with_type},
+    utils::{
+        client::bearer_token,
+        jwt::{Claims, TokenType, decode_jwt_with_type},
+    },
 };
 use axum::{
     extract::{Request, State},
     http::StatusCode,
     middleware::Next,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
 };
 use axum_extra::extract::cookie::CookieJar;
+use uuid::Uuid;
+
+/// Claims of the access token sent as a bearer token (native clients) or cookie (browsers).
+/// The error is the message of the 401 response.
+fn access_token_claims(
+    state: &AppState,
+    jar: &CookieJar,
+    req: &Request,
+) -> Result<Claims, &'static str> {
+    let access_token = bearer_token(req.headers())
+        .or_else(|| {
+            jar.get(ACCESS_TOKEN_COOKIE_NAME)
+                .map(|cookie| cookie.value())
+        })
+        .ok_or("Missing access token")?;
+
+    decode_jwt_with_type(access_token, &state.config.jwt, TokenType::Access)
+        .map_err(|_| "Invalid access token")
+}
+
+/// Claims standing in for the owner of the key sent in `X-API-Key`
+async fn api_key_claims(state: &AppState, raw_key: &str) -> Result<Claims, Response> {
+    match api_key_service::authenticate(&state.pool, raw_key.trim(), &state.config.jwt).await {
+        Ok(Some(claims)) => Ok(claims),
+        Ok(None) => Err((StatusCode::UNAUTHORIZED, "Invalid API key").into_response()),
+        Err(e) => Err(e.into_response()),
+    }
+}
 
 pub async fn auth_middleware(
     State(state): State<AppState>,
@@ -17,18 +50,39 @@ pub async fn auth_middleware(
     mut req: Request,
     next: Next,
 ) -> Result<impl IntoResponse, impl IntoResponse> {
-    let access_token = match jar.get(ACCESS_TOKEN_COOKIE_NAME) {
-        Some(cookie) => cookie.value(),
-        None => {
-            return Err((StatusCode::UNAUTHORIZED, "Missing access token").into_response());
-        }
+    // API keys only reach routes that opt in with `RequireScope`; see `middlewares::scope`
+    let api_key = req
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let claims = match api_key {
+        Some(raw_key) => api_key_claims(&state, &raw_key).await?,
+        None => access_token_claims(&state, &jar, &req)
+            .map_err(|message| (StatusCode::UNAUTHORIZED, message).into_response())?,
+    };
+
+    let Ok(user_id) = Uuid::parse_str(&claims.sub) else {
+        return Err((StatusCode::UNAUTHORIZED, "Invalid access token").into_response());
     };
 
-    match decode_jwt_with_type(access_token, &state.config.jwt_secret, TokenType::Access) {
-        Ok(claims) => {
-            req.extensions_mut().insert(claims);
-            Ok(next.run(req).await)
+    // Access tokens stay valid until they expire, so account status and revocation are
+    // re-checked on every request
+    let status = match state.user_status_cache.get(&state.pool, user_id).await {
+        Ok(status) => status,
+        Err(e) => return Err(e.into_response()),
+    };
+    match status.account {
+        AccountStatus::Active => {}
+        AccountStatus::Suspended => return Err(AuthError::AccountSuspended.into_response()),
+        AccountStatus::Deleted => {
+            return Err((StatusCode::UNAUTHORIZED, "Invalid access token").into_response());
         }
-        Err(_) => Err((StatusCode::UNAUTHORIZED, "Invalid access token").into_response()),
     }
+    if !claims.is_api_key() && status.is_revoked(&claims) {
+        return Err((StatusCode::UNAUTHORIZED, "Access token revoked").into_response());
+    }
+
+    req.extensions_mut().insert(claims);
+    Ok(next.run(req).await)
 }
@@ -0,0 +1,44 @@
+use crate::{
+    constant::{
+        api_key::API_KEY_HEADER,
+        auth::{CSRF_COOKIE_NAME, CSRF_HEADER},
+    },
+    error::AppError,
+    utils::{client::bearer_token, token::constant_time_eq},
+};
+use axum::{
+    extract::Request,
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::cookie::CookieJar;
+
+/// Double-submit CSRF check for cookie-authenticated requests.
+/// Unsafe methods must echo the CSRF cookie in the `X-CSRF-Token` header, which a cross-site
+/// form cannot do. Bearer tokens and API keys are not sent by the browser on its own,
+/// so those requests are exempt.
+pub async fn csrf_middleware(jar: CookieJar, req: Request, next: Next) -> Response {
+    let safe_method = matches!(
+        *req.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    );
+    let headers = req.headers();
+    if safe_method || bearer_token(headers).is_some() || headers.contains_key(API_KEY_HEADER) {
+        return next.run(req).await;
+    }
+
+    let cookie = jar.get(CSRF_COOKIE_NAME).map(|cookie| cookie.value());
+    let header = headers
+        .get(CSRF_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    match (cookie, header) {
+        (Some(cookie), Some(header))
+            if !cookie.is_empty() && constant_time_eq(cookie.as_bytes(), header.as_bytes()) =>
+        {
+            next.run(req).await
+        }
+        _ => AppError::Forbidden("Missing or invalid CSRF token".into()).into_response(),
+    }
+}
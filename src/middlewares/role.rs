@@ -0,0 +1,28 @@
+use crate::{error::AppError, models::role::Role, utils::jwt::Claims};
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+/// Minimum role required by a group of routes.
+/// Attach after `auth_middleware` (which provides the claims), e.g.
+/// `.route_layer(from_fn_with_state(RequireRole(Role::Admin), require_role))`.
+#[derive(Debug, Clone, Copy)]
+pub struct RequireRole(pub Role);
+
+pub async fn require_role(
+    State(RequireRole(required)): State<RequireRole>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(claims) = req.extensions().get::<Claims>() else {
+        return AppError::Unauthorized("Missing access token".into()).into_response();
+    };
+
+    if !claims.role().satisfies(required) {
+        return AppError::Forbidden("Insufficient permissions".into()).into_response();
+    }
+
+    next.run(req).await
+}
@@ -0,0 +1,45 @@
+use crate::{error::AppError, models::api_key::ApiKeyScope, utils::jwt::Claims};
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+/// Scope an API key needs for a group of routes; interactive sessions always pass.
+/// Attach after `auth_middleware`, e.g.
+/// `.route_layer(from_fn_with_state(RequireScope(ApiKeyScope::FriendsRead), require_scope))`.
+#[derive(Debug, Clone, Copy)]
+pub struct RequireScope(pub ApiKeyScope);
+
+pub async fn require_scope(
+    State(RequireScope(required)): State<RequireScope>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(claims) = req.extensions().get::<Claims>() else {
+        return AppError::Unauthorized("Missing access token".into()).into_response();
+    };
+
+    if !claims.has_scope(required) {
+        return AppError::Forbidden(
+            format!("API key lacks the '{}' scope", required.as_str()).into(),
+        )
+        .into_response();
+    }
+
+    next.run(req).await
+}
+
+/// Rejects API keys on routes that manage the account itself (credentials, sessions, keys).
+/// Attach after `auth_middleware` with `.route_layer(from_fn(require_session))`.
+pub async fn require_session(req: Request, next: Next) -> Response {
+    let Some(claims) = req.extensions().get::<Claims>() else {
+        return AppError::Unauthorized("Missing access token".into()).into_response();
+    };
+
+    if claims.is_api_key() {
+        return AppError::Forbidden("API keys cannot access this endpoint".into()).into_response();
+    }
+
+    next.run(req).await
+}
@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// What an API key may do. Keys can only reach routes that require one of their scopes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ApiKeyScope {
+    #[serde(rename = "profile:read")]
+    ProfileRead,
+    #[serde(rename = "profile:write")]
+    ProfileWrite,
+    #[serde(rename = "friends:read")]
+    FriendsRead,
+    #[serde(rename = "friends:write")]
+    FriendsWrite,
+}
+
+impl ApiKeyScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyScope::ProfileRead => "profile:read",
+            ApiKeyScope::ProfileWrite => "profile:write",
+            ApiKeyScope::FriendsRead => "friends:read",
+            ApiKeyScope::FriendsWrite => "friends:write",
+        }
+    }
+
+    /// Parses a stored scope name, or None if it is not a known scope
+    pub fn parse(value: &str) -> Option<ApiKeyScope> {
+        match value {
+            "profile:read" => Some(ApiKeyScope::ProfileRead),
+            "profile:write" => Some(ApiKeyScope::ProfileWrite),
+            "friends:read" => Some(ApiKeyScope::FriendsRead),
+            "friends:write" => Some(ApiKeyScope::FriendsWrite),
+            _ => None,
+        }
+    }
+
+    /// Whether this scope grants `required` (write access includes read access)
+    pub fn satisfies(&self, required: ApiKeyScope) -> bool {
+        *self == required
+            || matches!(
+                (self, required),
+                (ApiKeyScope::ProfileWrite, ApiKeyScope::ProfileRead)
+                    | (ApiKeyScope::FriendsWrite, ApiKeyScope::FriendsRead)
+            )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ApiKeyModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    #[serde(skip_serializing)]
+    pub secret_hash: String,
+    pub scopes: Vec<String>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ApiKeyModel {
+    /// Granted scopes; unknown names (e.g. from a removed scope) grant nothing
+    pub fn scopes(&self) -> Vec<ApiKeyScope> {
+        self.scopes
+            .iter()
+            .filter_map(|scope| ApiKeyScope::parse(scope))
+            .collect()
+    }
+}
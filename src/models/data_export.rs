@@ -0,0 +1,23 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+pub const EXPORT_STATUS_PENDING: &str = "pending";
+pub const EXPORT_STATUS_COMPLETED: &str = "completed";
+pub const EXPORT_STATUS_FAILED: &str = "failed";
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct DataExportModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// One of `pending`, `completed` or `failed`
+    pub status: String,
+    /// Location of the archive in R2, set once the export has completed
+    pub object_key: Option<String>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// The archive is deleted after this time
+    pub expires_at: Option<DateTime<Utc>>,
+}
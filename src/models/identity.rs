@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct UserIdentityModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Name of the provider as configured, e.g. `google`
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_login_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct OidcAuthRequestModel {
+    pub state_hash: String,
+    pub provider: String,
+    pub code_verifier: String,
+    pub nonce: String,
+    pub link_user_id: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
@@ -1,4 +1,16 @@
+pub mod account_deletion;
+pub mod api_key;
+pub mod audit;
+pub mod data_export;
 pub mod friend;
+pub mod identity;
+pub mod login_throttle;
+pub mod magic_link;
+pub mod mfa;
+pub mod password_reset;
 pub mod profile;
+pub mod role;
 pub mod token;
 pub mod user;
+pub mod verification;
+pub mod webauthn;
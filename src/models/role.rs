@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+/// Authorization level of an account, stored in `users_auth.role`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    User,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Admin => "admin",
+        }
+    }
+
+    /// Parses a stored role name, or None if it is not a known role
+    pub fn parse(value: &str) -> Option<Role> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "user" => Some(Role::User),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+
+    /// Reads a role from the database, falling back to the least privileged role
+    pub fn from_db(value: &str) -> Role {
+        Role::parse(value).unwrap_or_else(|| {
+            tracing::warn!(role = value, "Unknown role in users_auth, treating as user");
+            Role::User
+        })
+    }
+
+    /// Whether this role grants everything `required` grants (admins can do what users can)
+    pub fn satisfies(&self, required: Role) -> bool {
+        match required {
+            Role::User => true,
+            Role::Admin => *self == Role::Admin,
+        }
+    }
+}
@@ -7,8 +7,28 @@ use uuid::Uuid;
 pub struct RefreshToken {
     pub id: Uuid,
     pub user_id: Uuid,
+    /// All tokens produced by rotating the same login share a family
+    pub family_id: Uuid,
     pub token_hash: String,
     pub created_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
     pub used: bool,
+    /// Set when the session was signed out or revoked, as opposed to the token being rotated
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub device_name: Option<String>,
+    pub last_used_at: DateTime<Utc>,
+}
+
+/// An active login (one refresh token family) as shown to the user
+#[derive(Debug, Serialize, FromRow)]
+pub struct SessionModel {
+    pub family_id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub device_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
 }
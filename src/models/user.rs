@@ -6,14 +6,28 @@ use uuid::Uuid;
 pub struct UserModel {
     pub id: Uuid,
     pub email: String,
+    /// Lookup key derived from `email` (see `utils::email`); None while it conflicts with
+    /// another account's
     #[serde(skip_serializing)]
-    pub password_hash: String,
+    pub email_normalized: Option<String>,
+    /// None for accounts that have only ever signed in through an external provider
+    #[serde(skip_serializing)]
+    pub password_hash: Option<String>,
     #[serde(skip_serializing)]
     pub role: String,
     #[serde(skip_serializing)]
     pub is_active: bool,
     #[serde(skip_serializing)]
     pub is_deleted: bool,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    /// Account that owns this account's normalized address, if the two collided
+    #[serde(skip_serializing)]
+    pub email_conflict_with: Option<Uuid>,
+    /// Access tokens issued with a lower version are rejected
+    #[serde(skip_serializing)]
+    pub token_version: i32,
+    #[serde(skip_serializing)]
+    pub deleted_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing)]
     pub created_at: DateTime<Utc>,
     #[serde(skip_serializing)]
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Why a WebAuthn challenge was issued; a challenge is only accepted for the same ceremony
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChallengePurpose {
+    Registration,
+    Authentication,
+}
+
+impl ChallengePurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChallengePurpose::Registration => "registration",
+            ChallengePurpose::Authentication => "authentication",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct WebauthnCredentialModel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    #[serde(skip_serializing)]
+    pub credential_id: Vec<u8>,
+    #[serde(skip_serializing)]
+    pub public_key: Vec<u8>,
+    pub algorithm: i32,
+    pub sign_count: i64,
+    pub transports: Vec<String>,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct WebauthnChallengeModel {
+    pub challenge_hash: String,
+    pub purpose: String,
+    pub user_id: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,74 @@
+use crate::models::account_deletion::AccountDeletionToken;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub async fn create_token(
+    pool: &PgPool,
+    user_id: Uuid,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<AccountDeletionToken, sqlx::Error> {
+    sqlx::query_as::<_, AccountDeletionToken>(
+        r#"
+        INSERT INTO account_deletion_tokens (user_id, token_hash, expires_at)
+        VALUES ($1, $2, $3)
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .bind(token_hash)
+    .bind(expires_at)
+    .fetch_one(pool)
+    .await
+}
+
+/// Atomically consumes an unused, unexpired token issued to the user and returns it.
+/// Returns None if the token does not exist, belongs to someone else, was already used or has expired.
+pub async fn consume_token(
+    pool: &PgPool,
+    user_id: Uuid,
+    token_hash: &str,
+) -> Result<Option<AccountDeletionToken>, sqlx::Error> {
+    sqlx::query_as::<_, AccountDeletionToken>(
+        r#"
+        UPDATE account_deletion_tokens
+        SET used_at = NOW()
+        WHERE token_hash = $1 AND user_id = $2 AND used_at IS NULL AND expires_at > NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(token_hash)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Invalidates all outstanding links for a user so only the newest one works
+pub async fn invalidate_user_tokens(pool: &PgPool, user_id: Uuid) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE account_deletion_tokens SET used_at = NOW() WHERE user_id = $1 AND used_at IS NULL",
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn delete_expired_tokens(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM account_deletion_tokens
+        WHERE id IN (
+            SELECT id FROM account_deletion_tokens
+            WHERE expires_at < NOW() OR used_at IS NOT NULL
+            LIMIT 1000
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,92 @@
+use crate::models::api_key::ApiKeyModel;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub async fn create_key(
+    pool: &PgPool,
+    user_id: Uuid,
+    name: &str,
+    prefix: &str,
+    secret_hash: &str,
+    scopes: &[String],
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<ApiKeyModel, sqlx::Error> {
+    sqlx::query_as::<_, ApiKeyModel>(
+        "INSERT INTO api_keys (user_id, name, prefix, secret_hash, scopes, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *",
+    )
+    .bind(user_id)
+    .bind(name)
+    .bind(prefix)
+    .bind(secret_hash)
+    .bind(scopes)
+    .bind(expires_at)
+    .fetch_one(pool)
+    .await
+}
+
+/// Keys that have not been revoked, newest first. Expired keys are included so users can see them.
+pub async fn find_user_keys(pool: &PgPool, user_id: Uuid) -> Result<Vec<ApiKeyModel>, sqlx::Error> {
+    sqlx::query_as::<_, ApiKeyModel>(
+        "SELECT * FROM api_keys WHERE user_id = $1 AND revoked_at IS NULL ORDER BY created_at DESC",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn count_user_keys(pool: &PgPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM api_keys WHERE user_id = $1 AND revoked_at IS NULL")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+}
+
+/// Finds a key that is neither revoked nor expired
+pub async fn find_usable_key_by_hash(
+    pool: &PgPool,
+    secret_hash: &str,
+) -> Result<Option<ApiKeyModel>, sqlx::Error> {
+    sqlx::query_as::<_, ApiKeyModel>(
+        "SELECT * FROM api_keys
+        WHERE secret_hash = $1 AND revoked_at IS NULL AND (expires_at IS NULL OR expires_at > NOW())",
+    )
+    .bind(secret_hash)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Records a use of the key unless one was already recorded after `recorded_after`
+pub async fn touch_last_used(
+    pool: &PgPool,
+    key_id: Uuid,
+    recorded_after: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE api_keys SET last_used_at = NOW()
+        WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < $2)",
+    )
+    .bind(key_id)
+    .bind(recorded_after)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Returns the number of revoked keys (0 if the key does not belong to the user)
+pub async fn revoke_user_key(
+    pool: &PgPool,
+    user_id: Uuid,
+    key_id: Uuid,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE api_keys SET revoked_at = NOW() WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+    )
+    .bind(key_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,61 @@
+use crate::{models::audit::AuditEventModel, utils::device::DeviceInfo};
+use chrono::{DateTime, Utc};
+use sqlx::{Error, PgPool};
+use uuid::Uuid;
+
+pub async fn insert_event(
+    pool: &PgPool,
+    event_type: &str,
+    actor_user_id: Option<Uuid>,
+    subject_user_id: Option<Uuid>,
+    device: &DeviceInfo,
+    details: &serde_json::Value,
+) -> Result<(), Error> {
+    sqlx::query(
+        "INSERT INTO audit_events (event_type, actor_user_id, subject_user_id, ip_address, user_agent, details)
+        VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(event_type)
+    .bind(actor_user_id)
+    .bind(subject_user_id)
+    .bind(&device.ip_address)
+    .bind(&device.user_agent)
+    .bind(details)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Events concerning a user, newest first, paged with a keyset cursor on (created_at, id)
+pub async fn find_for_subject(
+    pool: &PgPool,
+    subject_user_id: Uuid,
+    cursor: Option<(DateTime<Utc>, Uuid)>,
+    limit: i32,
+) -> Result<Vec<AuditEventModel>, Error> {
+    let (cursor_time, cursor_id) = cursor.unzip();
+
+    sqlx::query_as::<_, AuditEventModel>(
+        r#"
+        SELECT * FROM audit_events
+        WHERE subject_user_id = $1
+          AND ($2::timestamptz IS NULL OR (created_at, id) < ($2, $3))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $4
+        "#,
+    )
+    .bind(subject_user_id)
+    .bind(cursor_time)
+    .bind(cursor_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn delete_older_than(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64, Error> {
+    let result = sqlx::query("DELETE FROM audit_events WHERE created_at < $1")
+        .bind(cutoff)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,128 @@
+use crate::models::data_export::{
+    DataExportModel, EXPORT_STATUS_COMPLETED, EXPORT_STATUS_FAILED, EXPORT_STATUS_PENDING,
+};
+use chrono::{DateTime, Utc};
+use sqlx::{Error, PgPool};
+use uuid::Uuid;
+
+/// Creates a pending export, or returns None if the user already has one in progress
+pub async fn create_export(pool: &PgPool, user_id: Uuid) -> Result<Option<DataExportModel>, Error> {
+    sqlx::query_as::<_, DataExportModel>(
+        "INSERT INTO data_exports (user_id, status) VALUES ($1, $2)
+        ON CONFLICT (user_id) WHERE status = 'pending' DO NOTHING
+        RETURNING *",
+    )
+    .bind(user_id)
+    .bind(EXPORT_STATUS_PENDING)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn find_pending_export(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Option<DataExportModel>, Error> {
+    sqlx::query_as::<_, DataExportModel>(
+        "SELECT * FROM data_exports WHERE user_id = $1 AND status = $2",
+    )
+    .bind(user_id)
+    .bind(EXPORT_STATUS_PENDING)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn find_export(
+    pool: &PgPool,
+    user_id: Uuid,
+    export_id: Uuid,
+) -> Result<Option<DataExportModel>, Error> {
+    sqlx::query_as::<_, DataExportModel>(
+        "SELECT * FROM data_exports WHERE id = $1 AND user_id = $2",
+    )
+    .bind(export_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn mark_completed(
+    pool: &PgPool,
+    export_id: Uuid,
+    object_key: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    sqlx::query(
+        "UPDATE data_exports SET status = $1, object_key = $2, completed_at = NOW(), expires_at = $3 WHERE id = $4",
+    )
+    .bind(EXPORT_STATUS_COMPLETED)
+    .bind(object_key)
+    .bind(expires_at)
+    .bind(export_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn mark_failed(pool: &PgPool, export_id: Uuid, error: &str) -> Result<(), Error> {
+    sqlx::query(
+        "UPDATE data_exports SET status = $1, error = $2, completed_at = NOW() WHERE id = $3",
+    )
+    .bind(EXPORT_STATUS_FAILED)
+    .bind(error)
+    .bind(export_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Marks the user's pending export as failed if it was started before `started_before`,
+/// so an export whose job did not survive (e.g. a restart) can be replaced
+pub async fn fail_interrupted_exports(
+    pool: &PgPool,
+    user_id: Uuid,
+    started_before: DateTime<Utc>,
+) -> Result<u64, Error> {
+    let result = sqlx::query(
+        "UPDATE data_exports SET status = $1, error = $2, completed_at = NOW()
+        WHERE user_id = $3 AND status = $4 AND created_at < $5",
+    )
+    .bind(EXPORT_STATUS_FAILED)
+    .bind("Export interrupted")
+    .bind(user_id)
+    .bind(EXPORT_STATUS_PENDING)
+    .bind(started_before)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Exports whose archive has expired, plus failed or abandoned ones older than `stale_before`
+pub async fn find_expired_exports(
+    pool: &PgPool,
+    stale_before: DateTime<Utc>,
+) -> Result<Vec<DataExportModel>, Error> {
+    sqlx::query_as::<_, DataExportModel>(
+        "SELECT * FROM data_exports WHERE expires_at < NOW() OR (expires_at IS NULL AND created_at < $1)",
+    )
+    .bind(stale_before)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn delete_export(pool: &PgPool, export_id: Uuid) -> Result<(), Error> {
+    sqlx::query("DELETE FROM data_exports WHERE id = $1")
+        .bind(export_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn find_user_exports(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<DataExportModel>, Error> {
+    sqlx::query_as::<_, DataExportModel>("SELECT * FROM data_exports WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+}
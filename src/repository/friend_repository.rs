@@ -82,6 +82,7 @@ pub async fn get_friends(
                 p.user_id, p.full_name, p.avatar_url, f.status, f.created_at
             FROM friendships f
             JOIN profiles p ON (f.user_id = p.user_id OR f.friend_id = p.user_id)
+            JOIN users_auth u ON u.id = p.user_id AND u.is_deleted = FALSE
             WHERE (f.user_id = $1 OR f.friend_id = $1)
               AND f.status = 'accepted'
               AND p.user_id != $1
@@ -101,6 +102,7 @@ pub async fn get_friends(
                 p.user_id, p.full_name, p.avatar_url, f.status, f.created_at
             FROM friendships f
             JOIN profiles p ON (f.user_id = p.user_id OR f.friend_id = p.user_id)
+            JOIN users_auth u ON u.id = p.user_id AND u.is_deleted = FALSE
             WHERE (f.user_id = $1 OR f.friend_id = $1)
               AND f.status = 'accepted'
               AND p.user_id != $1
@@ -125,7 +127,8 @@ pub async fn get_pending_requests(
         SELECT 
             p.user_id, p.full_name, p.avatar_url, f.status, f.created_at
         FROM friendships f
-        JOIN profiles p ON f.user_id = p.user_id 
+        JOIN profiles p ON f.user_id = p.user_id
+        JOIN users_auth u ON u.id = p.user_id AND u.is_deleted = FALSE
         WHERE f.friend_id = $1 AND f.status = 'pending'
         ORDER BY f.created_at DESC
         "#,
@@ -146,6 +149,7 @@ pub async fn get_sent_requests(
             p.user_id, p.full_name, p.avatar_url, f.status, f.created_at
         FROM friendships f
         JOIN profiles p ON f.friend_id = p.user_id
+        JOIN users_auth u ON u.id = p.user_id AND u.is_deleted = FALSE
         WHERE f.user_id = $1 AND f.status = 'pending'
         ORDER BY f.created_at DESC
         "#,
@@ -154,3 +158,40 @@ pub async fn get_sent_requests(
     .fetch_all(pool)
     .await
 }
+
+/// Every friendship or request the user is part of, in either direction
+pub async fn find_all_for_user(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<FriendshipModel>, Error> {
+    sqlx::query_as::<_, FriendshipModel>(
+        "SELECT * FROM friendships WHERE user_id = $1 OR friend_id = $1 ORDER BY created_at",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Friendship counts of a user, for admin views
+#[derive(sqlx::FromRow)]
+pub struct FriendCounts {
+    pub friends: i64,
+    pub pending_received: i64,
+    pub pending_sent: i64,
+}
+
+pub async fn count_for_user(pool: &PgPool, user_id: Uuid) -> Result<FriendCounts, Error> {
+    sqlx::query_as::<_, FriendCounts>(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE status = 'accepted') AS friends,
+            COUNT(*) FILTER (WHERE status = 'pending' AND friend_id = $1) AS pending_received,
+            COUNT(*) FILTER (WHERE status = 'pending' AND user_id = $1) AS pending_sent
+        FROM friendships
+        WHERE user_id = $1 OR friend_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
@@ -0,0 +1,163 @@
+use crate::models::{
+    identity::{OidcAuthRequestModel, UserIdentityModel},
+    user::UserModel,
+};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub async fn create_auth_request(
+    pool: &PgPool,
+    state_hash: &str,
+    provider: &str,
+    code_verifier: &str,
+    nonce: &str,
+    link_user_id: Option<Uuid>,
+    expires_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO oidc_auth_requests (state_hash, provider, code_verifier, nonce, link_user_id, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(state_hash)
+    .bind(provider)
+    .bind(code_verifier)
+    .bind(nonce)
+    .bind(link_user_id)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Deletes and returns an unexpired request, so each `state` can only be used once
+pub async fn take_auth_request(
+    pool: &PgPool,
+    state_hash: &str,
+) -> Result<Option<OidcAuthRequestModel>, sqlx::Error> {
+    sqlx::query_as::<_, OidcAuthRequestModel>(
+        "DELETE FROM oidc_auth_requests WHERE state_hash = $1 AND expires_at > NOW() RETURNING *",
+    )
+    .bind(state_hash)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn delete_expired_auth_requests(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM oidc_auth_requests WHERE expires_at <= NOW()")
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+pub async fn find_identity(
+    pool: &PgPool,
+    provider: &str,
+    subject: &str,
+) -> Result<Option<UserIdentityModel>, sqlx::Error> {
+    sqlx::query_as::<_, UserIdentityModel>(
+        "SELECT * FROM user_identities WHERE provider = $1 AND subject = $2",
+    )
+    .bind(provider)
+    .bind(subject)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn find_user_identities(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<UserIdentityModel>, sqlx::Error> {
+    sqlx::query_as::<_, UserIdentityModel>(
+        "SELECT * FROM user_identities WHERE user_id = $1 ORDER BY created_at",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Links an identity to an existing account.
+/// Returns None if the user already has an identity at this provider, or the subject is linked elsewhere.
+pub async fn create_identity(
+    pool: &PgPool,
+    user_id: Uuid,
+    provider: &str,
+    subject: &str,
+    email: Option<&str>,
+) -> Result<Option<UserIdentityModel>, sqlx::Error> {
+    sqlx::query_as::<_, UserIdentityModel>(
+        r#"
+        INSERT INTO user_identities (user_id, provider, subject, email)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT DO NOTHING
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .bind(provider)
+    .bind(subject)
+    .bind(email)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Creates a password-less, verified account together with its first identity
+pub async fn create_user_with_identity(
+    pool: &PgPool,
+    email: &str,
+    email_normalized: &str,
+    provider: &str,
+    subject: &str,
+) -> Result<UserModel, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let user = sqlx::query_as::<_, UserModel>(
+        "INSERT INTO users_auth (email, email_normalized, password_hash, email_verified_at) VALUES ($1, $2, NULL, NOW()) RETURNING *",
+    )
+    .bind(email)
+    .bind(email_normalized)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO user_identities (user_id, provider, subject, email, last_login_at)
+        VALUES ($1, $2, $3, $4, NOW())",
+    )
+    .bind(user.id)
+    .bind(provider)
+    .bind(subject)
+    .bind(email)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(user)
+}
+
+pub async fn touch_last_login(
+    pool: &PgPool,
+    identity_id: Uuid,
+    email: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE user_identities SET last_login_at = NOW(), email = COALESCE($2, email) WHERE id = $1",
+    )
+    .bind(identity_id)
+    .bind(email)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn delete_identity(
+    pool: &PgPool,
+    user_id: Uuid,
+    provider: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM user_identities WHERE user_id = $1 AND provider = $2")
+        .bind(user_id)
+        .bind(provider)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,78 @@
+use crate::models::login_throttle::LoginThrottleModel;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub async fn find_by_key(
+    pool: &PgPool,
+    throttle_key: &str,
+) -> Result<Option<LoginThrottleModel>, sqlx::Error> {
+    sqlx::query_as::<_, LoginThrottleModel>("SELECT * FROM login_throttles WHERE throttle_key = $1")
+        .bind(throttle_key)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Counts a failed attempt and returns the new number of consecutive failures.
+/// The counter restarts when the previous failure is older than `window_start`.
+pub async fn record_failure(
+    pool: &PgPool,
+    throttle_key: &str,
+    user_id: Option<Uuid>,
+    window_start: DateTime<Utc>,
+) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO login_throttles (throttle_key, user_id, failed_attempts, last_failed_at)
+        VALUES ($1, $2, 1, NOW())
+        ON CONFLICT (throttle_key) DO UPDATE
+        SET failed_attempts = CASE
+                WHEN login_throttles.last_failed_at < $3 THEN 1
+                ELSE login_throttles.failed_attempts + 1
+            END,
+            last_failed_at = NOW()
+        RETURNING failed_attempts
+        "#,
+    )
+    .bind(throttle_key)
+    .bind(user_id)
+    .bind(window_start)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn set_locked_until(
+    pool: &PgPool,
+    throttle_key: &str,
+    locked_until: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE login_throttles SET locked_until = $2 WHERE throttle_key = $1")
+        .bind(throttle_key)
+        .bind(locked_until)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn clear(pool: &PgPool, throttle_key: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM login_throttles WHERE throttle_key = $1")
+        .bind(throttle_key)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Removes entries whose last failure is older than `cutoff` and that are no longer locked
+pub async fn delete_stale(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM login_throttles
+        WHERE last_failed_at < $1 AND (locked_until IS NULL OR locked_until < NOW())
+        "#,
+    )
+    .bind(cutoff)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,119 @@
+use crate::models::magic_link::MagicLinkToken;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub async fn create_token(
+    pool: &PgPool,
+    user_id: Uuid,
+    token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<MagicLinkToken, sqlx::Error> {
+    sqlx::query_as::<_, MagicLinkToken>(
+        r#"
+        INSERT INTO magic_link_tokens (user_id, token_hash, expires_at)
+        VALUES ($1, $2, $3)
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .bind(token_hash)
+    .bind(expires_at)
+    .fetch_one(pool)
+    .await
+}
+
+/// Atomically consumes an unused, unexpired token and returns it.
+/// Returns None if the token does not exist, was already used or has expired.
+pub async fn consume_token(
+    pool: &PgPool,
+    token_hash: &str,
+) -> Result<Option<MagicLinkToken>, sqlx::Error> {
+    sqlx::query_as::<_, MagicLinkToken>(
+        r#"
+        UPDATE magic_link_tokens
+        SET used_at = NOW()
+        WHERE token_hash = $1 AND used_at IS NULL AND expires_at > NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Invalidates all outstanding links for a user so only the newest one works
+pub async fn invalidate_user_tokens(pool: &PgPool, user_id: Uuid) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE magic_link_tokens SET used_at = NOW() WHERE user_id = $1 AND used_at IS NULL",
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn delete_expired_tokens(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM magic_link_tokens
+        WHERE id IN (
+            SELECT id FROM magic_link_tokens
+            WHERE expires_at < NOW() OR used_at IS NOT NULL
+            LIMIT 1000
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn record_request(
+    pool: &PgPool,
+    email_hash: &str,
+    ip_address: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO magic_link_requests (email_hash, ip_address) VALUES ($1, $2)")
+        .bind(email_hash)
+        .bind(ip_address)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Number of requests since `since` for the address and for the IP address
+pub async fn count_recent_requests(
+    pool: &PgPool,
+    email_hash: &str,
+    ip_address: Option<&str>,
+    since: DateTime<Utc>,
+) -> Result<(i64, i64), sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE email_hash = $1),
+            COUNT(*) FILTER (WHERE ip_address = $2)
+        FROM magic_link_requests
+        WHERE requested_at > $3 AND (email_hash = $1 OR ip_address = $2)
+        "#,
+    )
+    .bind(email_hash)
+    .bind(ip_address)
+    .bind(since)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn delete_requests_before(
+    pool: &PgPool,
+    cutoff: DateTime<Utc>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM magic_link_requests WHERE requested_at < $1")
+        .bind(cutoff)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
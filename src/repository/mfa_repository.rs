@@ -0,0 +1,132 @@
+use crate::models::mfa::UserTotpModel;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub async fn find_totp(pool: &PgPool, user_id: Uuid) -> Result<Option<UserTotpModel>, sqlx::Error> {
+    sqlx::query_as::<_, UserTotpModel>("SELECT * FROM user_totp WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Stores a new secret awaiting confirmation, replacing any unconfirmed one.
+/// An already enabled secret is never overwritten.
+pub async fn upsert_pending_totp(
+    pool: &PgPool,
+    user_id: Uuid,
+    secret: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO user_totp (user_id, secret)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE
+        SET secret = EXCLUDED.secret, last_used_step = NULL, created_at = NOW()
+        WHERE user_totp.enabled_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(secret)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn enable_totp(pool: &PgPool, user_id: Uuid, step: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE user_totp SET enabled_at = NOW(), last_used_step = $2 WHERE user_id = $1")
+        .bind(user_id)
+        .bind(step)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Records the time step of an accepted code.
+/// Returns false if the same or a later step was already used (replayed code).
+pub async fn record_totp_step(
+    pool: &PgPool,
+    user_id: Uuid,
+    step: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE user_totp SET last_used_step = $2
+        WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)
+        "#,
+    )
+    .bind(user_id)
+    .bind(step)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Removes the TOTP secret and all recovery codes of a user
+pub async fn delete_mfa(pool: &PgPool, user_id: Uuid) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM user_totp WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await
+}
+
+/// Replaces all recovery codes of a user with a new set
+pub async fn replace_recovery_codes(
+    pool: &PgPool,
+    user_id: Uuid,
+    code_hashes: &[String],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "INSERT INTO mfa_recovery_codes (user_id, code_hash) SELECT $1, UNNEST($2::varchar[])",
+    )
+    .bind(user_id)
+    .bind(code_hashes)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+/// Marks an unused recovery code as used. Returns false if no such code exists.
+pub async fn consume_recovery_code(
+    pool: &PgPool,
+    user_id: Uuid,
+    code_hash: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE mfa_recovery_codes SET used_at = NOW()
+        WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(code_hash)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn count_unused_recovery_codes(pool: &PgPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM mfa_recovery_codes WHERE user_id = $1 AND used_at IS NULL",
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
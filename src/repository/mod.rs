@@ -1,4 +1,23 @@
+pub mod account_deletion_repository;
+pub mod api_key_repository;
+pub mod audit_repository;
+pub mod export_repository;
 pub mod friend_repository;
+pub mod identity_repository;
+pub mod login_throttle_repository;
+pub mod magic_link_repository;
+pub mod mfa_repository;
+pub mod password_reset_repository;
 pub mod profile_repository;
 pub mod token_repository;
 pub mod user_repository;
+pub mod verification_repository;
+pub mod webauthn_repository;
+
+/// Postgres SQLSTATE for a violated unique constraint or index
+const UNIQUE_VIOLATION: &str = "23505";
+
+/// Whether a statement failed because it would have duplicated a unique value
+pub fn is_unique_violation(error: &sqlx::Error) -> bool {
+    matches!(error, sqlx::Error::Database(e) if e.code().as_deref() == Some(UNIQUE_VIOLATION))
+}
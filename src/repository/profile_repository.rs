@@ -90,3 +90,11 @@ pub async fn update_profile(
         .fetch_one(pool)
         .await
 }
+
+pub async fn clear_avatar_url(pool: &PgPool, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE profiles SET avatar_url = NULL WHERE user_id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
@@ -1,4 +1,5 @@
-use crate::models::token::RefreshToken;
+use crate::models::token::{RefreshToken, SessionModel};
+use crate::utils::device::DeviceInfo;
 use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -6,19 +7,26 @@ use uuid::Uuid;
 pub async fn create_token(
     pool: &PgPool,
     user_id: Uuid,
+    family_id: Uuid,
     token_hash: &str,
     expires_at: DateTime<Utc>,
+    device: &DeviceInfo,
 ) -> Result<RefreshToken, sqlx::Error> {
     sqlx::query_as!(
         RefreshToken,
         r#"
-        INSERT INTO refresh_tokens (user_id, token_hash, expires_at)
-        VALUES ($1, $2, $3)
+        INSERT INTO refresh_tokens
+            (user_id, family_id, token_hash, expires_at, user_agent, ip_address, device_name)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
         RETURNING *
         "#,
         user_id,
+        family_id,
         token_hash,
-        expires_at
+        expires_at,
+        device.user_agent.as_deref(),
+        device.ip_address.as_deref(),
+        device.device_name.as_deref()
     )
     .fetch_one(pool)
     .await
@@ -40,12 +48,38 @@ pub async fn find_token_by_hash(
     .await
 }
 
-pub async fn set_token_used(pool: &PgPool, token_hash: &str) -> Result<(), sqlx::Error> {
+/// Atomically marks a token as used.
+/// Returns false if the token was already used, so concurrent rotations cannot both succeed.
+pub async fn consume_token(pool: &PgPool, token_hash: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        "UPDATE refresh_tokens SET used = TRUE WHERE token_hash = $1 AND used = FALSE",
+        token_hash
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Revokes every token in a family (all rotations of a single login)
+pub async fn revoke_family(pool: &PgPool, family_id: Uuid) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        "UPDATE refresh_tokens SET used = TRUE, revoked_at = NOW() WHERE family_id = $1 AND used = FALSE",
+        family_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Revokes a single token, unless it was already rotated or revoked
+pub async fn revoke_token(pool: &PgPool, token_hash: &str) -> Result<(), sqlx::Error> {
     sqlx::query!(
         r#"
         UPDATE refresh_tokens
-        SET used = TRUE
-        WHERE token_hash = $1
+        SET used = TRUE, revoked_at = NOW()
+        WHERE token_hash = $1 AND used = FALSE
         "#,
         token_hash
     )
@@ -53,6 +87,98 @@ pub async fn set_token_used(pool: &PgPool, token_hash: &str) -> Result<(), sqlx:
     .await?;
     Ok(())
 }
+
+/// Revokes every outstanding refresh token of a user, signing out all sessions
+pub async fn revoke_all_user_tokens(pool: &PgPool, user_id: Uuid) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        "UPDATE refresh_tokens SET used = TRUE, revoked_at = NOW() WHERE user_id = $1 AND used = FALSE",
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Revokes every outstanding refresh token of a user except the given one,
+/// signing out all other sessions while keeping the current one alive
+pub async fn revoke_other_user_tokens(
+    pool: &PgPool,
+    user_id: Uuid,
+    keep_token_hash: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        "UPDATE refresh_tokens SET used = TRUE, revoked_at = NOW() WHERE user_id = $1 AND used = FALSE AND token_hash <> $2",
+        user_id,
+        keep_token_hash
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Lists the active sessions of a user, most recently used first.
+/// Each active family has exactly one unused token, which carries the latest metadata.
+pub async fn find_active_sessions(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<SessionModel>, sqlx::Error> {
+    sqlx::query_as!(
+        SessionModel,
+        r#"
+        SELECT
+            t.family_id, t.user_agent, t.ip_address, t.device_name, t.last_used_at, t.expires_at,
+            (SELECT MIN(f.created_at) FROM refresh_tokens f WHERE f.family_id = t.family_id) AS "created_at!"
+        FROM refresh_tokens t
+        WHERE t.user_id = $1 AND t.used = FALSE AND t.expires_at > NOW()
+        ORDER BY t.last_used_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Lists every session of a user that is still on record, including signed-out ones.
+/// Metadata comes from the newest token of each family.
+pub async fn find_session_history(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<SessionModel>, sqlx::Error> {
+    sqlx::query_as!(
+        SessionModel,
+        r#"
+        SELECT DISTINCT ON (t.family_id)
+            t.family_id, t.user_agent, t.ip_address, t.device_name, t.last_used_at, t.expires_at,
+            (SELECT MIN(f.created_at) FROM refresh_tokens f WHERE f.family_id = t.family_id) AS "created_at!"
+        FROM refresh_tokens t
+        WHERE t.user_id = $1
+        ORDER BY t.family_id, t.created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Revokes one session of a user. The user_id check prevents revoking other users' sessions.
+pub async fn revoke_user_family(
+    pool: &PgPool,
+    user_id: Uuid,
+    family_id: Uuid,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        "UPDATE refresh_tokens SET used = TRUE, revoked_at = NOW() WHERE user_id = $1 AND family_id = $2 AND used = FALSE",
+        user_id,
+        family_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
 pub async fn delete_expired_tokens(pool: &PgPool) -> Result<u64, sqlx::Error> {
     let result = sqlx::query!(
         r#"
@@ -69,3 +195,55 @@ pub async fn delete_expired_tokens(pool: &PgPool) -> Result<u64, sqlx::Error> {
 
     Ok(result.rows_affected())
 }
+
+/// Denylists a single access token until it expires
+pub async fn revoke_access_token(
+    pool: &PgPool,
+    jti: &str,
+    user_id: Uuid,
+    expires_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO revoked_access_tokens (jti, user_id, expires_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (jti) DO NOTHING
+        "#,
+        jti,
+        user_id,
+        expires_at
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The `jti`s of a user's denylisted access tokens that have not expired yet
+pub async fn find_revoked_access_tokens(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT jti FROM revoked_access_tokens WHERE user_id = $1 AND expires_at > NOW()",
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn delete_expired_revoked_access_tokens(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM revoked_access_tokens
+        WHERE jti IN (
+            SELECT jti FROM revoked_access_tokens
+            WHERE expires_at < NOW()
+            LIMIT 1000
+        )
+        "#
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -1,5 +1,6 @@
 use crate::models::profile::ProfileModel;
 use crate::models::user::UserModel;
+use chrono::{DateTime, Utc};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
@@ -22,8 +23,8 @@ pub async fn find_user_with_profile(
     let result = sqlx::query(
         r#"
         SELECT 
-            u.id as user_id, u.email, u.password_hash, u.role, u.is_active, u.is_deleted,
-            u.created_at as user_created_at, u.updated_at as user_updated_at,
+            u.id as user_id, u.email, u.email_normalized, u.password_hash, u.role, u.is_active, u.is_deleted,
+            u.email_verified_at, u.email_conflict_with, u.token_version, u.deleted_at, u.created_at as user_created_at, u.updated_at as user_updated_at,
             p.id as profile_id, p.user_id as profile_user_id, p.full_name, p.bio, p.avatar_url, 
             p.created_at as profile_created_at, p.updated_at as profile_updated_at
         FROM users_auth u
@@ -41,10 +42,15 @@ pub async fn find_user_with_profile(
             let user = UserModel {
                 id: row.try_get("user_id")?,
                 email: row.try_get("email")?,
+                email_normalized: row.try_get("email_normalized")?,
                 password_hash: row.try_get("password_hash")?,
                 role: row.try_get("role")?,
                 is_active: row.try_get("is_active")?,
                 is_deleted: row.try_get("is_deleted")?,
+                email_verified_at: row.try_get("email_verified_at")?,
+                email_conflict_with: row.try_get("email_conflict_with")?,
+                token_version: row.try_get("token_version")?,
+                deleted_at: row.try_get("deleted_at")?,
                 created_at: row.try_get("user_created_at")?,
                 updated_at: row.try_get("user_updated_at")?,
             };
@@ -72,12 +78,14 @@ pub async fn find_user_with_profile(
 pub async fn create_user(
     pool: &PgPool,
     email: &str,
+    email_normalized: &str,
     password_hash: &str,
 ) -> Result<UserModel, sqlx::Error> {
     let user = sqlx::query_as!(
         UserModel,
-        "INSERT INTO users_auth (email, password_hash) VALUES ($1, $2) RETURNING *",
+        "INSERT INTO users_auth (email, email_normalized, password_hash) VALUES ($1, $2, $3) RETURNING *",
         email,
+        email_normalized,
         password_hash
     )
     .fetch_one(pool)
@@ -85,16 +93,332 @@ pub async fn create_user(
     Ok(user)
 }
 
+/// Looks an account up by the normalized form of its address (see `utils::email`)
 pub async fn find_user_by_email(
     pool: &PgPool,
-    email: &str,
+    email_normalized: &str,
 ) -> Result<Option<UserModel>, sqlx::Error> {
     let user = sqlx::query_as!(
         UserModel,
-        "SELECT * FROM users_auth WHERE email = $1",
-        email
+        "SELECT * FROM users_auth WHERE email_normalized = $1",
+        email_normalized
     )
     .fetch_optional(pool)
     .await?;
     Ok(user)
 }
+
+/// Every account's address with its stored normalized form
+/// Addresses of accounts not flagged as conflicting; with `only_unnormalized`,
+/// just those that have no normalized form yet
+pub async fn find_emails_to_normalize(
+    pool: &PgPool,
+    only_unnormalized: bool,
+) -> Result<Vec<(Uuid, String, Option<String>)>, sqlx::Error> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, email, email_normalized FROM users_auth
+        WHERE email_conflict_with IS NULL AND (email_normalized IS NULL OR NOT $1)
+        ORDER BY created_at, id
+        "#,
+        only_unnormalized
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.id, row.email, row.email_normalized))
+        .collect())
+}
+
+/// Whether provider rules were on when stored addresses were last normalized
+pub async fn find_email_normalization_rules(pool: &PgPool) -> Result<Option<bool>, sqlx::Error> {
+    sqlx::query_scalar!("SELECT provider_rules FROM email_normalization_state")
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn set_email_normalization_rules(
+    pool: &PgPool,
+    provider_rules: bool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO email_normalization_state (provider_rules) VALUES ($1)
+        ON CONFLICT (id) DO UPDATE SET provider_rules = EXCLUDED.provider_rules
+        "#,
+        provider_rules
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn update_email_normalized(
+    pool: &PgPool,
+    user_id: Uuid,
+    email_normalized: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE users_auth SET email_normalized = $1 WHERE id = $2",
+        email_normalized,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Flags an account whose address normalizes to `email_normalized`, which another account owns
+pub async fn flag_email_conflict(
+    pool: &PgPool,
+    user_id: Uuid,
+    email_normalized: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE users_auth SET email_conflict_with = (SELECT id FROM users_auth WHERE email_normalized = $1) WHERE id = $2",
+        email_normalized,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Marks the user's email as verified (no-op if it was already verified)
+pub async fn mark_email_verified(pool: &PgPool, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE users_auth SET email_verified_at = COALESCE(email_verified_at, NOW()) WHERE id = $1",
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn update_password_hash(
+    pool: &PgPool,
+    user_id: Uuid,
+    password_hash: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE users_auth SET password_hash = $1 WHERE id = $2",
+        password_hash,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Replaces the hash only if it is still `current_hash`, so an upgrade computed during login
+/// cannot overwrite a password changed in the meantime
+pub async fn upgrade_password_hash(
+    pool: &PgPool,
+    user_id: Uuid,
+    current_hash: &str,
+    new_hash: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE users_auth SET password_hash = $1 WHERE id = $2 AND password_hash = $3",
+        new_hash,
+        user_id,
+        current_hash
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Soft-deletes the account; it stays restorable until purged
+pub async fn mark_deleted(pool: &PgPool, user_id: Uuid) -> Result<Option<UserModel>, sqlx::Error> {
+    sqlx::query_as!(
+        UserModel,
+        "UPDATE users_auth SET is_deleted = TRUE, deleted_at = NOW() WHERE id = $1 AND is_deleted = FALSE RETURNING *",
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Undoes a soft delete, as long as the account was deleted after `deleted_after`
+pub async fn restore_deleted(
+    pool: &PgPool,
+    user_id: Uuid,
+    deleted_after: DateTime<Utc>,
+) -> Result<Option<UserModel>, sqlx::Error> {
+    sqlx::query_as!(
+        UserModel,
+        "UPDATE users_auth SET is_deleted = FALSE, deleted_at = NULL WHERE id = $1 AND is_deleted = TRUE AND deleted_at > $2 RETURNING *",
+        user_id,
+        deleted_after
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Accounts whose deletion grace period ended before `deleted_before`
+pub async fn find_accounts_to_purge(
+    pool: &PgPool,
+    deleted_before: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT id FROM users_auth WHERE is_deleted = TRUE AND deleted_at <= $1 ORDER BY deleted_at LIMIT $2",
+        deleted_before,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Permanently removes a soft-deleted account and everything stored for it.
+/// Dependent rows are deleted explicitly so the purge does not rely on cascade rules.
+pub async fn purge_user(
+    pool: &PgPool,
+    user_id: Uuid,
+    deleted_before: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        "DELETE FROM friendships WHERE user_id = $1 OR friend_id = $1",
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM oidc_auth_requests WHERE link_user_id = $1",
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    for table in [
+        "profiles",
+        "refresh_tokens",
+        "revoked_access_tokens",
+        "email_verification_tokens",
+        "password_reset_tokens",
+        "mfa_recovery_codes",
+        "user_totp",
+        "login_throttles",
+        "data_exports",
+        "user_identities",
+        "api_keys",
+        "webauthn_credentials",
+        "webauthn_challenges",
+        "magic_link_tokens",
+        "account_deletion_tokens",
+    ] {
+        sqlx::query(&format!("DELETE FROM {} WHERE user_id = $1", table))
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    // Only purge if the account was not restored (or deleted again) in the meantime
+    let result = sqlx::query!(
+        "DELETE FROM users_auth WHERE id = $1 AND is_deleted = TRUE AND deleted_at <= $2",
+        user_id,
+        deleted_before
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Ok(false);
+    }
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// A row of the admin user search
+#[derive(sqlx::FromRow)]
+pub struct UserSearchRow {
+    pub id: Uuid,
+    pub email: String,
+    pub role: String,
+    pub is_active: bool,
+    pub is_deleted: bool,
+    pub full_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Searches accounts by email or full name (case-insensitive substring), ordered by email.
+/// Pages with a keyset cursor on (email, id).
+pub async fn search_users(
+    pool: &PgPool,
+    query: Option<&str>,
+    cursor: Option<(String, Uuid)>,
+    limit: i32,
+) -> Result<Vec<UserSearchRow>, sqlx::Error> {
+    // Escape LIKE wildcards so the search term is matched literally
+    let pattern = query.map(|q| {
+        format!(
+            "%{}%",
+            q.replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        )
+    });
+    let (cursor_email, cursor_id) = cursor.unzip();
+
+    sqlx::query_as!(
+        UserSearchRow,
+        r#"
+        SELECT u.id, u.email, u.role, u.is_active, u.is_deleted, p.full_name, u.created_at
+        FROM users_auth u
+        LEFT JOIN profiles p ON p.user_id = u.id
+        WHERE ($1::text IS NULL OR u.email ILIKE $1 OR p.full_name ILIKE $1)
+          AND ($2::text IS NULL OR (u.email, u.id) > ($2, $3))
+        ORDER BY u.email ASC, u.id ASC
+        LIMIT $4
+        "#,
+        pattern,
+        cursor_email,
+        cursor_id,
+        i64::from(limit)
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn set_active(
+    pool: &PgPool,
+    user_id: Uuid,
+    is_active: bool,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        "UPDATE users_auth SET is_active = $1 WHERE id = $2",
+        is_active,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Returns the new version, or None if the user does not exist
+pub async fn increment_token_version(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Option<i32>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "UPDATE users_auth SET token_version = token_version + 1 WHERE id = $1 RETURNING token_version",
+        user_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn set_role(pool: &PgPool, user_id: Uuid, role: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        "UPDATE users_auth SET role = $1 WHERE id = $2",
+        role,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
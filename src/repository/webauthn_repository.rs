@@ -0,0 +1,155 @@
+use crate::models::webauthn::{ChallengePurpose, WebauthnChallengeModel, WebauthnCredentialModel};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub async fn create_challenge(
+    pool: &PgPool,
+    challenge_hash: &str,
+    purpose: ChallengePurpose,
+    user_id: Option<Uuid>,
+    expires_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO webauthn_challenges (challenge_hash, purpose, user_id, expires_at)
+        VALUES ($1, $2, $3, $4)",
+    )
+    .bind(challenge_hash)
+    .bind(purpose.as_str())
+    .bind(user_id)
+    .bind(expires_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Deletes and returns an unexpired challenge, so each challenge can only be answered once
+pub async fn take_challenge(
+    pool: &PgPool,
+    challenge_hash: &str,
+    purpose: ChallengePurpose,
+) -> Result<Option<WebauthnChallengeModel>, sqlx::Error> {
+    sqlx::query_as::<_, WebauthnChallengeModel>(
+        "DELETE FROM webauthn_challenges
+        WHERE challenge_hash = $1 AND purpose = $2 AND expires_at > NOW()
+        RETURNING *",
+    )
+    .bind(challenge_hash)
+    .bind(purpose.as_str())
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn delete_expired_challenges(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM webauthn_challenges WHERE expires_at <= NOW()")
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Stores a new passkey. Returns None if the credential is already registered.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_credential(
+    pool: &PgPool,
+    user_id: Uuid,
+    credential_id: &[u8],
+    public_key: &[u8],
+    algorithm: i32,
+    sign_count: i64,
+    transports: &[String],
+    name: &str,
+) -> Result<Option<WebauthnCredentialModel>, sqlx::Error> {
+    sqlx::query_as::<_, WebauthnCredentialModel>(
+        r#"
+        INSERT INTO webauthn_credentials
+            (user_id, credential_id, public_key, algorithm, sign_count, transports, name)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (credential_id) DO NOTHING
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .bind(credential_id)
+    .bind(public_key)
+    .bind(algorithm)
+    .bind(sign_count)
+    .bind(transports)
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn find_credential(
+    pool: &PgPool,
+    credential_id: &[u8],
+) -> Result<Option<WebauthnCredentialModel>, sqlx::Error> {
+    sqlx::query_as::<_, WebauthnCredentialModel>(
+        "SELECT * FROM webauthn_credentials WHERE credential_id = $1",
+    )
+    .bind(credential_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn find_user_credentials(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<WebauthnCredentialModel>, sqlx::Error> {
+    sqlx::query_as::<_, WebauthnCredentialModel>(
+        "SELECT * FROM webauthn_credentials WHERE user_id = $1 ORDER BY created_at",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn count_user_credentials(pool: &PgPool, user_id: Uuid) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM webauthn_credentials WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+}
+
+/// Records a sign-in with the passkey. The counter must increase unless the authenticator
+/// does not implement one (always 0); returns false otherwise, which also makes a replayed
+/// assertion lose against the concurrent original.
+pub async fn record_use(pool: &PgPool, id: Uuid, sign_count: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE webauthn_credentials SET sign_count = $2, last_used_at = NOW()
+        WHERE id = $1 AND (sign_count < $2 OR (sign_count = 0 AND $2 = 0))",
+    )
+    .bind(id)
+    .bind(sign_count)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn rename_credential(
+    pool: &PgPool,
+    user_id: Uuid,
+    id: Uuid,
+    name: &str,
+) -> Result<Option<WebauthnCredentialModel>, sqlx::Error> {
+    sqlx::query_as::<_, WebauthnCredentialModel>(
+        "UPDATE webauthn_credentials SET name = $3 WHERE id = $1 AND user_id = $2 RETURNING *",
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn delete_credential(
+    pool: &PgPool,
+    user_id: Uuid,
+    id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM webauthn_credentials WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
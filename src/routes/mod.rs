@@ -2,4 +2,4 @@ pub mod private;
 pub mod public;
 
 pub use private::private_routes;
-pub use public::public_routes;
+pub use public::{public_routes, well_known_routes};
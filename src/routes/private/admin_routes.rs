@@ -0,0 +1,26 @@
+use crate::handlers::admin::{
+    change_role_handler, force_logout_handler, get_user_handler, remove_avatar_handler,
+    search_users_handler, suspend_user_handler, unsuspend_user_handler,
+};
+use crate::middlewares::role::{RequireRole, require_role};
+use crate::models::role::Role;
+use crate::state::AppState;
+use axum::{
+    Router,
+    middleware::from_fn_with_state,
+    routing::{delete, get, post, put},
+};
+
+pub fn admin_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/users", get(search_users_handler))
+        .route("/users/{id}", get(get_user_handler))
+        .route("/users/{id}/suspend", post(suspend_user_handler))
+        .route("/users/{id}/unsuspend", post(unsuspend_user_handler))
+        .route("/users/{id}/logout", post(force_logout_handler))
+        .route("/users/{id}/role", put(change_role_handler))
+        .route("/users/{id}/avatar", delete(remove_avatar_handler))
+        // Every admin route requires the admin role
+        .route_layer(from_fn_with_state(RequireRole(Role::Admin), require_role))
+        .with_state(state)
+}
@@ -2,19 +2,33 @@ use crate::handlers::friend::{
     accept_request_handler, delete_friend_handler, get_friends_handler,
     get_pending_requests_handler, get_sent_requests_handler, send_request_handler,
 };
+use crate::middlewares::scope::{RequireScope, require_scope};
+use crate::models::api_key::ApiKeyScope;
 use crate::state::AppState;
 use axum::{
     Router,
+    middleware::from_fn_with_state,
     routing::{delete, get, post},
 };
 
 pub fn friend_routes(state: AppState) -> Router {
-    Router::new()
+    let read = Router::new()
         .route("/", get(get_friends_handler))
         .route("/pending", get(get_pending_requests_handler))
         .route("/sent", get(get_sent_requests_handler))
+        .route_layer(from_fn_with_state(
+            RequireScope(ApiKeyScope::FriendsRead),
+            require_scope,
+        ));
+
+    let write = Router::new()
         .route("/request/{target_id}", post(send_request_handler))
         .route("/accept/{target_id}", post(accept_request_handler))
         .route("/{target_id}", delete(delete_friend_handler))
-        .with_state(state)
+        .route_layer(from_fn_with_state(
+            RequireScope(ApiKeyScope::FriendsWrite),
+            require_scope,
+        ));
+
+    Router::new().merge(read).merge(write).with_state(state)
 }
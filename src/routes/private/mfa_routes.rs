@@ -0,0 +1,32 @@
+use crate::handlers::mfa::{
+    mfa_status_handler, totp_confirm_handler, totp_disable_handler, totp_setup_handler,
+};
+use crate::middlewares::scope::require_session;
+use crate::state::AppState;
+use axum::{
+    Router,
+    middleware::from_fn,
+    routing::{get, post},
+};
+
+pub fn mfa_routes(state: AppState) -> Router {
+    // Routes without rate limiting
+    let non_limited = Router::new().route("/", get(mfa_status_handler));
+
+    // Routes with rate limiting for code guessing protection
+    // Uses shared config from AppState (per docs: do not create config multiple times!)
+    let rate_limited = Router::new()
+        .route("/totp/setup", post(totp_setup_handler))
+        .route("/totp/confirm", post(totp_confirm_handler))
+        .route("/totp/disable", post(totp_disable_handler))
+        .layer(tower_governor::GovernorLayer::new(
+            state.rate_limit_config.clone(),
+        ));
+
+    Router::new()
+        .merge(non_limited)
+        .merge(rate_limited)
+        // Second factors are managed from an interactive session only
+        .route_layer(from_fn(require_session))
+        .with_state(state)
+}
@@ -1,8 +1,13 @@
-use crate::middlewares::auth::auth_middleware;
+use crate::middlewares::{auth::auth_middleware, csrf::csrf_middleware};
 use crate::state::AppState;
-use axum::{Router, middleware::from_fn_with_state};
+use axum::{
+    Router,
+    middleware::{from_fn, from_fn_with_state},
+};
 
+mod admin_routes;
 mod friend_routes;
+mod mfa_routes;
 mod user_routes;
 
 pub fn private_routes(state: AppState) -> Router {
@@ -10,6 +15,9 @@ pub fn private_routes(state: AppState) -> Router {
         // Nest all private route modules here
         .nest("/user", user_routes::user_routes(state.clone()))
         .nest("/friends", friend_routes::friend_routes(state.clone()))
-        // Apply auth middleware to all private routes
+        .nest("/admin", admin_routes::admin_routes(state.clone()))
+        // Cookie-authenticated changes must carry the CSRF token
+        .route_layer(from_fn(csrf_middleware))
+        // Apply auth middleware to all private routes (runs before the CSRF check)
         .route_layer(from_fn_with_state(state, auth_middleware))
 }
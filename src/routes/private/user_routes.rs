@@ -1,28 +1,109 @@
 use crate::constant::image::MAX_AVATAR_SIZE;
+use crate::handlers::account::{
+    change_password_handler, delete_account_handler, list_sessions_handler,
+    request_deletion_confirmation_handler, revoke_other_sessions_handler, revoke_session_handler,
+    security_events_handler,
+};
+use crate::handlers::api_key::{
+    create_api_key_handler, list_api_keys_handler, revoke_api_key_handler,
+};
+use crate::handlers::export::{export_status_handler, request_export_handler};
+use crate::handlers::oidc::{
+    complete_link_handler, list_identities_handler, start_link_handler, unlink_handler,
+};
+use crate::handlers::passkey::{
+    list_passkeys_handler, passkey_registration_options_handler, register_passkey_handler,
+    remove_passkey_handler, rename_passkey_handler,
+};
 use crate::handlers::profile::{edit_profile_handler, me_handler, upload_avatar_handler};
+use crate::middlewares::scope::{RequireScope, require_scope, require_session};
+use crate::models::api_key::ApiKeyScope;
+use crate::routes::private::mfa_routes::mfa_routes;
 use crate::state::AppState;
 use axum::{
     Router,
     extract::DefaultBodyLimit,
-    routing::{get, post, put},
+    middleware::{from_fn, from_fn_with_state},
+    routing::{delete, get, post, put},
 };
 
 pub fn user_routes(state: AppState) -> Router {
-    // Routes without rate limiting
-    let non_limited = Router::new().route("/me", get(me_handler));
+    // Profile routes, also reachable with an API key holding the matching scope
+    let profile_read = Router::new()
+        .route("/me", get(me_handler))
+        .route_layer(from_fn_with_state(
+            RequireScope(ApiKeyScope::ProfileRead),
+            require_scope,
+        ));
 
-    // Routes with rate limiting for upload protection
-    // Uses shared config from AppState (per docs: do not create config multiple times!)
-    let rate_limited = Router::new()
+    let profile_write = Router::new()
         .route("/avatar", post(upload_avatar_handler))
         .route("/edit", put(edit_profile_handler))
+        .route_layer(from_fn_with_state(
+            RequireScope(ApiKeyScope::ProfileWrite),
+            require_scope,
+        ))
         .layer(DefaultBodyLimit::max(MAX_AVATAR_SIZE + 1024)) // Prevent DoS: limit body size before reading
         .layer(tower_governor::GovernorLayer::new(
             state.rate_limit_config.clone(),
         ));
 
-    Router::new()
+    // Routes without rate limiting
+    let non_limited = Router::new()
+        .route(
+            "/sessions",
+            get(list_sessions_handler).delete(revoke_other_sessions_handler),
+        )
+        .route("/sessions/{id}", delete(revoke_session_handler))
+        .route("/export/{id}", get(export_status_handler))
+        .route("/security-events", get(security_events_handler))
+        .route("/identities", get(list_identities_handler))
+        .route("/api-keys", get(list_api_keys_handler))
+        .route("/api-keys/{id}", delete(revoke_api_key_handler))
+        .route("/passkeys", get(list_passkeys_handler))
+        .route(
+            "/passkeys/{id}",
+            put(rename_passkey_handler).delete(remove_passkey_handler),
+        );
+
+    // Routes with rate limiting for password guessing protection
+    // Uses shared config from AppState (per docs: do not create config multiple times!)
+    let rate_limited = Router::new()
+        .route("/password", put(change_password_handler))
+        .route("/me", delete(delete_account_handler))
+        .route(
+            "/me/deletion-confirmation",
+            post(request_deletion_confirmation_handler),
+        )
+        .route("/export", post(request_export_handler))
+        .route(
+            "/identities/{provider}",
+            post(start_link_handler).delete(unlink_handler),
+        )
+        .route(
+            "/identities/{provider}/callback",
+            post(complete_link_handler),
+        )
+        .route("/api-keys", post(create_api_key_handler))
+        .route(
+            "/passkeys/options",
+            post(passkey_registration_options_handler),
+        )
+        .route("/passkeys", post(register_passkey_handler))
+        .layer(tower_governor::GovernorLayer::new(
+            state.rate_limit_config.clone(),
+        ));
+
+    // Account management stays off limits to API keys
+    let session_only = Router::new()
         .merge(non_limited)
         .merge(rate_limited)
-        .with_state(state)
+        .route_layer(from_fn(require_session));
+
+    Router::new()
+        .merge(profile_read)
+        .merge(profile_write)
+        .merge(session_only)
+        .with_state(state.clone())
+        .nest("/mfa", mfa_routes(state))
 }
@@ -1,8 +1,16 @@
 use crate::handlers::auth::{
-    login_handler, logout_handler, refresh_token_handler, register_handler,
+    consume_magic_link_handler, forgot_password_handler, login_handler, login_mfa_handler,
+    logout_handler, magic_link_confirmation_handler, refresh_token_handler, register_handler,
+    request_magic_link_handler, resend_verification_handler, reset_password_handler,
+    restore_account_handler, verify_email_handler,
 };
+use crate::handlers::oidc::{authorize_handler, callback_handler, list_providers_handler};
+use crate::handlers::passkey::{passkey_login_handler, passkey_login_options_handler};
 use crate::state::AppState;
-use axum::{Router, routing::post};
+use axum::{
+    Router,
+    routing::{get, post},
+};
 
 pub fn auth_routes(state: AppState) -> Router {
     // Routes with rate limiting for brute force protection
@@ -10,6 +18,20 @@ pub fn auth_routes(state: AppState) -> Router {
     let rate_limited = Router::new()
         .route("/register", post(register_handler))
         .route("/login", post(login_handler))
+        .route("/login/mfa", post(login_mfa_handler))
+        .route("/resend-verification", post(resend_verification_handler))
+        .route("/forgot-password", post(forgot_password_handler))
+        .route("/reset-password", post(reset_password_handler))
+        .route("/restore-account", post(restore_account_handler))
+        .route("/oidc/{provider}/authorize", post(authorize_handler))
+        .route("/oidc/{provider}/callback", post(callback_handler))
+        .route("/passkey/options", post(passkey_login_options_handler))
+        .route("/passkey/login", post(passkey_login_handler))
+        .route("/magic-link", post(request_magic_link_handler))
+        .route(
+            "/magic-link/consume",
+            get(magic_link_confirmation_handler).post(consume_magic_link_handler),
+        )
         .layer(tower_governor::GovernorLayer::new(
             state.rate_limit_config.clone(),
         ));
@@ -17,7 +39,9 @@ pub fn auth_routes(state: AppState) -> Router {
     // Routes without rate limiting
     let non_limited = Router::new()
         .route("/refresh-token", post(refresh_token_handler))
-        .route("/logout", post(logout_handler));
+        .route("/logout", post(logout_handler))
+        .route("/verify-email", post(verify_email_handler))
+        .route("/oidc/providers", get(list_providers_handler));
 
     Router::new()
         .merge(rate_limited)
@@ -2,6 +2,9 @@ use crate::state::AppState;
 use axum::Router;
 
 mod auth_routes;
+mod well_known_routes;
+
+pub use well_known_routes::well_known_routes;
 
 pub fn public_routes(state: AppState) -> Router {
     Router::new()
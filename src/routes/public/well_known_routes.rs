@@ -0,0 +1,9 @@
+use crate::handlers::jwks::jwks_handler;
+use crate::state::AppState;
+use axum::{Router, routing::get};
+
+pub fn well_known_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/jwks.json", get(jwks_handler))
+        .with_state(state)
+}
@@ -0,0 +1,231 @@
+use crate::{
+    error::AppError,
+    models::{profile::ProfileModel, role::Role, user::UserModel},
+    repository::{
+        friend_repository::{self, FriendCounts},
+        profile_repository, token_repository,
+        user_repository::{self, UserSearchRow},
+    },
+    services::{
+        audit_service::{AuditEvent, AuditRecord, AuditService},
+        auth::account_status_service::UserStatusCache,
+        profile_service,
+    },
+    utils::device::DeviceInfo,
+};
+use aws_sdk_s3::Client as S3Client;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// The admin performing an action, recorded in the audit log
+pub struct AdminActor {
+    pub user_id: Uuid,
+    pub device: DeviceInfo,
+}
+
+pub struct UserDetails {
+    pub user: UserModel,
+    pub profile: Option<ProfileModel>,
+    pub friend_counts: FriendCounts,
+    pub active_sessions: usize,
+}
+
+fn internal(e: sqlx::Error) -> AppError {
+    AppError::InternalError(e.to_string().into())
+}
+
+fn audit(
+    audit: &AuditService,
+    actor: &AdminActor,
+    event: AuditEvent,
+    subject_user_id: Uuid,
+    details: serde_json::Value,
+) {
+    audit.record(
+        AuditRecord::new(event, &actor.device)
+            .user(subject_user_id)
+            .actor(actor.user_id)
+            .details(details),
+    );
+}
+
+async fn find_user(pool: &PgPool, user_id: Uuid) -> Result<UserModel, AppError> {
+    user_repository::find_user_by_id(pool, user_id)
+        .await
+        .map_err(internal)?
+        .ok_or(AppError::BadRequest("User not found".into()))
+}
+
+/// Admins cannot suspend or demote themselves, so an instance is never left without one by accident
+fn ensure_not_self(actor: &AdminActor, user_id: Uuid) -> Result<(), AppError> {
+    if actor.user_id == user_id {
+        return Err(AppError::BadRequest(
+            "Admins cannot perform this action on their own account".into(),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn search_users(
+    pool: &PgPool,
+    query: Option<&str>,
+    cursor: Option<(String, Uuid)>,
+    limit: i32,
+) -> Result<Vec<UserSearchRow>, AppError> {
+    let query = query.map(str::trim).filter(|q| !q.is_empty());
+    user_repository::search_users(pool, query, cursor, limit)
+        .await
+        .map_err(internal)
+}
+
+pub async fn get_user_details(pool: &PgPool, user_id: Uuid) -> Result<UserDetails, AppError> {
+    let (user, profile) = user_repository::find_user_with_profile(pool, user_id)
+        .await
+        .map_err(internal)?
+        .ok_or(AppError::BadRequest("User not found".into()))?;
+    let friend_counts = friend_repository::count_for_user(pool, user_id)
+        .await
+        .map_err(internal)?;
+    let active_sessions = token_repository::find_active_sessions(pool, user_id)
+        .await
+        .map_err(internal)?
+        .len();
+
+    Ok(UserDetails {
+        user,
+        profile,
+        friend_counts,
+        active_sessions,
+    })
+}
+
+/// Suspends (`is_active = false`) or reinstates an account.
+/// Suspending also signs the user out everywhere.
+pub async fn set_suspended(
+    pool: &PgPool,
+    audit_service: &AuditService,
+    status_cache: &UserStatusCache,
+    actor: &AdminActor,
+    user_id: Uuid,
+    suspended: bool,
+) -> Result<(), AppError> {
+    ensure_not_self(actor, user_id)?;
+    find_user(pool, user_id).await?;
+
+    user_repository::set_active(pool, user_id, !suspended)
+        .await
+        .map_err(internal)?;
+    if suspended {
+        token_repository::revoke_all_user_tokens(pool, user_id)
+            .await
+            .map_err(internal)?;
+        // Reinstating the account later must not bring its old access tokens back
+        status_cache
+            .revoke_access_tokens(pool, user_id)
+            .await
+            .map_err(internal)?;
+    } else {
+        status_cache.invalidate(user_id).await;
+    }
+
+    let event = if suspended {
+        AuditEvent::AdminUserSuspended
+    } else {
+        AuditEvent::AdminUserUnsuspended
+    };
+    audit(audit_service, actor, event, user_id, json!({}));
+    Ok(())
+}
+
+/// Revokes every refresh and access token of the user. Returns the number of revoked refresh tokens.
+pub async fn force_logout(
+    pool: &PgPool,
+    audit_service: &AuditService,
+    status_cache: &UserStatusCache,
+    actor: &AdminActor,
+    user_id: Uuid,
+) -> Result<u64, AppError> {
+    find_user(pool, user_id).await?;
+
+    let revoked = token_repository::revoke_all_user_tokens(pool, user_id)
+        .await
+        .map_err(internal)?;
+    status_cache
+        .revoke_access_tokens(pool, user_id)
+        .await
+        .map_err(internal)?;
+
+    audit(
+        audit_service,
+        actor,
+        AuditEvent::AdminUserLoggedOut,
+        user_id,
+        json!({ "revoked_tokens": revoked }),
+    );
+    Ok(revoked)
+}
+
+/// Changes the role of a user. Sessions are revoked so the next access token carries the new role.
+pub async fn change_role(
+    pool: &PgPool,
+    audit_service: &AuditService,
+    status_cache: &UserStatusCache,
+    actor: &AdminActor,
+    user_id: Uuid,
+    role: Role,
+) -> Result<(), AppError> {
+    ensure_not_self(actor, user_id)?;
+    let user = find_user(pool, user_id).await?;
+
+    let previous = Role::from_db(&user.role);
+    if previous == role {
+        return Ok(());
+    }
+
+    user_repository::set_role(pool, user_id, role.as_str())
+        .await
+        .map_err(internal)?;
+    token_repository::revoke_all_user_tokens(pool, user_id)
+        .await
+        .map_err(internal)?;
+    status_cache
+        .revoke_access_tokens(pool, user_id)
+        .await
+        .map_err(internal)?;
+
+    audit(
+        audit_service,
+        actor,
+        AuditEvent::AdminUserRoleChanged,
+        user_id,
+        json!({ "from": previous.as_str(), "to": role.as_str() }),
+    );
+    Ok(())
+}
+
+/// Removes the user's avatar from the profile and deletes the files from R2
+pub async fn remove_avatar(
+    pool: &PgPool,
+    audit_service: &AuditService,
+    s3_client: &S3Client,
+    bucket: &str,
+    actor: &AdminActor,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    find_user(pool, user_id).await?;
+
+    profile_repository::clear_avatar_url(pool, user_id)
+        .await
+        .map_err(internal)?;
+    let deleted = profile_service::delete_user_avatars(s3_client, bucket, user_id).await?;
+
+    audit(
+        audit_service,
+        actor,
+        AuditEvent::AdminUserAvatarRemoved,
+        user_id,
+        json!({ "deleted_objects": deleted }),
+    );
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use crate::{
+    config::JwtConfig,
+    constant::api_key::{
+        API_KEY_DISPLAY_PREFIX_LENGTH, API_KEY_LAST_USED_RESOLUTION_SECONDS, API_KEY_PREFIX,
+        MAX_API_KEYS_PER_USER,
+    },
+    error::{AppError, AuthError},
+    models::api_key::{ApiKeyModel, ApiKeyScope},
+    repository::api_key_repository,
+    utils::{
+        jwt::{Claims, api_key_claims},
+        token::{generate_opaque_token, hash_token},
+    },
+};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+fn internal(e: sqlx::Error) -> AppError {
+    AppError::InternalError(e.to_string().into())
+}
+
+/// Creates a key and returns it together with the full secret, which is not stored
+/// and cannot be shown again
+pub async fn create_api_key(
+    pool: &PgPool,
+    user_id: Uuid,
+    name: &str,
+    scopes: &[ApiKeyScope],
+    expires_in_days: Option<i64>,
+) -> Result<(ApiKeyModel, String), AppError> {
+    let existing = api_key_repository::count_user_keys(pool, user_id)
+        .await
+        .map_err(internal)?;
+    if existing >= MAX_API_KEYS_PER_USER {
+        return Err(AppError::BadRequest(
+            format!(
+                "At most {} API keys can exist at once; revoke one first",
+                MAX_API_KEYS_PER_USER
+            )
+            .into(),
+        ));
+    }
+
+    let mut scopes = scopes.to_vec();
+    scopes.sort();
+    scopes.dedup();
+    let scopes: Vec<String> = scopes.iter().map(|s| s.as_str().to_string()).collect();
+
+    let secret = format!("{}{}", API_KEY_PREFIX, generate_opaque_token());
+    let prefix: String = secret.chars().take(API_KEY_DISPLAY_PREFIX_LENGTH).collect();
+    let expires_at = expires_in_days.map(|days| Utc::now() + Duration::days(days));
+
+    let key = api_key_repository::create_key(
+        pool,
+        user_id,
+        name,
+        &prefix,
+        &hash_token(&secret),
+        &scopes,
+        expires_at,
+    )
+    .await
+    .map_err(internal)?;
+
+    Ok((key, secret))
+}
+
+pub async fn list_api_keys(pool: &PgPool, user_id: Uuid) -> Result<Vec<ApiKeyModel>, AppError> {
+    api_key_repository::find_user_keys(pool, user_id)
+        .await
+        .map_err(internal)
+}
+
+pub async fn revoke_api_key(pool: &PgPool, user_id: Uuid, key_id: Uuid) -> Result<(), AppError> {
+    let count = api_key_repository::revoke_user_key(pool, user_id, key_id)
+        .await
+        .map_err(internal)?;
+
+    if count == 0 {
+        return Err(AppError::BadRequest("API key not found".into()));
+    }
+    Ok(())
+}
+
+/// Resolves an `X-API-Key` header to the principal it acts as, or None if the key is
+/// unknown, revoked or expired
+pub async fn authenticate(
+    pool: &PgPool,
+    raw_key: &str,
+    jwt: &JwtConfig,
+) -> Result<Option<Claims>, AuthError> {
+    if !raw_key.starts_with(API_KEY_PREFIX) {
+        return Ok(None);
+    }
+
+    let Some(key) = api_key_repository::find_usable_key_by_hash(pool, &hash_token(raw_key)).await?
+    else {
+        return Ok(None);
+    };
+
+    let recorded_after = Utc::now() - Duration::seconds(API_KEY_LAST_USED_RESOLUTION_SECONDS);
+    api_key_repository::touch_last_used(pool, key.id, recorded_after).await?;
+
+    Ok(Some(api_key_claims(key.user_id, key.id, key.scopes(), jwt)))
+}
@@ -0,0 +1,202 @@
+use crate::{
+    constant::audit::{AUDIT_EVENT_RETENTION_DAYS, AUDIT_QUEUE_CAPACITY},
+    error::AppError,
+    models::audit::AuditEventModel,
+    repository::{audit_repository, user_repository},
+    utils::device::DeviceInfo,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use uuid::Uuid;
+
+/// Kinds of security-relevant events kept in `audit_events`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditEvent {
+    Registered,
+    LoginSucceeded,
+    LoginFailed,
+    RefreshTokenReused,
+    LoggedOut,
+    PasswordChanged,
+    PasswordReset,
+    MfaEnabled,
+    MfaDisabled,
+    AccountDeleted,
+    IdentityLinked,
+    IdentityUnlinked,
+    ApiKeyCreated,
+    ApiKeyRevoked,
+    PasskeyAdded,
+    PasskeyRemoved,
+    AdminUserSuspended,
+    AdminUserUnsuspended,
+    AdminUserLoggedOut,
+    AdminUserRoleChanged,
+    AdminUserAvatarRemoved,
+}
+
+impl AuditEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEvent::Registered => "user.registered",
+            AuditEvent::LoginSucceeded => "auth.login_succeeded",
+            AuditEvent::LoginFailed => "auth.login_failed",
+            AuditEvent::RefreshTokenReused => "auth.refresh_token_reused",
+            AuditEvent::LoggedOut => "auth.logged_out",
+            AuditEvent::PasswordChanged => "account.password_changed",
+            AuditEvent::PasswordReset => "account.password_reset",
+            AuditEvent::MfaEnabled => "account.mfa_enabled",
+            AuditEvent::MfaDisabled => "account.mfa_disabled",
+            AuditEvent::AccountDeleted => "account.deleted",
+            AuditEvent::IdentityLinked => "account.identity_linked",
+            AuditEvent::IdentityUnlinked => "account.identity_unlinked",
+            AuditEvent::ApiKeyCreated => "account.api_key_created",
+            AuditEvent::ApiKeyRevoked => "account.api_key_revoked",
+            AuditEvent::PasskeyAdded => "account.passkey_added",
+            AuditEvent::PasskeyRemoved => "account.passkey_removed",
+            AuditEvent::AdminUserSuspended => "admin.user_suspended",
+            AuditEvent::AdminUserUnsuspended => "admin.user_unsuspended",
+            AuditEvent::AdminUserLoggedOut => "admin.user_logged_out",
+            AuditEvent::AdminUserRoleChanged => "admin.user_role_changed",
+            AuditEvent::AdminUserAvatarRemoved => "admin.user_avatar_removed",
+        }
+    }
+}
+
+/// Whose account an event concerns
+#[derive(Debug, Clone)]
+pub enum AuditSubject {
+    None,
+    User(Uuid),
+    /// Resolved to a user by the background writer, e.g. for failed logins by email
+    Email(String),
+}
+
+/// An event waiting to be written
+#[derive(Debug, Clone)]
+pub struct AuditRecord {
+    event: AuditEvent,
+    actor_user_id: Option<Uuid>,
+    subject: AuditSubject,
+    device: DeviceInfo,
+    details: serde_json::Value,
+}
+
+impl AuditRecord {
+    pub fn new(event: AuditEvent, device: &DeviceInfo) -> Self {
+        Self {
+            event,
+            actor_user_id: None,
+            subject: AuditSubject::None,
+            device: device.clone(),
+            details: json!({}),
+        }
+    }
+
+    /// The user the event happened to; also recorded as the actor unless `actor` is set
+    pub fn user(mut self, user_id: Uuid) -> Self {
+        self.subject = AuditSubject::User(user_id);
+        self
+    }
+
+    pub fn email(mut self, email: &str) -> Self {
+        self.subject = AuditSubject::Email(email.to_string());
+        self
+    }
+
+    /// Someone acting on another user's account, e.g. an admin
+    pub fn actor(mut self, user_id: Uuid) -> Self {
+        self.actor_user_id = Some(user_id);
+        self
+    }
+
+    pub fn details(mut self, details: serde_json::Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Records audit events without slowing down requests.
+/// Events are queued and written by a background task; when the queue is full,
+/// events are dropped with a warning rather than blocking the caller.
+#[derive(Clone)]
+pub struct AuditService {
+    sender: mpsc::Sender<AuditRecord>,
+}
+
+impl AuditService {
+    /// Spawns the background writer. Must be called from within the Tokio runtime.
+    pub fn start(pool: PgPool) -> Self {
+        let (sender, mut receiver) = mpsc::channel::<AuditRecord>(AUDIT_QUEUE_CAPACITY);
+
+        tokio::spawn(async move {
+            while let Some(record) = receiver.recv().await {
+                if let Err(e) = write_record(&pool, record).await {
+                    tracing::error!("Failed to write audit event: {}", e);
+                }
+            }
+        });
+
+        Self { sender }
+    }
+
+    pub fn record(&self, record: AuditRecord) {
+        match self.sender.try_send(record) {
+            Ok(()) => {}
+            Err(TrySendError::Full(record)) => {
+                tracing::warn!(
+                    event = record.event.as_str(),
+                    "Audit queue full, event dropped"
+                );
+            }
+            Err(TrySendError::Closed(record)) => {
+                tracing::error!(
+                    event = record.event.as_str(),
+                    "Audit writer stopped, event dropped"
+                );
+            }
+        }
+    }
+}
+
+async fn write_record(pool: &PgPool, record: AuditRecord) -> Result<(), sqlx::Error> {
+    // Only a known user acts on their own account; attempts by email may come from anyone
+    let (subject_user_id, actor_user_id) = match &record.subject {
+        AuditSubject::None => (None, record.actor_user_id),
+        AuditSubject::User(user_id) => (Some(*user_id), record.actor_user_id.or(Some(*user_id))),
+        AuditSubject::Email(email) => {
+            let user = user_repository::find_user_by_email(pool, email).await?;
+            (user.map(|user| user.id), record.actor_user_id)
+        }
+    };
+
+    audit_repository::insert_event(
+        pool,
+        record.event.as_str(),
+        actor_user_id,
+        subject_user_id,
+        &record.device,
+        &record.details,
+    )
+    .await
+}
+
+/// Security events concerning a user, newest first
+pub async fn list_user_events(
+    pool: &PgPool,
+    user_id: Uuid,
+    cursor: Option<(DateTime<Utc>, Uuid)>,
+    limit: i32,
+) -> Result<Vec<AuditEventModel>, AppError> {
+    audit_repository::find_for_subject(pool, user_id, cursor, limit)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string().into()))
+}
+
+/// Deletes audit events older than the retention period
+pub async fn delete_expired_events(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let cutoff = Utc::now() - Duration::days(AUDIT_EVENT_RETENTION_DAYS);
+    audit_repository::delete_older_than(pool, cutoff).await
+}
@@ -0,0 +1,231 @@
+use crate::{
+    config::{JwtConfig, PasswordHashConfig},
+    constant::auth::ACCOUNT_DELETION_TOKEN_DURATION_MINUTES,
+    error::{AppError, AuthError},
+    models::user::UserModel,
+    repository::{account_deletion_repository, token_repository, user_repository},
+    services::{
+        auth::{account_status_service::UserStatusCache, auth_service::verify_current_password},
+        export_service,
+        mail::{mail_service, sender::MailSender},
+        profile_service,
+    },
+    utils::{
+        jwt::{TokenType, decode_jwt_with_type},
+        token::{generate_opaque_token, hash_token},
+    },
+};
+use aws_sdk_s3::Client as S3Client;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Maximum number of accounts purged per scheduler run
+const PURGE_BATCH_SIZE: i64 = 100;
+
+/// When a soft-deleted account will be purged, or None if it is not deleted
+pub fn purge_at(user: &UserModel, grace_days: i64) -> Option<DateTime<Utc>> {
+    if !user.is_deleted {
+        return None;
+    }
+    // Accounts deleted without a timestamp (e.g. by hand) are purged on the next run
+    Some(user.deleted_at.unwrap_or(user.updated_at) + Duration::days(grace_days))
+}
+
+/// Whether a deleted account can still be restored, and until when
+pub fn restorable_until(user: &UserModel, grace_days: i64) -> Option<DateTime<Utc>> {
+    purge_at(user, grace_days).filter(|purge_at| *purge_at > Utc::now())
+}
+
+/// How the user re-authenticates before deleting the account
+pub enum DeletionProof<'a> {
+    Password(&'a str),
+    /// Token from the link sent by `request_deletion_confirmation`
+    EmailConfirmation(&'a str),
+}
+
+/// Accounts with a password confirm with it. Accounts without one (provider or passkey sign-in
+/// only) confirm through an emailed link instead, so the session alone is never enough.
+fn ensure_proof_accepted(user: &UserModel, proof: &DeletionProof) -> Result<(), AuthError> {
+    match (user.password_hash.is_some(), proof) {
+        (true, DeletionProof::Password(_)) | (false, DeletionProof::EmailConfirmation(_)) => Ok(()),
+        (true, DeletionProof::EmailConfirmation(_)) => Err(AuthError::ValidationError(
+            "Password is required".to_string(),
+        )),
+        (false, DeletionProof::Password(_)) => Err(AuthError::ValidationError(
+            "This account has no password; confirm the deletion through the emailed link"
+                .to_string(),
+        )),
+    }
+}
+
+/// Emails a single-use link confirming the deletion of an account that has no password
+pub async fn request_deletion_confirmation(
+    pool: &PgPool,
+    mailer: &dyn MailSender,
+    app_base_url: &str,
+    user_id: Uuid,
+) -> Result<(), AuthError> {
+    let user = user_repository::find_user_by_id(pool, user_id)
+        .await?
+        .ok_or(AuthError::InvalidCredentials)?;
+    if user.password_hash.is_some() {
+        return Err(AuthError::ValidationError(
+            "Password is required".to_string(),
+        ));
+    }
+
+    // Only the most recently requested link stays valid
+    account_deletion_repository::invalidate_user_tokens(pool, user.id).await?;
+
+    let token = generate_opaque_token();
+    let expires_at = Utc::now() + Duration::minutes(ACCOUNT_DELETION_TOKEN_DURATION_MINUTES);
+    account_deletion_repository::create_token(pool, user.id, &hash_token(&token), expires_at)
+        .await?;
+
+    mail_service::send_account_deletion_email(mailer, app_base_url, &user.email, &token)
+        .await
+        .map_err(|e| AuthError::MailDeliveryError(e.to_string()))
+}
+
+/// Soft-deletes the account after re-authentication and signs it out everywhere.
+/// Returns when the account will be permanently purged.
+pub async fn delete_account(
+    pool: &PgPool,
+    status_cache: &UserStatusCache,
+    user_id: Uuid,
+    proof: DeletionProof<'_>,
+    grace_days: i64,
+    password_config: &PasswordHashConfig,
+) -> Result<DateTime<Utc>, AuthError> {
+    let user = user_repository::find_user_by_id(pool, user_id)
+        .await?
+        .ok_or(AuthError::InvalidCredentials)?;
+
+    ensure_proof_accepted(&user, &proof)?;
+    match proof {
+        DeletionProof::Password(password) => {
+            verify_current_password(password, user.password_hash.as_deref(), password_config)
+                .await?
+        }
+        DeletionProof::EmailConfirmation(token) => {
+            account_deletion_repository::consume_token(pool, user_id, &hash_token(token))
+                .await?
+                .ok_or(AuthError::InvalidOrExpiredToken)?;
+        }
+    }
+
+    let user = user_repository::mark_deleted(pool, user_id)
+        .await?
+        .ok_or(AuthError::AccountDeleted)?;
+
+    token_repository::revoke_all_user_tokens(pool, user_id).await?;
+    // Outstanding access tokens stop working right away, and stay revoked if the account is restored
+    status_cache.revoke_access_tokens(pool, user_id).await?;
+
+    let purge_at = purge_at(&user, grace_days).unwrap_or_else(Utc::now);
+    tracing::info!(user_id = %user_id, purge_at = %purge_at, "Account scheduled for deletion");
+    Ok(purge_at)
+}
+
+/// Restores a deleted account within its grace period using the token issued at login
+pub async fn restore_account(
+    pool: &PgPool,
+    status_cache: &UserStatusCache,
+    restore_token: &str,
+    jwt: &JwtConfig,
+    grace_days: i64,
+) -> Result<UserModel, AuthError> {
+    let claims = decode_jwt_with_type(restore_token, jwt, TokenType::AccountRestore)
+        .map_err(|_| AuthError::InvalidOrExpiredToken)?;
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| AuthError::InvalidOrExpiredToken)?;
+
+    let deleted_after = Utc::now() - Duration::days(grace_days);
+    let user = user_repository::restore_deleted(pool, user_id, deleted_after)
+        .await?
+        .ok_or(AuthError::InvalidOrExpiredToken)?;
+
+    status_cache.invalidate(user_id).await;
+    tracing::info!(user_id = %user_id, "Deleted account restored");
+    Ok(user)
+}
+
+/// Permanently removes accounts whose grace period has ended, including their avatars in R2.
+/// Returns the number of purged accounts.
+pub async fn purge_expired_accounts(
+    pool: &PgPool,
+    s3_client: &S3Client,
+    bucket: &str,
+    grace_days: i64,
+) -> Result<usize, AppError> {
+    let deleted_before = Utc::now() - Duration::days(grace_days);
+    let user_ids = user_repository::find_accounts_to_purge(pool, deleted_before, PURGE_BATCH_SIZE)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string().into()))?;
+
+    let mut purged = 0;
+    for user_id in user_ids {
+        // R2 objects go first: if R2 fails, the account stays and is retried on the next run
+        if let Err(e) = profile_service::delete_user_avatars(s3_client, bucket, user_id).await {
+            tracing::error!(user_id = %user_id, "Failed to purge avatars: {}", e);
+            continue;
+        }
+        if let Err(e) = export_service::delete_user_exports(pool, s3_client, bucket, user_id).await
+        {
+            tracing::error!(user_id = %user_id, "Failed to purge data exports: {}", e);
+            continue;
+        }
+
+        match user_repository::purge_user(pool, user_id, deleted_before).await {
+            Ok(true) => purged += 1,
+            Ok(false) => {}
+            Err(e) => tracing::error!(user_id = %user_id, "Failed to purge account: {}", e),
+        }
+    }
+
+    Ok(purged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(password_hash: Option<&str>) -> UserModel {
+        let now = Utc::now();
+        UserModel {
+            id: Uuid::new_v4(),
+            email: "user@example.com".to_string(),
+            email_normalized: Some("user@example.com".to_string()),
+            password_hash: password_hash.map(str::to_string),
+            role: "user".to_string(),
+            is_active: true,
+            is_deleted: false,
+            email_verified_at: Some(now),
+            email_conflict_with: None,
+            token_version: 0,
+            deleted_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_password_account_confirms_with_password() {
+        let user = user(Some("$argon2id$..."));
+        assert!(ensure_proof_accepted(&user, &DeletionProof::Password("secret")).is_ok());
+        assert!(matches!(
+            ensure_proof_accepted(&user, &DeletionProof::EmailConfirmation("token")),
+            Err(AuthError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_passwordless_account_confirms_by_email() {
+        let user = user(None);
+        assert!(ensure_proof_accepted(&user, &DeletionProof::EmailConfirmation("token")).is_ok());
+        assert!(matches!(
+            ensure_proof_accepted(&user, &DeletionProof::Password("")),
+            Err(AuthError::ValidationError(_))
+        ));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{
+    constant::auth::{USER_STATUS_CACHE_MAX_ENTRIES, USER_STATUS_CACHE_TTL_SECONDS},
+    error::AuthError,
+    models::user::UserModel,
+    repository::{token_repository, user_repository},
+    utils::jwt::Claims,
+};
+use chrono::DateTime;
+use sqlx::PgPool;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Whether an account may currently authenticate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountStatus {
+    Active,
+    /// Deactivated (`is_active = false`), e.g. by an administrator
+    Suspended,
+    /// Deleted (`is_deleted = true`) or no longer present
+    Deleted,
+}
+
+impl AccountStatus {
+    pub fn of(user: &UserModel) -> Self {
+        if user.is_deleted {
+            AccountStatus::Deleted
+        } else if !user.is_active {
+            AccountStatus::Suspended
+        } else {
+            AccountStatus::Active
+        }
+    }
+
+    /// Maps a non-active status to the error reported to the client
+    pub fn ensure_active(self) -> Result<(), AuthError> {
+        match self {
+            AccountStatus::Active => Ok(()),
+            AccountStatus::Suspended => Err(AuthError::AccountSuspended),
+            AccountStatus::Deleted => Err(AuthError::AccountDeleted),
+        }
+    }
+}
+
+/// Fails unless the account is allowed to sign in or refresh its session
+pub fn ensure_user_active(user: &UserModel) -> Result<(), AuthError> {
+    AccountStatus::of(user).ensure_active()
+}
+
+/// What the auth middleware checks about the owner of an access token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserStatus {
+    pub account: AccountStatus,
+    /// Access tokens issued with a lower version have been revoked
+    pub token_version: i32,
+    /// `jti`s of access tokens revoked individually, e.g. on logout
+    pub revoked_access_tokens: HashSet<String>,
+}
+
+impl UserStatus {
+    fn of(user: Option<&UserModel>, revoked_access_tokens: Vec<String>) -> Self {
+        match user {
+            Some(user) => UserStatus {
+                account: AccountStatus::of(user),
+                token_version: user.token_version,
+                revoked_access_tokens: revoked_access_tokens.into_iter().collect(),
+            },
+            None => UserStatus {
+                account: AccountStatus::Deleted,
+                token_version: 0,
+                revoked_access_tokens: HashSet::new(),
+            },
+        }
+    }
+
+    /// Whether an access token of this user has been revoked, individually or all at once
+    pub fn is_revoked(&self, claims: &Claims) -> bool {
+        claims.is_revoked(self.token_version) || self.revoked_access_tokens.contains(&claims.jti)
+    }
+}
+
+/// Short-lived in-memory cache of account statuses, so the auth middleware can reject
+/// suspended or deleted accounts and revoked access tokens on every request without a
+/// database query each time. Changes made through this process call `invalidate` to take
+/// effect immediately; otherwise they apply once the entry expires.
+#[derive(Clone, Default)]
+pub struct UserStatusCache {
+    entries: Arc<RwLock<HashMap<Uuid, (UserStatus, Instant)>>>,
+}
+
+impl UserStatusCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn ttl() -> Duration {
+        Duration::from_secs(USER_STATUS_CACHE_TTL_SECONDS)
+    }
+
+    /// Returns the cached status, loading it from the database when missing or expired
+    pub async fn get(&self, pool: &PgPool, user_id: Uuid) -> Result<UserStatus, AuthError> {
+        if let Some((status, loaded_at)) = self.entries.read().await.get(&user_id)
+            && loaded_at.elapsed() < Self::ttl()
+        {
+            return Ok(status.clone());
+        }
+
+        let user = user_repository::find_user_by_id(pool, user_id).await?;
+        let revoked = token_repository::find_revoked_access_tokens(pool, user_id).await?;
+        let status = UserStatus::of(user.as_ref(), revoked);
+
+        let mut entries = self.entries.write().await;
+        if entries.len() >= USER_STATUS_CACHE_MAX_ENTRIES {
+            entries.retain(|_, (_, loaded_at)| loaded_at.elapsed() < Self::ttl());
+            // Still full of fresh entries: start over rather than grow without bound
+            if entries.len() >= USER_STATUS_CACHE_MAX_ENTRIES {
+                entries.clear();
+            }
+        }
+        entries.insert(user_id, (status.clone(), Instant::now()));
+
+        Ok(status)
+    }
+
+    /// Drops the cached status so the next request re-reads it from the database
+    pub async fn invalidate(&self, user_id: Uuid) {
+        self.entries.write().await.remove(&user_id);
+    }
+
+    /// Revokes every access token issued to the user so far.
+    /// Sessions that keep a valid refresh token obtain a new access token on their next refresh.
+    pub async fn revoke_access_tokens(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        user_repository::increment_token_version(pool, user_id).await?;
+        self.invalidate(user_id).await;
+        Ok(())
+    }
+
+    /// Revokes a single access token, leaving the user's other sessions signed in
+    pub async fn revoke_access_token(
+        &self,
+        pool: &PgPool,
+        user_id: Uuid,
+        claims: &Claims,
+    ) -> Result<(), sqlx::Error> {
+        let expires_at = DateTime::from_timestamp(claims.exp as i64, 0).unwrap_or_default();
+        token_repository::revoke_access_token(pool, &claims.jti, user_id, expires_at).await?;
+        self.invalidate(user_id).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::jwt::TokenType;
+
+    fn access_claims(jti: &str, token_version: i32) -> Claims {
+        Claims {
+            sub: Uuid::nil().to_string(),
+            iss: "issuer".to_string(),
+            aud: "audience".to_string(),
+            jti: jti.to_string(),
+            iat: 0,
+            nbf: 0,
+            exp: 0,
+            token_type: TokenType::Access.as_str().to_string(),
+            role: None,
+            token_version: Some(token_version),
+            scopes: None,
+        }
+    }
+
+    #[test]
+    fn test_revoked_jti_does_not_affect_other_sessions() {
+        let status = UserStatus {
+            account: AccountStatus::Active,
+            token_version: 1,
+            revoked_access_tokens: HashSet::from(["logged-out".to_string()]),
+        };
+        assert!(status.is_revoked(&access_claims("logged-out", 1)));
+        assert!(!status.is_revoked(&access_claims("other-session", 1)));
+        // Bumping the version still revokes every older token
+        assert!(status.is_revoked(&access_claims("other-session", 0)));
+    }
+}
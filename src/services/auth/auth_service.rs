@@ -1,95 +1,402 @@
 use crate::constant::auth::REFRESH_TOKEN_DURATION_DAYS;
 use crate::{
+    config::{JwtConfig, PasswordHashConfig},
     error::AuthError,
-    models::user::UserModel,
-    repository::{token_repository, user_repository},
-    utils::jwt::{TokenType, create_jwt, create_refresh_token, decode_jwt_with_type},
+    models::{role::Role, token::RefreshToken, user::UserModel},
+    repository::{is_unique_violation, token_repository, user_repository},
+    services::auth::{
+        account_deletion_service,
+        account_status_service::{UserStatusCache, ensure_user_active},
+        lockout_service::{self, ThrottleTarget},
+        mfa_service, password_service,
+    },
+    utils::{
+        device::DeviceInfo,
+        email::normalize_email,
+        jwt::{
+            TokenType, create_account_restore_token, create_jwt, create_mfa_pending_token,
+            create_refresh_token, decode_jwt_with_type,
+        },
+        password,
+        token::hash_token,
+    },
 };
-use argon2::{
-    Argon2,
-    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
-};
-use chrono::{Duration, Utc};
-use sha2::{Digest, Sha256};
+use chrono::{DateTime, Duration, Utc};
 use sqlx::PgPool;
+use std::path::Path;
 use uuid::Uuid;
 
-fn hash_password(password: &str) -> Result<String, AuthError> {
-    let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-    argon2
-        .hash_password(password.as_bytes(), &salt)
-        .map(|hash| hash.to_string())
-        .map_err(|e| AuthError::HashingError(e.to_string()))
-}
-
-fn hash_token(token: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(token.as_bytes());
-    format!("{:x}", hasher.finalize())
+/// Hashing is deliberately slow, so it runs on the blocking thread pool
+pub(crate) async fn hash_password(
+    password: &str,
+    config: &PasswordHashConfig,
+) -> Result<String, AuthError> {
+    let password = password.to_string();
+    let config = config.clone();
+    tokio::task::spawn_blocking(move || password::hash_password(&password, &config))
+        .await
+        .map_err(|e| AuthError::HashingError(e.to_string()))?
+        .map_err(AuthError::HashingError)
 }
 
+/// Creates a new, unverified account.
+/// No session is issued until the email address has been verified.
 pub async fn register_user(
     pool: &PgPool,
     email: &str,
+    email_normalized: &str,
     password: &str,
-    jwt_secret: &str,
-) -> Result<(String, String, UserModel), AuthError> {
-    if user_repository::find_user_by_email(pool, email).await?.is_some() {
+    password_config: &PasswordHashConfig,
+    breached_passwords_dir: Option<&Path>,
+) -> Result<UserModel, AuthError> {
+    if user_repository::find_user_by_email(pool, email_normalized)
+        .await?
+        .is_some()
+    {
         return Err(AuthError::EmailAlreadyExists);
     }
-    let hashed_password = hash_password(password)?;
-    let user = user_repository::create_user(pool, email, &hashed_password)
+    password_service::ensure_not_breached(breached_passwords_dir, password).await?;
+
+    let hashed_password = hash_password(password, password_config).await?;
+    // A concurrent registration can still claim the address between the check and the insert
+    let user = user_repository::create_user(pool, email, email_normalized, &hashed_password)
         .await
         .map_err(|e| {
-            if e.to_string().contains("duplicate key")
-                || e.to_string().contains("unique constraint")
-            {
+            if is_unique_violation(&e) {
                 AuthError::EmailAlreadyExists
             } else {
                 AuthError::from(e)
             }
         })?;
 
-    let token = create_jwt(&user.id.to_string(), jwt_secret)?;
-    let refresh_token = create_refresh_token(&user.id.to_string(), jwt_secret)?;
+    Ok(user)
+}
+
+/// Accounts without a password (created through an external provider) never match
+pub(crate) async fn verify_password(
+    password: &str,
+    password_hash: Option<&str>,
+    config: &PasswordHashConfig,
+) -> Result<(), AuthError> {
+    let Some(password_hash) = password_hash else {
+        return Err(AuthError::InvalidCredentials);
+    };
+    let password = password.to_string();
+    let password_hash = password_hash.to_string();
+    let config = config.clone();
+
+    let matches = tokio::task::spawn_blocking(move || {
+        password::verify_password(&password, &password_hash, &config)
+    })
+    .await
+    .map_err(|e| AuthError::HashingError(e.to_string()))?
+    .map_err(AuthError::HashingError)?;
+
+    if matches {
+        Ok(())
+    } else {
+        Err(AuthError::InvalidCredentials)
+    }
+}
+
+/// Re-authenticates an already logged-in user before a sensitive change.
+/// A wrong password is reported as IncorrectPassword rather than InvalidCredentials (401),
+/// so clients do not mistake it for an expired session.
+pub(crate) async fn verify_current_password(
+    password: &str,
+    password_hash: Option<&str>,
+    config: &PasswordHashConfig,
+) -> Result<(), AuthError> {
+    verify_password(password, password_hash, config)
+        .await
+        .map_err(|e| match e {
+            AuthError::InvalidCredentials => AuthError::IncorrectPassword,
+            other => other,
+        })
+}
+
+/// Creates an access token and a refresh token for the user and stores the refresh token
+/// in the given family. Shared by every flow that signs a user in or rotates a session.
+pub async fn issue_session_tokens(
+    pool: &PgPool,
+    user: &UserModel,
+    family_id: Uuid,
+    device: &DeviceInfo,
+    jwt: &JwtConfig,
+) -> Result<(String, String), AuthError> {
+    let token = create_jwt(
+        &user.id.to_string(),
+        Role::from_db(&user.role),
+        user.token_version,
+        jwt,
+    )?;
+    let refresh_token = create_refresh_token(&user.id.to_string(), jwt)?;
 
     let expires_at = Utc::now() + Duration::days(REFRESH_TOKEN_DURATION_DAYS);
     let token_hash = hash_token(&refresh_token);
 
-    token_repository::create_token(pool, user.id, &token_hash, expires_at).await?;
+    token_repository::create_token(pool, user.id, family_id, &token_hash, expires_at, device)
+        .await?;
 
-    Ok((token, refresh_token, user))
+    Ok((token, refresh_token))
 }
 
-fn verify_password(password: &str, password_hash: &str) -> Result<(), AuthError> {
-    let parsed_hash =
-        PasswordHash::new(password_hash).map_err(|e| AuthError::HashingError(e.to_string()))?;
+/// Issues a new access token to the session holding `refresh_token`, after a change made from
+/// that session revoked all of the user's access tokens. Nothing is issued when the session is
+/// not the user's or has been signed out.
+pub async fn reissue_access_token(
+    pool: &PgPool,
+    user_id: Uuid,
+    refresh_token: Option<&str>,
+    jwt: &JwtConfig,
+) -> Result<Option<String>, AuthError> {
+    let Some(refresh_token) = refresh_token else {
+        return Ok(None);
+    };
+    let signed_in = token_repository::find_token_by_hash(pool, &hash_token(refresh_token))
+        .await?
+        .is_some_and(|token| {
+            token.user_id == user_id && !token.used && token.expires_at > Utc::now()
+        });
+    if !signed_in {
+        return Ok(None);
+    }
+
+    let Some(user) = user_repository::find_user_by_id(pool, user_id)
+        .await?
+        .filter(|user| ensure_user_active(user).is_ok())
+    else {
+        return Ok(None);
+    };
+    Ok(Some(create_jwt(
+        &user.id.to_string(),
+        Role::from_db(&user.role),
+        user.token_version,
+        jwt,
+    )?))
+}
+
+/// Result of the password step of a login
+pub enum LoginOutcome {
+    /// Credentials were sufficient; a session has been issued
+    Authenticated {
+        access_token: String,
+        refresh_token: String,
+        user: UserModel,
+    },
+    /// The account has two-factor authentication enabled.
+    /// The short-lived token must be exchanged together with a code via `complete_mfa_login`.
+    MfaRequired { mfa_token: String },
+    /// The account was deleted but is still within its grace period.
+    /// The short-lived token can be exchanged via `restore_deleted_account` to undo the deletion.
+    RestoreAvailable {
+        restore_token: String,
+        purge_at: DateTime<Utc>,
+    },
+}
+
+/// Replaces a hash made with outdated settings now that the password is known.
+/// Failures are only logged; the login itself already succeeded.
+async fn upgrade_password_hash(
+    pool: &PgPool,
+    user: &UserModel,
+    password: &str,
+    config: &PasswordHashConfig,
+) {
+    let Some(current_hash) = user.password_hash.as_deref() else {
+        return;
+    };
+    if !password::needs_rehash(current_hash, config) {
+        return;
+    }
+
+    let result = match hash_password(password, config).await {
+        Ok(new_hash) => {
+            user_repository::upgrade_password_hash(pool, user.id, current_hash, &new_hash)
+                .await
+                .map_err(AuthError::from)
+        }
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(()) => tracing::info!(user_id = %user.id, "Password hash upgraded to current settings"),
+        Err(e) => tracing::warn!(user_id = %user.id, "Failed to upgrade password hash: {:?}", e),
+    }
+}
+
+/// Brings the stored normalized addresses in line with the current rules. Every address is
+/// revisited only when the rules changed, e.g. provider rules were switched on; otherwise just
+/// the ones not normalized yet. An address that would collide with another account's keeps its
+/// previous form and is flagged as conflicting, and flagged accounts are not retried.
+/// Returns how many addresses were updated.
+pub async fn normalize_stored_emails(
+    pool: &PgPool,
+    provider_rules: bool,
+) -> Result<u64, sqlx::Error> {
+    let rules_unchanged =
+        user_repository::find_email_normalization_rules(pool).await? == Some(provider_rules);
+
+    let mut updated = 0;
+    for (user_id, email, stored) in
+        user_repository::find_emails_to_normalize(pool, rules_unchanged).await?
+    {
+        let normalized = match normalize_email(&email, provider_rules) {
+            Ok(normalized) => normalized,
+            Err(e) => {
+                tracing::warn!(user_id = %user_id, "Stored email cannot be normalized: {}", e);
+                continue;
+            }
+        };
+        if stored.as_deref() == Some(normalized.as_str()) {
+            continue;
+        }
+
+        match user_repository::update_email_normalized(pool, user_id, &normalized).await {
+            Ok(()) => updated += 1,
+            Err(e) if is_unique_violation(&e) => {
+                tracing::warn!(
+                    user_id = %user_id,
+                    "Normalized email collides with another account, flagged for support"
+                );
+                user_repository::flag_email_conflict(pool, user_id, &normalized).await?;
+            }
+            Err(e) => return Err(e),
+        }
+    }
 
-    Argon2::default()
-        .verify_password(password.as_bytes(), &parsed_hash)
-        .map_err(|_| AuthError::InvalidCredentials)
+    if !rules_unchanged {
+        user_repository::set_email_normalization_rules(pool, provider_rules).await?;
+    }
+    Ok(updated)
 }
 
+/// Password login; `email` is the normalized address
 pub async fn login_user(
     pool: &PgPool,
     email: &str,
     password: &str,
-    jwt_secret: &str,
+    device: &DeviceInfo,
+    jwt: &JwtConfig,
+    password_config: &PasswordHashConfig,
+    deletion_grace_days: i64,
+) -> Result<LoginOutcome, AuthError> {
+    let Some(user) = user_repository::find_user_by_email(pool, email).await? else {
+        // Unknown addresses are throttled the same way, so lockouts do not reveal which emails exist
+        let target = ThrottleTarget::UnknownEmail(email);
+        lockout_service::ensure_not_locked(pool, target).await?;
+        return Err(
+            lockout_service::record_failure(pool, target, AuthError::InvalidCredentials).await,
+        );
+    };
+
+    let target = ThrottleTarget::User(user.id);
+    lockout_service::ensure_not_locked(pool, target).await?;
+
+    if let Err(e) = verify_password(password, user.password_hash.as_deref(), password_config).await
+    {
+        return Err(match e {
+            AuthError::InvalidCredentials => lockout_service::record_failure(pool, target, e).await,
+            other => other,
+        });
+    }
+
+    // Correct password: forget earlier failures
+    lockout_service::reset(pool, target).await?;
+    upgrade_password_hash(pool, &user, password, password_config).await;
+
+    // Checked only after the password, so account status is not revealed to guessers
+    if let Some(purge_at) = account_deletion_service::restorable_until(&user, deletion_grace_days) {
+        let restore_token = create_account_restore_token(&user.id.to_string(), jwt)?;
+        return Ok(LoginOutcome::RestoreAvailable {
+            restore_token,
+            purge_at,
+        });
+    }
+    ensure_user_active(&user)?;
+
+    finish_login(pool, user, device, jwt).await
+}
+
+/// Restores a deleted account within its grace period, then continues the login it interrupted
+pub async fn restore_deleted_account(
+    pool: &PgPool,
+    status_cache: &UserStatusCache,
+    restore_token: &str,
+    device: &DeviceInfo,
+    jwt: &JwtConfig,
+    deletion_grace_days: i64,
+) -> Result<LoginOutcome, AuthError> {
+    let user = account_deletion_service::restore_account(
+        pool,
+        status_cache,
+        restore_token,
+        jwt,
+        deletion_grace_days,
+    )
+    .await?;
+    ensure_user_active(&user)?;
+
+    finish_login(pool, user, device, jwt).await
+}
+
+/// Remaining login steps once the password has been accepted
+pub(crate) async fn finish_login(
+    pool: &PgPool,
+    user: UserModel,
+    device: &DeviceInfo,
+    jwt: &JwtConfig,
+) -> Result<LoginOutcome, AuthError> {
+    if user.email_verified_at.is_none() {
+        return Err(AuthError::EmailNotVerified);
+    }
+
+    if mfa_service::is_totp_enabled(pool, user.id).await? {
+        let mfa_token = create_mfa_pending_token(&user.id.to_string(), jwt)?;
+        return Ok(LoginOutcome::MfaRequired { mfa_token });
+    }
+
+    // Every login starts a new token family
+    let (access_token, refresh_token) =
+        issue_session_tokens(pool, &user, Uuid::new_v4(), device, jwt).await?;
+
+    Ok(LoginOutcome::Authenticated {
+        access_token,
+        refresh_token,
+        user,
+    })
+}
+
+/// Second step of a login for accounts with two-factor authentication
+pub async fn complete_mfa_login(
+    pool: &PgPool,
+    mfa_token: &str,
+    code: &str,
+    device: &DeviceInfo,
+    jwt: &JwtConfig,
 ) -> Result<(String, String, UserModel), AuthError> {
-    let user = user_repository::find_user_by_email(pool, email)
+    let claims = decode_jwt_with_type(mfa_token, jwt, TokenType::MfaPending)
+        .map_err(|_| AuthError::InvalidCredentials)?;
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| AuthError::InvalidCredentials)?;
+
+    let user = user_repository::find_user_by_id(pool, user_id)
         .await?
         .ok_or(AuthError::InvalidCredentials)?;
+    ensure_user_active(&user)?;
 
-    verify_password(password, &user.password_hash)?;
+    // Wrong second factors count towards the same lockout as wrong passwords
+    let target = ThrottleTarget::User(user.id);
+    lockout_service::ensure_not_locked(pool, target).await?;
 
-    let token = create_jwt(&user.id.to_string(), jwt_secret)?;
-    let refresh_token = create_refresh_token(&user.id.to_string(), jwt_secret)?;
-
-    let expires_at = Utc::now() + Duration::days(REFRESH_TOKEN_DURATION_DAYS);
-    let token_hash = hash_token(&refresh_token);
+    if let Err(e) = mfa_service::verify_second_factor(pool, user.id, code).await {
+        return Err(match e {
+            AuthError::InvalidMfaCode => lockout_service::record_failure(pool, target, e).await,
+            other => other,
+        });
+    }
+    lockout_service::reset(pool, target).await?;
 
-    token_repository::create_token(pool, user.id, &token_hash, expires_at).await?;
+    let (token, refresh_token) =
+        issue_session_tokens(pool, &user, Uuid::new_v4(), device, jwt).await?;
 
     Ok((token, refresh_token, user))
 }
@@ -97,10 +404,11 @@ pub async fn login_user(
 pub async fn refresh_access_token(
     pool: &PgPool,
     refresh_token: &str,
-    jwt_secret: &str,
+    device: &DeviceInfo,
+    jwt: &JwtConfig,
 ) -> Result<(String, String, UserModel), AuthError> {
-    let claims = decode_jwt_with_type(refresh_token, jwt_secret, TokenType::Refresh)
-        .map_err(|_| AuthError::InvalidCredentials)?;
+    let claims = decode_jwt_with_type(refresh_token, jwt, TokenType::Refresh)
+        .map_err(|_| AuthError::InvalidRefreshToken)?;
     let user_id = Uuid::parse_str(&claims.sub)
         .map_err(|_| AuthError::TokenCreationError("Invalid user ID".to_string()))?;
 
@@ -108,37 +416,82 @@ pub async fn refresh_access_token(
 
     let token_record = token_repository::find_token_by_hash(pool, &token_hash)
         .await?
-        .ok_or(AuthError::InvalidCredentials)?;
+        .ok_or(AuthError::InvalidRefreshToken)?;
 
-    if token_record.used || token_record.user_id != user_id {
-        return Err(AuthError::InvalidCredentials);
+    if token_record.user_id != user_id {
+        return Err(AuthError::InvalidRefreshToken);
+    }
+
+    // The session was signed out or revoked; a device still holding its token is not an attacker
+    if token_record.revoked_at.is_some() {
+        return Err(AuthError::InvalidRefreshToken);
+    }
+
+    // A used token being presented again means it was replayed after rotation:
+    // either the legitimate client or an attacker holds a stolen copy, so kill the whole chain.
+    if token_record.used {
+        revoke_reused_family(pool, &token_record).await?;
+        return Err(AuthError::RefreshTokenReused { user_id });
     }
 
     if token_record.expires_at < Utc::now() {
-        return Err(AuthError::InvalidCredentials);
+        return Err(AuthError::InvalidRefreshToken);
     }
 
-    token_repository::set_token_used(pool, &token_hash).await?;
+    // Lost race against a concurrent rotation of the same token counts as reuse too,
+    // losing it against a concurrent revocation does not
+    if !token_repository::consume_token(pool, &token_hash).await? {
+        let revoked = token_repository::find_token_by_hash(pool, &token_hash)
+            .await?
+            .is_none_or(|record| record.revoked_at.is_some());
+        if revoked {
+            return Err(AuthError::InvalidRefreshToken);
+        }
+        revoke_reused_family(pool, &token_record).await?;
+        return Err(AuthError::RefreshTokenReused { user_id });
+    }
 
     let user = user_repository::find_user_by_id(pool, user_id)
         .await?
-        .ok_or(AuthError::InvalidCredentials)?;
+        .ok_or(AuthError::InvalidRefreshToken)?;
 
-    let new_access_token = create_jwt(&user.id.to_string(), jwt_secret)?;
-    let new_refresh_token = create_refresh_token(&user.id.to_string(), jwt_secret)?;
+    // Suspended or deleted accounts cannot extend their sessions
+    if let Err(e) = ensure_user_active(&user) {
+        token_repository::revoke_family(pool, token_record.family_id).await?;
+        return Err(e);
+    }
 
-    let expires_at = Utc::now() + Duration::days(REFRESH_TOKEN_DURATION_DAYS);
-    let new_token_hash = hash_token(&new_refresh_token);
+    // The session keeps its name; address and user agent reflect the latest use
+    let device = DeviceInfo {
+        device_name: device
+            .device_name
+            .clone()
+            .or_else(|| token_record.device_name.clone()),
+        ..device.clone()
+    };
 
-    token_repository::create_token(pool, user.id, &new_token_hash, expires_at).await?;
+    let (new_access_token, new_refresh_token) =
+        issue_session_tokens(pool, &user, token_record.family_id, &device, jwt).await?;
 
     Ok((new_access_token, new_refresh_token, user))
 }
 
-/// Invalidate a refresh token by marking it as used in the database.
-/// This should be called during logout to prevent token reuse.
+/// Revokes the family of a refresh token that was presented after being used
+async fn revoke_reused_family(pool: &PgPool, token_record: &RefreshToken) -> Result<(), AuthError> {
+    let revoked = token_repository::revoke_family(pool, token_record.family_id).await?;
+    tracing::warn!(
+        user_id = %token_record.user_id,
+        family_id = %token_record.family_id,
+        revoked,
+        "Security event: refresh token reuse detected, token family revoked"
+    );
+    Ok(())
+}
+
+/// Invalidate a refresh token by revoking it in the database.
+/// This should be called during logout so the token can no longer be refreshed.
 pub async fn invalidate_refresh_token(pool: &PgPool, refresh_token: &str) -> Result<(), AuthError> {
     let token_hash = hash_token(refresh_token);
-    token_repository::set_token_used(pool, &token_hash).await?;
+    token_repository::revoke_token(pool, &token_hash).await?;
     Ok(())
 }
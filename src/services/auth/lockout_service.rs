@@ -0,0 +1,142 @@
+use crate::{
+    constant::auth::{
+        LOGIN_FAILURE_WINDOW_HOURS, LOGIN_LOCKOUT_BASE_SECONDS, LOGIN_LOCKOUT_MAX_SECONDS,
+        LOGIN_LOCKOUT_THRESHOLD,
+    },
+    error::AuthError,
+    repository::login_throttle_repository,
+};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// What failed login attempts are counted against
+#[derive(Debug, Clone, Copy)]
+pub enum ThrottleTarget<'a> {
+    /// An existing account
+    User(Uuid),
+    /// An email address with no account, so probing unknown addresses is throttled too
+    UnknownEmail(&'a str),
+}
+
+impl ThrottleTarget<'_> {
+    fn key(&self) -> String {
+        match self {
+            ThrottleTarget::User(id) => format!("user:{}", id),
+            ThrottleTarget::UnknownEmail(email) => format!("email:{}", email.to_lowercase()),
+        }
+    }
+
+    fn user_id(&self) -> Option<Uuid> {
+        match self {
+            ThrottleTarget::User(id) => Some(*id),
+            ThrottleTarget::UnknownEmail(_) => None,
+        }
+    }
+}
+
+/// Lockout applied after a given number of consecutive failures.
+/// The first few failures are free; after that the lockout doubles each time, up to a cap.
+pub fn lockout_duration(failed_attempts: i32) -> Option<Duration> {
+    if failed_attempts < LOGIN_LOCKOUT_THRESHOLD {
+        return None;
+    }
+
+    let exponent = (failed_attempts - LOGIN_LOCKOUT_THRESHOLD).min(30) as u32;
+    let seconds = LOGIN_LOCKOUT_BASE_SECONDS
+        .saturating_mul(2i64.saturating_pow(exponent))
+        .min(LOGIN_LOCKOUT_MAX_SECONDS);
+    Some(Duration::seconds(seconds))
+}
+
+/// Fails with TooManyLoginAttempts while the target is locked out
+pub async fn ensure_not_locked(pool: &PgPool, target: ThrottleTarget<'_>) -> Result<(), AuthError> {
+    let Some(throttle) = login_throttle_repository::find_by_key(pool, &target.key()).await? else {
+        return Ok(());
+    };
+
+    match throttle.locked_until {
+        Some(locked_until) if locked_until > Utc::now() => Err(AuthError::TooManyLoginAttempts {
+            retry_after_secs: (locked_until - Utc::now()).num_seconds().max(1) as u64,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Records a failed attempt. Returns TooManyLoginAttempts if this failure triggered a lockout,
+/// otherwise returns `error` unchanged so the caller reports the original failure.
+pub async fn record_failure(
+    pool: &PgPool,
+    target: ThrottleTarget<'_>,
+    error: AuthError,
+) -> AuthError {
+    let key = target.key();
+    let window_start = Utc::now() - Duration::hours(LOGIN_FAILURE_WINDOW_HOURS);
+
+    let failed_attempts =
+        match login_throttle_repository::record_failure(pool, &key, target.user_id(), window_start)
+            .await
+        {
+            Ok(count) => count,
+            Err(e) => return AuthError::from(e),
+        };
+
+    let Some(duration) = lockout_duration(failed_attempts) else {
+        return error;
+    };
+
+    if let Err(e) =
+        login_throttle_repository::set_locked_until(pool, &key, Utc::now() + duration).await
+    {
+        return AuthError::from(e);
+    }
+
+    tracing::warn!(
+        throttle_key = %key,
+        failed_attempts,
+        lockout_secs = duration.num_seconds(),
+        "Security event: login temporarily locked after repeated failures"
+    );
+
+    AuthError::TooManyLoginAttempts {
+        retry_after_secs: duration.num_seconds() as u64,
+    }
+}
+
+/// Forgets previous failures after a successful login or password reset
+pub async fn reset(pool: &PgPool, target: ThrottleTarget<'_>) -> Result<(), AuthError> {
+    login_throttle_repository::clear(pool, &target.key()).await?;
+    Ok(())
+}
+
+/// Deletes throttle entries that are outside the failure window and no longer locked
+pub async fn delete_stale_throttles(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let cutoff = Utc::now() - Duration::hours(LOGIN_FAILURE_WINDOW_HOURS);
+    login_throttle_repository::delete_stale(pool, cutoff).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_lockout_below_threshold() {
+        for attempts in 0..LOGIN_LOCKOUT_THRESHOLD {
+            assert!(lockout_duration(attempts).is_none());
+        }
+    }
+
+    #[test]
+    fn test_lockout_doubles_after_threshold() {
+        let first = lockout_duration(LOGIN_LOCKOUT_THRESHOLD).unwrap();
+        let second = lockout_duration(LOGIN_LOCKOUT_THRESHOLD + 1).unwrap();
+        assert_eq!(first.num_seconds(), LOGIN_LOCKOUT_BASE_SECONDS);
+        assert_eq!(second.num_seconds(), LOGIN_LOCKOUT_BASE_SECONDS * 2);
+    }
+
+    #[test]
+    fn test_lockout_is_capped() {
+        let duration = lockout_duration(i32::MAX).unwrap();
+        assert_eq!(duration.num_seconds(), LOGIN_LOCKOUT_MAX_SECONDS);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{
+    config::JwtConfig,
+    constant::auth::{
+        MAGIC_LINK_MAX_REQUESTS_PER_EMAIL, MAGIC_LINK_MAX_REQUESTS_PER_IP,
+        MAGIC_LINK_RATE_LIMIT_WINDOW_MINUTES, MAGIC_LINK_TOKEN_DURATION_MINUTES,
+    },
+    error::AuthError,
+    repository::{magic_link_repository, user_repository},
+    services::{
+        auth::{
+            account_deletion_service,
+            account_status_service::ensure_user_active,
+            auth_service::{self, LoginOutcome},
+        },
+        mail::{mail_service, sender::MailSender},
+    },
+    utils::{
+        device::DeviceInfo,
+        jwt::create_account_restore_token,
+        token::{generate_opaque_token, hash_token},
+    },
+};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+
+/// Emails a single-use sign-in link if an account exists for the (normalized) address.
+/// Succeeds for unknown addresses too so callers cannot probe for accounts;
+/// the per-address and per-IP limits apply either way.
+pub async fn request_magic_link(
+    pool: &PgPool,
+    mailer: &dyn MailSender,
+    app_base_url: &str,
+    email: &str,
+    ip_address: Option<&str>,
+) -> Result<(), AuthError> {
+    let email_hash = hash_token(email);
+    let window = Duration::minutes(MAGIC_LINK_RATE_LIMIT_WINDOW_MINUTES);
+
+    let (per_email, per_ip) = magic_link_repository::count_recent_requests(
+        pool,
+        &email_hash,
+        ip_address,
+        Utc::now() - window,
+    )
+    .await?;
+    if per_email >= MAGIC_LINK_MAX_REQUESTS_PER_EMAIL || per_ip >= MAGIC_LINK_MAX_REQUESTS_PER_IP {
+        return Err(AuthError::TooManyRequests {
+            retry_after_secs: window.num_seconds() as u64,
+        });
+    }
+    magic_link_repository::record_request(pool, &email_hash, ip_address).await?;
+
+    let Some(user) = user_repository::find_user_by_email(pool, email).await? else {
+        return Ok(());
+    };
+    // Suspended accounts could not use the link anyway
+    if !user.is_active {
+        return Ok(());
+    }
+
+    // Only the most recently requested link stays valid
+    magic_link_repository::invalidate_user_tokens(pool, user.id).await?;
+
+    let token = generate_opaque_token();
+    let expires_at = Utc::now() + Duration::minutes(MAGIC_LINK_TOKEN_DURATION_MINUTES);
+    magic_link_repository::create_token(pool, user.id, &hash_token(&token), expires_at).await?;
+
+    mail_service::send_magic_link_email(mailer, app_base_url, &user.email, &token)
+        .await
+        .map_err(|e| AuthError::MailDeliveryError(e.to_string()))
+}
+
+/// Consumes a sign-in link. Continues like a password login: deleted accounts are offered
+/// a restore and accounts with two-factor authentication still need their code.
+pub async fn consume_magic_link(
+    pool: &PgPool,
+    token: &str,
+    device: &DeviceInfo,
+    jwt: &JwtConfig,
+    deletion_grace_days: i64,
+) -> Result<LoginOutcome, AuthError> {
+    let record = magic_link_repository::consume_token(pool, &hash_token(token))
+        .await?
+        .ok_or(AuthError::InvalidOrExpiredToken)?;
+
+    // Following the emailed link proves ownership of the address
+    user_repository::mark_email_verified(pool, record.user_id).await?;
+    let user = user_repository::find_user_by_id(pool, record.user_id)
+        .await?
+        .ok_or(AuthError::InvalidOrExpiredToken)?;
+
+    if let Some(purge_at) = account_deletion_service::restorable_until(&user, deletion_grace_days) {
+        let restore_token = create_account_restore_token(&user.id.to_string(), jwt)?;
+        return Ok(LoginOutcome::RestoreAvailable {
+            restore_token,
+            purge_at,
+        });
+    }
+    ensure_user_active(&user)?;
+
+    auth_service::finish_login(pool, user, device, jwt).await
+}
+
+/// Deletes rate-limit records that no longer count towards any window
+pub async fn delete_stale_requests(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let cutoff = Utc::now() - Duration::minutes(MAGIC_LINK_RATE_LIMIT_WINDOW_MINUTES);
+    magic_link_repository::delete_requests_before(pool, cutoff).await
+}
@@ -0,0 +1,184 @@
+use crate::{
+    config::PasswordHashConfig,
+    constant::auth::MFA_RECOVERY_CODE_COUNT,
+    error::AuthError,
+    repository::{mfa_repository, user_repository},
+    services::auth::auth_service::verify_current_password,
+    utils::{token::hash_token, totp},
+};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Alphabet for recovery codes, without look-alike characters (0/o, 1/l/i)
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+const RECOVERY_CODE_GROUP_LENGTH: usize = 5;
+
+pub struct MfaStatus {
+    pub enabled: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+fn now_unix() -> u64 {
+    Utc::now().timestamp().max(0) as u64
+}
+
+/// Generates a recovery code formatted as two groups, e.g. `k3m9x-q2w7r`
+fn generate_recovery_code() -> String {
+    let mut bytes = [0u8; RECOVERY_CODE_GROUP_LENGTH * 2];
+    OsRng.fill_bytes(&mut bytes);
+
+    let chars: String = bytes
+        .iter()
+        .map(|b| RECOVERY_CODE_ALPHABET[*b as usize % RECOVERY_CODE_ALPHABET.len()] as char)
+        .collect();
+    let (first, second) = chars.split_at(RECOVERY_CODE_GROUP_LENGTH);
+    format!("{}-{}", first, second)
+}
+
+/// Recovery codes are compared case-insensitively and without separators
+fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    hash_token(&normalized)
+}
+
+pub async fn is_totp_enabled(pool: &PgPool, user_id: Uuid) -> Result<bool, AuthError> {
+    Ok(mfa_repository::find_totp(pool, user_id)
+        .await?
+        .is_some_and(|totp| totp.enabled_at.is_some()))
+}
+
+pub async fn get_status(pool: &PgPool, user_id: Uuid) -> Result<MfaStatus, AuthError> {
+    let enabled = is_totp_enabled(pool, user_id).await?;
+    let recovery_codes_remaining = if enabled {
+        mfa_repository::count_unused_recovery_codes(pool, user_id).await?
+    } else {
+        0
+    };
+
+    Ok(MfaStatus {
+        enabled,
+        recovery_codes_remaining,
+    })
+}
+
+/// Starts TOTP enrolment: stores a new unconfirmed secret and returns it with its otpauth:// URI
+pub async fn start_totp_enrollment(
+    pool: &PgPool,
+    user_id: Uuid,
+    issuer: &str,
+) -> Result<(String, String), AuthError> {
+    let user = user_repository::find_user_by_id(pool, user_id)
+        .await?
+        .ok_or(AuthError::InvalidCredentials)?;
+
+    if is_totp_enabled(pool, user_id).await? {
+        return Err(AuthError::MfaAlreadyEnabled);
+    }
+
+    let secret = totp::generate_secret();
+    if mfa_repository::upsert_pending_totp(pool, user_id, &secret).await? == 0 {
+        // Enabled concurrently by another request
+        return Err(AuthError::MfaAlreadyEnabled);
+    }
+
+    let uri = totp::otpauth_uri(issuer, &user.email, &secret);
+    Ok((secret, uri))
+}
+
+/// Confirms enrolment with a first code from the authenticator app.
+/// Returns the plaintext recovery codes, which are shown to the user exactly once.
+pub async fn confirm_totp_enrollment(
+    pool: &PgPool,
+    user_id: Uuid,
+    code: &str,
+) -> Result<Vec<String>, AuthError> {
+    let pending = mfa_repository::find_totp(pool, user_id)
+        .await?
+        .ok_or(AuthError::MfaNotEnabled)?;
+
+    if pending.enabled_at.is_some() {
+        return Err(AuthError::MfaAlreadyEnabled);
+    }
+
+    let step =
+        totp::verify_code(&pending.secret, code, now_unix()).ok_or(AuthError::InvalidMfaCode)?;
+
+    let recovery_codes: Vec<String> = (0..MFA_RECOVERY_CODE_COUNT)
+        .map(|_| generate_recovery_code())
+        .collect();
+    let code_hashes: Vec<String> = recovery_codes
+        .iter()
+        .map(|c| hash_recovery_code(c))
+        .collect();
+
+    mfa_repository::replace_recovery_codes(pool, user_id, &code_hashes).await?;
+    mfa_repository::enable_totp(pool, user_id, step as i64).await?;
+
+    Ok(recovery_codes)
+}
+
+/// Verifies a second factor for an enabled user: a current TOTP code or an unused recovery code.
+/// Both are single-use.
+pub async fn verify_second_factor(
+    pool: &PgPool,
+    user_id: Uuid,
+    code: &str,
+) -> Result<(), AuthError> {
+    let totp_record = mfa_repository::find_totp(pool, user_id)
+        .await?
+        .filter(|t| t.enabled_at.is_some())
+        .ok_or(AuthError::MfaNotEnabled)?;
+
+    if let Some(step) = totp::verify_code(&totp_record.secret, code, now_unix()) {
+        if mfa_repository::record_totp_step(pool, user_id, step as i64).await? {
+            return Ok(());
+        }
+        // Valid code, but already used: treat as invalid to prevent replay
+        return Err(AuthError::InvalidMfaCode);
+    }
+
+    if mfa_repository::consume_recovery_code(pool, user_id, &hash_recovery_code(code)).await? {
+        tracing::info!(user_id = %user_id, "MFA recovery code used");
+        return Ok(());
+    }
+
+    Err(AuthError::InvalidMfaCode)
+}
+
+/// Disables TOTP after re-authentication with either a fresh code or the account password
+pub async fn disable_totp(
+    pool: &PgPool,
+    user_id: Uuid,
+    code: Option<&str>,
+    password: Option<&str>,
+    password_config: &PasswordHashConfig,
+) -> Result<(), AuthError> {
+    if !is_totp_enabled(pool, user_id).await? {
+        return Err(AuthError::MfaNotEnabled);
+    }
+
+    match (code, password) {
+        (Some(code), _) => verify_second_factor(pool, user_id, code).await?,
+        (None, Some(password)) => {
+            let user = user_repository::find_user_by_id(pool, user_id)
+                .await?
+                .ok_or(AuthError::InvalidCredentials)?;
+            verify_current_password(password, user.password_hash.as_deref(), password_config)
+                .await?;
+        }
+        (None, None) => {
+            return Err(AuthError::ValidationError(
+                "An authentication code or the account password is required".to_string(),
+            ));
+        }
+    }
+
+    mfa_repository::delete_mfa(pool, user_id).await?;
+    Ok(())
+}
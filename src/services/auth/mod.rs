@@ -1 +1,11 @@
+pub mod account_deletion_service;
+pub mod account_status_service;
 pub mod auth_service;
+pub mod lockout_service;
+pub mod magic_link_service;
+pub mod mfa_service;
+pub mod oidc_service;
+pub mod passkey_service;
+pub mod password_service;
+pub mod session_service;
+pub mod verification_service;
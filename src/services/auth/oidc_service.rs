@@ -0,0 +1,721 @@
+use crate::{
+    config::{JwtConfig, OidcProviderConfig, R2Config},
+    constant::{
+        auth::{
+            OIDC_AUTH_REQUEST_DURATION_MINUTES, OIDC_HTTP_TIMEOUT_SECONDS,
+            OIDC_ID_TOKEN_LEEWAY_SECONDS, OIDC_METADATA_CACHE_TTL_SECONDS,
+        },
+        image::{ALLOWED_CONTENT_TYPES, MAX_AVATAR_SIZE},
+    },
+    error::AuthError,
+    models::identity::{OidcAuthRequestModel, UserIdentityModel},
+    repository::{
+        identity_repository, is_unique_violation, profile_repository, user_repository,
+        webauthn_repository,
+    },
+    services::{
+        auth::{
+            account_deletion_service,
+            account_status_service::ensure_user_active,
+            auth_service::{self, LoginOutcome},
+        },
+        profile_service,
+    },
+    utils::{
+        device::DeviceInfo,
+        email::normalize_email,
+        jwt::create_account_restore_token,
+        pkce,
+        public_host::resolve_public_host,
+        token::{generate_opaque_token, hash_token},
+        validation::validate_full_name,
+    },
+};
+use aws_sdk_s3::Client as S3Client;
+use chrono::{Duration, Utc};
+use jsonwebtoken::{DecodingKey, Validation, decode, decode_header, jwk::JwkSet};
+use reqwest::{Client, Url, header::CONTENT_TYPE, redirect::Policy};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration as StdDuration, Instant},
+};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// The parts of the provider's discovery document used by the authorization-code flow
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+    #[serde(default)]
+    pub token_endpoint_auth_methods_supported: Vec<String>,
+}
+
+struct CachedProvider {
+    metadata: ProviderMetadata,
+    jwks: JwkSet,
+    fetched_at: Instant,
+}
+
+/// HTTP client for OpenID Connect providers, caching discovery documents and signing keys
+#[derive(Clone)]
+pub struct OidcClient {
+    http: Client,
+    cache: Arc<RwLock<HashMap<String, Arc<CachedProvider>>>>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Deserialize)]
+struct TokenErrorResponse {
+    error: String,
+}
+
+/// Claims of a verified ID token
+#[derive(Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    /// Some providers send the flag as a string
+    #[serde(default)]
+    email_verified: Option<serde_json::Value>,
+    pub name: Option<String>,
+    pub picture: Option<String>,
+    nonce: Option<String>,
+}
+
+impl IdTokenClaims {
+    /// The email address, if the provider vouches for it
+    pub fn verified_email(&self) -> Option<&str> {
+        let verified = match &self.email_verified {
+            Some(serde_json::Value::Bool(verified)) => *verified,
+            Some(serde_json::Value::String(verified)) => verified == "true",
+            _ => false,
+        };
+        self.email
+            .as_deref()
+            .map(str::trim)
+            .filter(|email| verified && !email.is_empty())
+    }
+}
+
+/// Result of a sign-in through a provider
+pub struct ProviderLogin {
+    pub outcome: LoginOutcome,
+    pub user_id: Uuid,
+    /// True when the sign-in created the account
+    pub new_account: bool,
+}
+
+fn provider_error(provider: &str, message: impl std::fmt::Display) -> AuthError {
+    tracing::warn!(provider = provider, "OIDC provider error: {}", message);
+    AuthError::OidcProviderError(message.to_string())
+}
+
+impl OidcClient {
+    pub fn new() -> Result<Self, reqwest::Error> {
+        let http = Client::builder()
+            .timeout(StdDuration::from_secs(OIDC_HTTP_TIMEOUT_SECONDS))
+            .build()?;
+        Ok(Self {
+            http,
+            cache: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Returns the provider's metadata and keys, fetching them when missing or stale.
+    /// `refresh` forces a fetch, e.g. when a token is signed with a key we have not seen.
+    async fn provider(
+        &self,
+        config: &OidcProviderConfig,
+        refresh: bool,
+    ) -> Result<Arc<CachedProvider>, AuthError> {
+        if !refresh
+            && let Some(cached) = self.cache.read().await.get(&config.name)
+            && cached.fetched_at.elapsed() < StdDuration::from_secs(OIDC_METADATA_CACHE_TTL_SECONDS)
+        {
+            return Ok(cached.clone());
+        }
+
+        let discovery_url = format!("{}/.well-known/openid-configuration", config.issuer);
+        let metadata: ProviderMetadata = self.get_json(&config.name, &discovery_url).await?;
+        if metadata.issuer.trim_end_matches('/') != config.issuer {
+            return Err(provider_error(
+                &config.name,
+                format!("discovery document is for issuer '{}'", metadata.issuer),
+            ));
+        }
+        let jwks: JwkSet = self.get_json(&config.name, &metadata.jwks_uri).await?;
+
+        let cached = Arc::new(CachedProvider {
+            metadata,
+            jwks,
+            fetched_at: Instant::now(),
+        });
+        self.cache
+            .write()
+            .await
+            .insert(config.name.clone(), cached.clone());
+        Ok(cached)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(
+        &self,
+        provider: &str,
+        url: &str,
+    ) -> Result<T, AuthError> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| provider_error(provider, e))?
+            .json()
+            .await
+            .map_err(|e| provider_error(provider, e))
+    }
+
+    /// Exchanges an authorization code for the provider's ID token
+    async fn exchange_code(
+        &self,
+        config: &OidcProviderConfig,
+        metadata: &ProviderMetadata,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<String, AuthError> {
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", config.redirect_uri.as_str()),
+            ("client_id", config.client_id.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        let mut request = self.http.post(&metadata.token_endpoint);
+
+        // client_secret_basic is the default when the provider does not list its methods
+        if let Some(secret) = &config.client_secret {
+            if metadata
+                .token_endpoint_auth_methods_supported
+                .iter()
+                .any(|method| method == "client_secret_post")
+            {
+                form.push(("client_secret", secret.as_str()));
+            } else {
+                request = request.basic_auth(&config.client_id, Some(secret));
+            }
+        }
+
+        let response = request
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| provider_error(&config.name, e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let reason = match response.json::<TokenErrorResponse>().await {
+                Ok(body) => body.error,
+                Err(_) => status.to_string(),
+            };
+            // invalid_grant: the code was already used, expired or issued to someone else
+            if reason == "invalid_grant" {
+                return Err(AuthError::InvalidOrExpiredToken);
+            }
+            return Err(provider_error(
+                &config.name,
+                format!("token request failed: {}", reason),
+            ));
+        }
+
+        let body: TokenResponse = response
+            .json()
+            .await
+            .map_err(|e| provider_error(&config.name, e))?;
+        Ok(body.id_token)
+    }
+}
+
+/// Verifies an ID token's signature, issuer, audience, expiry and nonce
+pub fn verify_id_token(
+    id_token: &str,
+    jwks: &JwkSet,
+    issuer: &str,
+    client_id: &str,
+    nonce: &str,
+) -> Result<IdTokenClaims, AuthError> {
+    let invalid =
+        |reason: &str| AuthError::OidcProviderError(format!("invalid ID token: {}", reason));
+
+    let header = decode_header(id_token).map_err(|_| invalid("malformed header"))?;
+    let jwk = match &header.kid {
+        Some(kid) => jwks.find(kid),
+        // Without a key ID the choice is only unambiguous for a single key
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }
+    .ok_or_else(|| invalid("unknown signing key"))?;
+
+    if let Some(key_algorithm) = jwk.common.key_algorithm
+        && key_algorithm.to_string() != format!("{:?}", header.alg)
+    {
+        return Err(invalid("algorithm does not match the signing key"));
+    }
+    let key = DecodingKey::from_jwk(jwk).map_err(|_| invalid("unsupported signing key"))?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_issuer(&[issuer]);
+    validation.set_audience(&[client_id]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+    validation.leeway = OIDC_ID_TOKEN_LEEWAY_SECONDS;
+
+    let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+        .map_err(|e| invalid(&e.to_string()))?
+        .claims;
+
+    // The nonce ties the token to the authorization request this browser started
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err(invalid("nonce mismatch"));
+    }
+
+    Ok(claims)
+}
+
+/// Starts an authorization-code flow with PKCE and returns the provider URL to send the browser to.
+/// `link_user_id` is set when a signed-in user links the provider to their account.
+pub async fn start_authorization(
+    pool: &PgPool,
+    client: &OidcClient,
+    config: &OidcProviderConfig,
+    link_user_id: Option<Uuid>,
+) -> Result<String, AuthError> {
+    let provider = client.provider(config, false).await?;
+
+    let state = generate_opaque_token();
+    let nonce = generate_opaque_token();
+    let code_verifier = pkce::generate_code_verifier();
+    let expires_at = Utc::now() + Duration::minutes(OIDC_AUTH_REQUEST_DURATION_MINUTES);
+
+    identity_repository::create_auth_request(
+        pool,
+        &hash_token(&state),
+        &config.name,
+        &code_verifier,
+        &nonce,
+        link_user_id,
+        expires_at,
+    )
+    .await?;
+
+    let mut url = Url::parse(&provider.metadata.authorization_endpoint)
+        .map_err(|e| provider_error(&config.name, e))?;
+    url.query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", &config.client_id)
+        .append_pair("redirect_uri", &config.redirect_uri)
+        .append_pair("scope", &config.scopes)
+        .append_pair("state", &state)
+        .append_pair("nonce", &nonce)
+        .append_pair("code_challenge", &pkce::code_challenge(&code_verifier))
+        .append_pair("code_challenge_method", "S256");
+
+    Ok(url.to_string())
+}
+
+/// Consumes the `state`, exchanges the code and verifies the returned ID token
+async fn complete_authorization(
+    pool: &PgPool,
+    client: &OidcClient,
+    config: &OidcProviderConfig,
+    code: &str,
+    state: &str,
+) -> Result<(OidcAuthRequestModel, IdTokenClaims), AuthError> {
+    let request = identity_repository::take_auth_request(pool, &hash_token(state))
+        .await?
+        .filter(|request| request.provider == config.name)
+        .ok_or(AuthError::InvalidOrExpiredToken)?;
+
+    let provider = client.provider(config, false).await?;
+    let id_token = client
+        .exchange_code(config, &provider.metadata, code, &request.code_verifier)
+        .await?;
+
+    // The provider may have rotated its keys since they were cached
+    let kid = decode_header(&id_token).ok().and_then(|header| header.kid);
+    let provider = match kid {
+        Some(kid) if provider.jwks.find(&kid).is_none() => client.provider(config, true).await?,
+        _ => provider,
+    };
+
+    // Tokens carry the issuer exactly as discovered, which may end in a `/` that config trims
+    let claims = verify_id_token(
+        &id_token,
+        &provider.jwks,
+        &provider.metadata.issuer,
+        &config.client_id,
+        &request.nonce,
+    )
+    .map_err(|e| provider_error(&config.name, e))?;
+
+    Ok((request, claims))
+}
+
+/// Signs a user in with a provider, creating a verified account on first use.
+/// An existing password account with the same email is never taken over automatically;
+/// its owner has to sign in and link the provider first.
+#[allow(clippy::too_many_arguments)]
+pub async fn login_with_provider(
+    pool: &PgPool,
+    client: &OidcClient,
+    s3_client: &S3Client,
+    r2: &R2Config,
+    config: &OidcProviderConfig,
+    code: &str,
+    state: &str,
+    device: &DeviceInfo,
+    jwt: &JwtConfig,
+    deletion_grace_days: i64,
+    email_provider_rules: bool,
+) -> Result<ProviderLogin, AuthError> {
+    let (request, claims) = complete_authorization(pool, client, config, code, state).await?;
+    // Link requests must be completed by the signed-in user who started them
+    if request.link_user_id.is_some() {
+        return Err(AuthError::InvalidOrExpiredToken);
+    }
+
+    if let Some(identity) =
+        identity_repository::find_identity(pool, &config.name, &claims.sub).await?
+    {
+        let user = user_repository::find_user_by_id(pool, identity.user_id)
+            .await?
+            .ok_or(AuthError::InvalidCredentials)?;
+        identity_repository::touch_last_login(pool, identity.id, claims.verified_email()).await?;
+
+        if let Some(purge_at) =
+            account_deletion_service::restorable_until(&user, deletion_grace_days)
+        {
+            let restore_token = create_account_restore_token(&user.id.to_string(), jwt)?;
+            return Ok(ProviderLogin {
+                outcome: LoginOutcome::RestoreAvailable {
+                    restore_token,
+                    purge_at,
+                },
+                user_id: user.id,
+                new_account: false,
+            });
+        }
+        ensure_user_active(&user)?;
+
+        let user_id = user.id;
+        let outcome = auth_service::finish_login(pool, user, device, jwt).await?;
+        return Ok(ProviderLogin {
+            outcome,
+            user_id,
+            new_account: false,
+        });
+    }
+
+    let email = claims
+        .verified_email()
+        .ok_or(AuthError::OidcEmailNotVerified)?;
+    let email_normalized = normalize_email(email, email_provider_rules)
+        .map_err(|e| AuthError::OidcProviderError(format!("Invalid email claim: {}", e)))?;
+    if user_repository::find_user_by_email(pool, &email_normalized)
+        .await?
+        .is_some()
+    {
+        return Err(AuthError::OidcAccountExists);
+    }
+
+    let user = identity_repository::create_user_with_identity(
+        pool,
+        email,
+        &email_normalized,
+        &config.name,
+        &claims.sub,
+    )
+    .await
+    .map_err(|e| {
+        if is_unique_violation(&e) {
+            AuthError::OidcAccountExists
+        } else {
+            AuthError::from(e)
+        }
+    })?;
+
+    // The profile is filled in the background so a slow avatar download does not delay sign-in
+    let (pool_clone, s3_clone) = (pool.clone(), s3_client.clone());
+    let r2 = r2.clone();
+    let (user_id, name, picture) = (user.id, claims.name.clone(), claims.picture.clone());
+    tokio::spawn(async move {
+        bootstrap_profile(
+            &pool_clone,
+            &s3_clone,
+            &r2,
+            user_id,
+            name.as_deref(),
+            picture.as_deref(),
+        )
+        .await;
+    });
+
+    let outcome = auth_service::finish_login(pool, user, device, jwt).await?;
+    Ok(ProviderLogin {
+        outcome,
+        user_id,
+        new_account: true,
+    })
+}
+
+/// Links a provider to the signed-in user's account
+pub async fn link_identity(
+    pool: &PgPool,
+    client: &OidcClient,
+    config: &OidcProviderConfig,
+    user_id: Uuid,
+    code: &str,
+    state: &str,
+) -> Result<UserIdentityModel, AuthError> {
+    let (request, claims) = complete_authorization(pool, client, config, code, state).await?;
+    if request.link_user_id != Some(user_id) {
+        return Err(AuthError::InvalidOrExpiredToken);
+    }
+
+    if let Some(identity) =
+        identity_repository::find_identity(pool, &config.name, &claims.sub).await?
+    {
+        return if identity.user_id == user_id {
+            Ok(identity)
+        } else {
+            Err(AuthError::IdentityAlreadyLinked)
+        };
+    }
+
+    identity_repository::create_identity(
+        pool,
+        user_id,
+        &config.name,
+        &claims.sub,
+        claims.verified_email(),
+    )
+    .await?
+    .ok_or(AuthError::IdentityAlreadyLinked)
+}
+
+pub async fn list_identities(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<UserIdentityModel>, AuthError> {
+    Ok(identity_repository::find_user_identities(pool, user_id).await?)
+}
+
+/// Unlinks a provider, unless it is the only way left to sign in
+pub async fn unlink_identity(
+    pool: &PgPool,
+    user_id: Uuid,
+    provider: &str,
+) -> Result<(), AuthError> {
+    let user = user_repository::find_user_by_id(pool, user_id)
+        .await?
+        .ok_or(AuthError::InvalidCredentials)?;
+    let identities = identity_repository::find_user_identities(pool, user_id).await?;
+
+    if !identities
+        .iter()
+        .any(|identity| identity.provider == provider)
+    {
+        return Err(AuthError::IdentityNotLinked);
+    }
+    if user.password_hash.is_none()
+        && identities.len() == 1
+        && webauthn_repository::count_user_credentials(pool, user_id).await? == 0
+    {
+        return Err(AuthError::LastSignInMethod);
+    }
+
+    identity_repository::delete_identity(pool, user_id, provider).await?;
+    Ok(())
+}
+
+/// Fills the profile of an account created through a provider with its name and picture.
+/// Failures are only logged; the user can always edit the profile themselves.
+async fn bootstrap_profile(
+    pool: &PgPool,
+    s3_client: &S3Client,
+    r2: &R2Config,
+    user_id: Uuid,
+    name: Option<&str>,
+    picture: Option<&str>,
+) {
+    let full_name = name
+        .map(str::trim)
+        .filter(|name| validate_full_name(name).is_ok());
+    if let Err(e) = profile_repository::ensure_profile_exists(pool, user_id).await {
+        tracing::error!(user_id = %user_id, "Failed to create profile: {}", e);
+        return;
+    }
+    if let Some(full_name) = full_name
+        && let Err(e) =
+            profile_repository::update_profile(pool, user_id, Some(full_name), None, None).await
+    {
+        tracing::error!(user_id = %user_id, "Failed to set profile name: {}", e);
+    }
+
+    let Some(picture) = picture else {
+        return;
+    };
+    match import_avatar(s3_client, r2, user_id, picture).await {
+        Ok(avatar_url) => {
+            if let Err(e) = profile_repository::update_avatar_url(pool, user_id, &avatar_url).await
+            {
+                tracing::error!(user_id = %user_id, "Failed to set imported avatar: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!(user_id = %user_id, "Failed to import avatar: {}", e),
+    }
+}
+
+/// Downloads the provider's profile picture and stores it like an uploaded avatar
+async fn import_avatar(
+    s3_client: &S3Client,
+    r2: &R2Config,
+    user_id: Uuid,
+    picture_url: &str,
+) -> Result<String, String> {
+    // The URL comes from the provider's claims, so it must not reach internal services:
+    // connect only to the checked public addresses and do not follow redirects
+    let url = Url::parse(picture_url).map_err(|e| e.to_string())?;
+    let (host, addrs) = resolve_public_host(&url).await?;
+    let http = Client::builder()
+        .timeout(StdDuration::from_secs(OIDC_HTTP_TIMEOUT_SECONDS))
+        .redirect(Policy::none())
+        .resolve_to_addrs(&host, &addrs)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut response = http
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?;
+    if response.status().is_redirection() {
+        return Err("picture URL redirects".to_string());
+    }
+
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_lowercase())
+        .unwrap_or_default();
+    if !ALLOWED_CONTENT_TYPES.contains(&content_type.as_str()) {
+        return Err(format!("unsupported picture type '{}'", content_type));
+    }
+
+    // Read in chunks so an oversized picture is rejected without buffering all of it
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() > MAX_AVATAR_SIZE {
+            return Err("picture is too large".to_string());
+        }
+    }
+
+    profile_service::process_and_upload_avatar(s3_client, r2, user_id, bytes, &content_type)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+    use jsonwebtoken::{EncodingKey, Header, encode};
+    use serde_json::json;
+
+    const SECRET: &[u8] = b"mock-issuer-signing-secret-of-32-bytes";
+    const ISSUER: &str = "http://localhost:8080/realms/test";
+
+    /// Key set as served by a mock issuer that signs with a shared secret
+    fn mock_jwks() -> JwkSet {
+        serde_json::from_value(json!({
+            "keys": [{ "kty": "oct", "kid": "mock", "alg": "HS256", "k": URL_SAFE_NO_PAD.encode(SECRET) }]
+        }))
+        .unwrap()
+    }
+
+    fn id_token(claims: serde_json::Value) -> String {
+        let mut header = Header::new(jsonwebtoken::Algorithm::HS256);
+        header.kid = Some("mock".to_string());
+        encode(&header, &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+    }
+
+    fn claims(nonce: &str) -> serde_json::Value {
+        json!({
+            "iss": ISSUER,
+            "aud": "web_be",
+            "sub": "subject-1",
+            "exp": Utc::now().timestamp() + 300,
+            "nonce": nonce,
+            "email": "user@example.com",
+            "email_verified": "true",
+        })
+    }
+
+    #[test]
+    fn test_verify_id_token_accepts_valid_token() {
+        let token = id_token(claims("nonce-1"));
+        let claims = verify_id_token(&token, &mock_jwks(), ISSUER, "web_be", "nonce-1").unwrap();
+
+        assert_eq!(claims.sub, "subject-1");
+        assert_eq!(claims.verified_email(), Some("user@example.com"));
+    }
+
+    #[test]
+    fn test_verify_id_token_rejects_wrong_nonce_and_audience() {
+        let token = id_token(claims("nonce-1"));
+
+        assert!(verify_id_token(&token, &mock_jwks(), ISSUER, "web_be", "nonce-2").is_err());
+        assert!(verify_id_token(&token, &mock_jwks(), ISSUER, "other-client", "nonce-1").is_err());
+        assert!(verify_id_token(&token, &mock_jwks(), "http://evil", "web_be", "nonce-1").is_err());
+    }
+
+    #[test]
+    fn test_verify_id_token_accepts_issuer_with_trailing_slash() {
+        let issuer = "https://tenant.auth0.com/";
+        let mut claims = claims("nonce-1");
+        claims["iss"] = json!(issuer);
+        let token = id_token(claims);
+
+        assert!(verify_id_token(&token, &mock_jwks(), issuer, "web_be", "nonce-1").is_ok());
+        assert!(
+            verify_id_token(
+                &token,
+                &mock_jwks(),
+                issuer.trim_end_matches('/'),
+                "web_be",
+                "nonce-1"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_unverified_email_is_ignored() {
+        let mut claims = claims("nonce-1");
+        claims["email_verified"] = json!(false);
+        let token = id_token(claims);
+
+        let claims = verify_id_token(&token, &mock_jwks(), ISSUER, "web_be", "nonce-1").unwrap();
+        assert_eq!(claims.verified_email(), None);
+    }
+}
@@ -0,0 +1,265 @@
+use crate::{
+    config::{JwtConfig, WebauthnConfig},
+    constant::auth::{MAX_PASSKEYS_PER_USER, WEBAUTHN_CHALLENGE_DURATION_MINUTES},
+    error::AuthError,
+    models::{
+        user::UserModel,
+        webauthn::{ChallengePurpose, WebauthnCredentialModel},
+    },
+    repository::{identity_repository, user_repository, webauthn_repository},
+    services::auth::{account_status_service::ensure_user_active, auth_service},
+    utils::{
+        device::DeviceInfo,
+        token::{generate_opaque_token, hash_token},
+        webauthn::{self, CEREMONY_CREATE, CEREMONY_GET},
+    },
+};
+use base64::{Engine as _, engine::general_purpose};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// What the browser needs to create a passkey for the user
+pub struct RegistrationOptions {
+    pub challenge: String,
+    pub user: UserModel,
+    /// Already registered passkeys, so the authenticator does not create a second one
+    pub existing: Vec<WebauthnCredentialModel>,
+}
+
+/// A `PublicKeyCredential` returned by `navigator.credentials.get()`, fields base64url encoded
+pub struct PasskeyAssertion<'a> {
+    pub credential_id: &'a str,
+    pub client_data_json: &'a str,
+    pub authenticator_data: &'a str,
+    pub signature: &'a str,
+    pub user_handle: Option<&'a str>,
+}
+
+/// The WebAuthn user handle: the account ID, never anything personally identifying
+pub fn user_handle(user_id: Uuid) -> String {
+    general_purpose::URL_SAFE_NO_PAD.encode(user_id.as_bytes())
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, String> {
+    // Some clients pad their base64url output
+    general_purpose::URL_SAFE_NO_PAD
+        .decode(value.trim().trim_end_matches('='))
+        .map_err(|e| format!("invalid base64url: {}", e))
+}
+
+/// The reason is only logged; clients learn nothing about why a passkey was not accepted
+fn assertion_rejected(reason: String) -> AuthError {
+    tracing::info!("Passkey assertion rejected: {}", reason);
+    AuthError::InvalidPasskey
+}
+
+/// Stores a new single-use challenge and returns it base64url encoded
+async fn create_challenge(
+    pool: &PgPool,
+    purpose: ChallengePurpose,
+    user_id: Option<Uuid>,
+) -> Result<String, AuthError> {
+    let challenge = generate_opaque_token();
+    let expires_at = Utc::now() + Duration::minutes(WEBAUTHN_CHALLENGE_DURATION_MINUTES);
+    webauthn_repository::create_challenge(
+        pool,
+        &hash_token(&challenge),
+        purpose,
+        user_id,
+        expires_at,
+    )
+    .await?;
+    Ok(challenge)
+}
+
+pub async fn start_registration(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<RegistrationOptions, AuthError> {
+    let user = user_repository::find_user_by_id(pool, user_id)
+        .await?
+        .ok_or(AuthError::InvalidCredentials)?;
+
+    let existing = webauthn_repository::find_user_credentials(pool, user_id).await?;
+    if existing.len() as i64 >= MAX_PASSKEYS_PER_USER {
+        return Err(AuthError::ValidationError(format!(
+            "You can register at most {} passkeys",
+            MAX_PASSKEYS_PER_USER
+        )));
+    }
+
+    let challenge = create_challenge(pool, ChallengePurpose::Registration, Some(user_id)).await?;
+    Ok(RegistrationOptions {
+        challenge,
+        user,
+        existing,
+    })
+}
+
+/// Verifies the authenticator's response to a registration challenge and stores the passkey
+pub async fn finish_registration(
+    pool: &PgPool,
+    webauthn_config: &WebauthnConfig,
+    user_id: Uuid,
+    client_data_json: &str,
+    attestation_object: &str,
+    transports: &[String],
+    name: &str,
+) -> Result<WebauthnCredentialModel, AuthError> {
+    let rejected = |e: String| {
+        tracing::info!(user_id = %user_id, "Passkey registration rejected: {}", e);
+        AuthError::ValidationError("Passkey registration failed".to_string())
+    };
+
+    let client_data_json = decode(client_data_json).map_err(rejected)?;
+    let challenge =
+        webauthn::verify_client_data(&client_data_json, CEREMONY_CREATE, &webauthn_config.origins)
+            .map_err(rejected)?;
+
+    // The challenge must have been issued to this user
+    webauthn_repository::take_challenge(
+        pool,
+        &hash_token(&challenge),
+        ChallengePurpose::Registration,
+    )
+    .await?
+    .filter(|record| record.user_id == Some(user_id))
+    .ok_or(AuthError::InvalidOrExpiredToken)?;
+
+    let attestation_object = decode(attestation_object).map_err(rejected)?;
+    let credential = webauthn::verify_registration(&attestation_object, &webauthn_config.rp_id)
+        .map_err(rejected)?;
+
+    if webauthn_repository::count_user_credentials(pool, user_id).await? >= MAX_PASSKEYS_PER_USER {
+        return Err(AuthError::ValidationError(format!(
+            "You can register at most {} passkeys",
+            MAX_PASSKEYS_PER_USER
+        )));
+    }
+
+    webauthn_repository::create_credential(
+        pool,
+        user_id,
+        &credential.credential_id,
+        &credential.public_key,
+        credential.algorithm as i32,
+        credential.sign_count as i64,
+        transports,
+        name,
+    )
+    .await?
+    .ok_or_else(|| AuthError::ValidationError("This passkey is already registered".to_string()))
+}
+
+/// Starts a passwordless sign-in. No account is named: the browser offers the user's
+/// discoverable passkeys, so the response does not reveal which accounts exist.
+pub async fn start_authentication(pool: &PgPool) -> Result<String, AuthError> {
+    create_challenge(pool, ChallengePurpose::Authentication, None).await
+}
+
+/// Verifies a passkey assertion and signs the user in.
+/// A user-verifying passkey is already two factors, so no TOTP code is asked for.
+pub async fn finish_authentication(
+    pool: &PgPool,
+    webauthn_config: &WebauthnConfig,
+    assertion: &PasskeyAssertion<'_>,
+    device: &DeviceInfo,
+    jwt: &JwtConfig,
+) -> Result<(String, String, UserModel), AuthError> {
+    let client_data_json = decode(assertion.client_data_json).map_err(assertion_rejected)?;
+    let challenge =
+        webauthn::verify_client_data(&client_data_json, CEREMONY_GET, &webauthn_config.origins)
+            .map_err(assertion_rejected)?;
+
+    webauthn_repository::take_challenge(
+        pool,
+        &hash_token(&challenge),
+        ChallengePurpose::Authentication,
+    )
+    .await?
+    .ok_or(AuthError::InvalidPasskey)?;
+
+    let credential_id = decode(assertion.credential_id).map_err(assertion_rejected)?;
+    let credential = webauthn_repository::find_credential(pool, &credential_id)
+        .await?
+        .ok_or(AuthError::InvalidPasskey)?;
+
+    if let Some(user_handle) = assertion.user_handle
+        && decode(user_handle).map_err(assertion_rejected)? != credential.user_id.as_bytes()
+    {
+        return Err(AuthError::InvalidPasskey);
+    }
+
+    let authenticator_data = decode(assertion.authenticator_data).map_err(assertion_rejected)?;
+    let signature = decode(assertion.signature).map_err(assertion_rejected)?;
+    let sign_count = webauthn::verify_assertion(
+        &credential.public_key,
+        &authenticator_data,
+        &client_data_json,
+        &signature,
+        &webauthn_config.rp_id,
+    )
+    .map_err(assertion_rejected)?;
+
+    if !webauthn_repository::record_use(pool, credential.id, sign_count as i64).await? {
+        tracing::warn!(
+            user_id = %credential.user_id,
+            credential = %credential.id,
+            stored = credential.sign_count,
+            presented = sign_count,
+            "Security event: passkey signature counter did not increase, possible cloned authenticator"
+        );
+        return Err(AuthError::InvalidPasskey);
+    }
+
+    let user = user_repository::find_user_by_id(pool, credential.user_id)
+        .await?
+        .ok_or(AuthError::InvalidPasskey)?;
+    ensure_user_active(&user)?;
+
+    // Every login starts a new token family
+    let (access_token, refresh_token) =
+        auth_service::issue_session_tokens(pool, &user, Uuid::new_v4(), device, jwt).await?;
+
+    Ok((access_token, refresh_token, user))
+}
+
+pub async fn list_passkeys(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<WebauthnCredentialModel>, AuthError> {
+    Ok(webauthn_repository::find_user_credentials(pool, user_id).await?)
+}
+
+pub async fn rename_passkey(
+    pool: &PgPool,
+    user_id: Uuid,
+    passkey_id: Uuid,
+    name: &str,
+) -> Result<WebauthnCredentialModel, AuthError> {
+    webauthn_repository::rename_credential(pool, user_id, passkey_id, name)
+        .await?
+        .ok_or(AuthError::PasskeyNotFound)
+}
+
+pub async fn remove_passkey(
+    pool: &PgPool,
+    user_id: Uuid,
+    passkey_id: Uuid,
+) -> Result<(), AuthError> {
+    let user = user_repository::find_user_by_id(pool, user_id)
+        .await?
+        .ok_or(AuthError::InvalidCredentials)?;
+    let passkeys = webauthn_repository::count_user_credentials(pool, user_id).await?;
+    let identities = identity_repository::find_user_identities(pool, user_id).await?;
+
+    if user.password_hash.is_none() && identities.is_empty() && passkeys <= 1 {
+        return Err(AuthError::LastSignInMethod);
+    }
+
+    if !webauthn_repository::delete_credential(pool, user_id, passkey_id).await? {
+        return Err(AuthError::PasskeyNotFound);
+    }
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use crate::{
+    config::PasswordHashConfig,
+    constant::auth::PASSWORD_RESET_TOKEN_DURATION_MINUTES,
+    error::AuthError,
+    repository::{password_reset_repository, token_repository, user_repository},
+    services::{
+        auth::{
+            account_status_service::UserStatusCache,
+            auth_service::{hash_password, verify_current_password},
+            lockout_service::{self, ThrottleTarget},
+        },
+        mail::{mail_service, sender::MailSender},
+    },
+    utils::{
+        breached_password,
+        token::{generate_opaque_token, hash_token},
+    },
+};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use std::path::Path;
+use uuid::Uuid;
+
+/// Rejects a new password that appears in the configured breach corpus.
+/// An unreadable corpus is logged rather than blocking every password change.
+pub(crate) async fn ensure_not_breached(
+    breached_passwords_dir: Option<&Path>,
+    password: &str,
+) -> Result<(), AuthError> {
+    let Some(dir) = breached_passwords_dir else {
+        return Ok(());
+    };
+
+    match breached_password::is_breached(dir, password).await {
+        Ok(false) => Ok(()),
+        Ok(true) => Err(AuthError::ValidationError(
+            "This password has appeared in a data breach. Please choose a different password"
+                .to_string(),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to read breached password ranges: {:?}", e);
+            Ok(())
+        }
+    }
+}
+
+/// Emails a password reset link if an account exists for the address.
+/// Always succeeds for unknown addresses so callers cannot probe for accounts.
+pub async fn request_password_reset(
+    pool: &PgPool,
+    mailer: &dyn MailSender,
+    app_base_url: &str,
+    email: &str,
+) -> Result<(), AuthError> {
+    let Some(user) = user_repository::find_user_by_email(pool, email).await? else {
+        return Ok(());
+    };
+
+    // Only the most recently requested link stays valid
+    password_reset_repository::invalidate_user_tokens(pool, user.id).await?;
+
+    let token = generate_opaque_token();
+    let expires_at = Utc::now() + Duration::minutes(PASSWORD_RESET_TOKEN_DURATION_MINUTES);
+    password_reset_repository::create_token(pool, user.id, &hash_token(&token), expires_at).await?;
+
+    mail_service::send_password_reset_email(mailer, app_base_url, &user.email, &token)
+        .await
+        .map_err(|e| AuthError::MailDeliveryError(e.to_string()))
+}
+
+/// Consumes a reset token, sets the new password and signs the user out everywhere
+pub async fn reset_password(
+    pool: &PgPool,
+    status_cache: &UserStatusCache,
+    token: &str,
+    new_password: &str,
+    password_config: &PasswordHashConfig,
+    breached_passwords_dir: Option<&Path>,
+) -> Result<Uuid, AuthError> {
+    // Checked first so a rejected password does not use up the link
+    ensure_not_breached(breached_passwords_dir, new_password).await?;
+
+    let record = password_reset_repository::consume_token(pool, &hash_token(token))
+        .await?
+        .ok_or(AuthError::InvalidOrExpiredToken)?;
+
+    let hashed_password = hash_password(new_password, password_config).await?;
+    user_repository::update_password_hash(pool, record.user_id, &hashed_password).await?;
+
+    // Following the emailed link proves ownership of the address
+    user_repository::mark_email_verified(pool, record.user_id).await?;
+
+    // Sessions opened with the old password must not survive the reset
+    token_repository::revoke_all_user_tokens(pool, record.user_id).await?;
+    status_cache
+        .revoke_access_tokens(pool, record.user_id)
+        .await?;
+
+    // The owner regained access, so lift any lockout caused by guessing the old password
+    lockout_service::reset(pool, ThrottleTarget::User(record.user_id)).await?;
+
+    Ok(record.user_id)
+}
+
+/// Changes the password of a logged-in user after re-checking the current one.
+/// Every other session is signed out; the session holding `current_refresh_token` stays valid
+/// and is handed a new access token by the caller (see `auth_service::reissue_access_token`).
+#[allow(clippy::too_many_arguments)]
+pub async fn change_password(
+    pool: &PgPool,
+    status_cache: &UserStatusCache,
+    user_id: Uuid,
+    current_password: &str,
+    new_password: &str,
+    current_refresh_token: Option<&str>,
+    password_config: &PasswordHashConfig,
+    breached_passwords_dir: Option<&Path>,
+) -> Result<(), AuthError> {
+    let user = user_repository::find_user_by_id(pool, user_id)
+        .await?
+        .ok_or(AuthError::InvalidCredentials)?;
+
+    verify_current_password(
+        current_password,
+        user.password_hash.as_deref(),
+        password_config,
+    )
+    .await?;
+
+    if current_password == new_password {
+        return Err(AuthError::ValidationError(
+            "New password must be different from the current password".to_string(),
+        ));
+    }
+    ensure_not_breached(breached_passwords_dir, new_password).await?;
+
+    let hashed_password = hash_password(new_password, password_config).await?;
+    user_repository::update_password_hash(pool, user.id, &hashed_password).await?;
+
+    match current_refresh_token {
+        Some(token) => {
+            token_repository::revoke_other_user_tokens(pool, user.id, &hash_token(token)).await?
+        }
+        None => token_repository::revoke_all_user_tokens(pool, user.id).await?,
+    };
+    status_cache.revoke_access_tokens(pool, user.id).await?;
+
+    Ok(())
+}
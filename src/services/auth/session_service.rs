@@ -0,0 +1,76 @@
+use crate::{
+    error::AppError, models::token::SessionModel, repository::token_repository,
+    services::auth::account_status_service::UserStatusCache, utils::token::hash_token,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Resolves the session (token family) that a refresh token belongs to, if it is the user's
+pub async fn current_session_id(
+    pool: &PgPool,
+    user_id: Uuid,
+    refresh_token: Option<&str>,
+) -> Result<Option<Uuid>, AppError> {
+    let Some(refresh_token) = refresh_token else {
+        return Ok(None);
+    };
+
+    let record = token_repository::find_token_by_hash(pool, &hash_token(refresh_token))
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string().into()))?;
+
+    Ok(record
+        .filter(|token| token.user_id == user_id)
+        .map(|token| token.family_id))
+}
+
+pub async fn list_sessions(pool: &PgPool, user_id: Uuid) -> Result<Vec<SessionModel>, AppError> {
+    token_repository::find_active_sessions(pool, user_id)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string().into()))
+}
+
+/// Signs out one session. Access tokens cannot be told apart by session, so every other
+/// session has to refresh its access token afterwards; the caller's own gets a new one.
+pub async fn revoke_session(
+    pool: &PgPool,
+    status_cache: &UserStatusCache,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> Result<(), AppError> {
+    let count = token_repository::revoke_user_family(pool, user_id, session_id)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string().into()))?;
+
+    if count == 0 {
+        return Err(AppError::BadRequest("Session not found".into()));
+    }
+
+    status_cache
+        .revoke_access_tokens(pool, user_id)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string().into()))
+}
+
+/// Signs out every session except the one holding `current_refresh_token`.
+/// Without a current refresh token every session is signed out.
+pub async fn revoke_other_sessions(
+    pool: &PgPool,
+    status_cache: &UserStatusCache,
+    user_id: Uuid,
+    current_refresh_token: Option<&str>,
+) -> Result<u64, AppError> {
+    let revoked = match current_refresh_token {
+        Some(token) => {
+            token_repository::revoke_other_user_tokens(pool, user_id, &hash_token(token)).await
+        }
+        None => token_repository::revoke_all_user_tokens(pool, user_id).await,
+    }
+    .map_err(|e| AppError::InternalError(e.to_string().into()))?;
+
+    status_cache
+        .revoke_access_tokens(pool, user_id)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string().into()))?;
+    Ok(revoked)
+}
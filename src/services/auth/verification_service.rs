@@ -0,0 +1,55 @@
+use crate::{
+    constant::auth::EMAIL_VERIFICATION_TOKEN_DURATION_HOURS,
+    error::AuthError,
+    models::user::UserModel,
+    repository::{user_repository, verification_repository},
+    services::mail::{mail_service, sender::MailSender},
+    utils::token::{generate_opaque_token, hash_token},
+};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+
+/// Issues a fresh verification token for the user and emails the link.
+/// Any previously issued tokens are invalidated so only the newest link works.
+pub async fn send_verification(
+    pool: &PgPool,
+    mailer: &dyn MailSender,
+    app_base_url: &str,
+    user: &UserModel,
+) -> Result<(), AuthError> {
+    verification_repository::invalidate_user_tokens(pool, user.id).await?;
+
+    let token = generate_opaque_token();
+    let expires_at = Utc::now() + Duration::hours(EMAIL_VERIFICATION_TOKEN_DURATION_HOURS);
+    verification_repository::create_token(pool, user.id, &hash_token(&token), expires_at).await?;
+
+    mail_service::send_verification_email(mailer, app_base_url, &user.email, &token)
+        .await
+        .map_err(|e| AuthError::MailDeliveryError(e.to_string()))
+}
+
+/// Consumes a verification token and marks the owning account as verified
+pub async fn verify_email(pool: &PgPool, token: &str) -> Result<(), AuthError> {
+    let record = verification_repository::consume_token(pool, &hash_token(token))
+        .await?
+        .ok_or(AuthError::InvalidOrExpiredToken)?;
+
+    user_repository::mark_email_verified(pool, record.user_id).await?;
+    Ok(())
+}
+
+/// Re-sends the verification email if the account exists and is still unverified.
+/// Always succeeds for unknown or already verified addresses so callers cannot probe for accounts.
+pub async fn resend_verification(
+    pool: &PgPool,
+    mailer: &dyn MailSender,
+    app_base_url: &str,
+    email: &str,
+) -> Result<(), AuthError> {
+    let user = match user_repository::find_user_by_email(pool, email).await? {
+        Some(user) if user.email_verified_at.is_none() => user,
+        _ => return Ok(()),
+    };
+
+    send_verification(pool, mailer, app_base_url, &user).await
+}
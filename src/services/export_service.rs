@@ -0,0 +1,311 @@
+use crate::{
+    constant::export::{EXPORT_DOWNLOAD_URL_EXPIRY_SECONDS, EXPORT_PREFIX, EXPORT_RETENTION_DAYS},
+    error::AppError,
+    models::{
+        data_export::{DataExportModel, EXPORT_STATUS_COMPLETED},
+        friend::FriendshipModel,
+        identity::UserIdentityModel,
+        profile::ProfileModel,
+        token::SessionModel,
+    },
+    repository::{
+        export_repository, friend_repository, identity_repository, profile_repository,
+        token_repository, user_repository,
+    },
+    services::{auth::mfa_service, profile_service},
+};
+use aws_sdk_s3::{Client as S3Client, presigning::PresigningConfig, primitives::ByteStream};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use std::time::Duration as StdDuration;
+use uuid::Uuid;
+
+/// Exports still pending after this long are assumed to have been interrupted
+/// (e.g. by a restart) and no longer block a new export
+const EXPORT_TIMEOUT_MINUTES: i64 = 30;
+
+/// Failed or abandoned exports are deleted once they are this old
+const STALE_EXPORT_HOURS: i64 = 24;
+
+/// The account record, without credentials
+#[derive(Serialize)]
+struct AccountExport {
+    id: Uuid,
+    email: String,
+    role: String,
+    is_active: bool,
+    email_verified_at: Option<DateTime<Utc>>,
+    mfa_enabled: bool,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+struct AvatarExport {
+    key: String,
+    content_type: Option<String>,
+    /// File contents, base64 encoded
+    data: String,
+}
+
+/// Everything stored about a user, as written to the archive
+#[derive(Serialize)]
+struct ExportArchive {
+    generated_at: DateTime<Utc>,
+    account: AccountExport,
+    profile: Option<ProfileModel>,
+    friendships: Vec<FriendshipModel>,
+    sessions: Vec<SessionModel>,
+    identities: Vec<UserIdentityModel>,
+    avatars: Vec<AvatarExport>,
+}
+
+fn internal(e: impl std::fmt::Display) -> AppError {
+    AppError::InternalError(e.to_string().into())
+}
+
+fn export_key(user_id: Uuid, export_id: Uuid) -> String {
+    format!("{}/{}/{}.json", EXPORT_PREFIX, user_id, export_id)
+}
+
+/// Starts an export in the background, or returns the one already in progress
+pub async fn request_export(
+    pool: &PgPool,
+    s3_client: &S3Client,
+    bucket: &str,
+    user_id: Uuid,
+) -> Result<DataExportModel, AppError> {
+    let started_before = Utc::now() - Duration::minutes(EXPORT_TIMEOUT_MINUTES);
+    export_repository::fail_interrupted_exports(pool, user_id, started_before)
+        .await
+        .map_err(internal)?;
+
+    let Some(export) = export_repository::create_export(pool, user_id)
+        .await
+        .map_err(internal)?
+    else {
+        return export_repository::find_pending_export(pool, user_id)
+            .await
+            .map_err(internal)?
+            .ok_or(AppError::InternalError("Failed to start export".into()));
+    };
+
+    let (pool, s3_client, bucket) = (pool.clone(), s3_client.clone(), bucket.to_string());
+    let (export_id, owner_id) = (export.id, export.user_id);
+    tokio::spawn(async move {
+        run_export(&pool, &s3_client, &bucket, export_id, owner_id).await;
+    });
+
+    Ok(export)
+}
+
+/// Builds the archive, uploads it and records the outcome
+async fn run_export(
+    pool: &PgPool,
+    s3_client: &S3Client,
+    bucket: &str,
+    export_id: Uuid,
+    user_id: Uuid,
+) {
+    let result = async {
+        let archive = build_archive(pool, s3_client, bucket, user_id).await?;
+        let body = serde_json::to_vec_pretty(&archive).map_err(internal)?;
+
+        let key = export_key(user_id, export_id);
+        s3_client
+            .put_object()
+            .bucket(bucket)
+            .key(&key)
+            .body(ByteStream::from(body))
+            .content_type("application/json")
+            .content_disposition("attachment; filename=\"data-export.json\"")
+            .send()
+            .await
+            .map_err(|e| internal(format!("Failed to upload export: {}", e)))?;
+
+        let expires_at = Utc::now() + Duration::days(EXPORT_RETENTION_DAYS);
+        export_repository::mark_completed(pool, export_id, &key, expires_at)
+            .await
+            .map_err(internal)
+    }
+    .await;
+
+    if let Err(e) = result {
+        tracing::error!(export_id = %export_id, user_id = %user_id, "Data export failed: {}", e);
+        if let Err(e) = export_repository::mark_failed(pool, export_id, "Export failed").await {
+            tracing::error!(export_id = %export_id, "Failed to record export failure: {}", e);
+        }
+    }
+}
+
+async fn build_archive(
+    pool: &PgPool,
+    s3_client: &S3Client,
+    bucket: &str,
+    user_id: Uuid,
+) -> Result<ExportArchive, AppError> {
+    let user = user_repository::find_user_by_id(pool, user_id)
+        .await
+        .map_err(internal)?
+        .ok_or(AppError::BadRequest("User not found".into()))?;
+    let mfa_enabled = mfa_service::is_totp_enabled(pool, user_id)
+        .await
+        .map_err(internal)?;
+
+    let profile = profile_repository::find_by_user_id(pool, user_id)
+        .await
+        .map_err(internal)?;
+    let friendships = friend_repository::find_all_for_user(pool, user_id)
+        .await
+        .map_err(internal)?;
+    let sessions = token_repository::find_session_history(pool, user_id)
+        .await
+        .map_err(internal)?;
+    let identities = identity_repository::find_user_identities(pool, user_id)
+        .await
+        .map_err(internal)?;
+
+    let mut avatars = Vec::new();
+    for key in profile_service::list_user_avatar_keys(s3_client, bucket, user_id).await? {
+        let object = s3_client
+            .get_object()
+            .bucket(bucket)
+            .key(&key)
+            .send()
+            .await
+            .map_err(|e| internal(format!("Failed to download avatar: {}", e)))?;
+        let content_type = object.content_type().map(str::to_string);
+        let bytes = object
+            .body
+            .collect()
+            .await
+            .map_err(|e| internal(format!("Failed to read avatar: {}", e)))?
+            .into_bytes();
+
+        avatars.push(AvatarExport {
+            key,
+            content_type,
+            data: STANDARD.encode(bytes),
+        });
+    }
+
+    Ok(ExportArchive {
+        generated_at: Utc::now(),
+        account: AccountExport {
+            id: user.id,
+            email: user.email,
+            role: user.role,
+            is_active: user.is_active,
+            email_verified_at: user.email_verified_at,
+            mfa_enabled,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        },
+        profile,
+        friendships,
+        sessions,
+        identities,
+        avatars,
+    })
+}
+
+/// Returns an export of the user, with a time-limited download URL once it has completed
+pub async fn get_export(
+    pool: &PgPool,
+    s3_client: &S3Client,
+    bucket: &str,
+    user_id: Uuid,
+    export_id: Uuid,
+) -> Result<(DataExportModel, Option<String>), AppError> {
+    let export = export_repository::find_export(pool, user_id, export_id)
+        .await
+        .map_err(internal)?
+        .ok_or(AppError::BadRequest("Export not found".into()))?;
+
+    let downloadable = export.status == EXPORT_STATUS_COMPLETED
+        && export
+            .expires_at
+            .is_some_and(|expires_at| expires_at > Utc::now());
+    let Some(key) = export.object_key.as_deref().filter(|_| downloadable) else {
+        return Ok((export, None));
+    };
+
+    let presigning =
+        PresigningConfig::expires_in(StdDuration::from_secs(EXPORT_DOWNLOAD_URL_EXPIRY_SECONDS))
+            .map_err(internal)?;
+    let request = s3_client
+        .get_object()
+        .bucket(bucket)
+        .key(key)
+        .presigned(presigning)
+        .await
+        .map_err(|e| internal(format!("Failed to sign download URL: {}", e)))?;
+    let url = request.uri().to_string();
+
+    Ok((export, Some(url)))
+}
+
+/// Deletes expired archives from R2 along with their records, and clears out
+/// failed or interrupted exports. Returns the number of deleted exports.
+pub async fn delete_expired_exports(
+    pool: &PgPool,
+    s3_client: &S3Client,
+    bucket: &str,
+) -> Result<usize, AppError> {
+    let stale_before = Utc::now() - Duration::hours(STALE_EXPORT_HOURS);
+    let exports = export_repository::find_expired_exports(pool, stale_before)
+        .await
+        .map_err(internal)?;
+
+    let mut deleted = 0;
+    for export in exports {
+        if let Some(key) = &export.object_key
+            && let Err(e) = s3_client
+                .delete_object()
+                .bucket(bucket)
+                .key(key)
+                .send()
+                .await
+        {
+            // Keep the record so the object is retried on the next run
+            tracing::error!(export_id = %export.id, "Failed to delete export archive: {}", e);
+            continue;
+        }
+
+        export_repository::delete_export(pool, export.id)
+            .await
+            .map_err(internal)?;
+        deleted += 1;
+    }
+
+    Ok(deleted)
+}
+
+/// Deletes all export archives of a user from R2, e.g. before the account is purged
+pub async fn delete_user_exports(
+    pool: &PgPool,
+    s3_client: &S3Client,
+    bucket: &str,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    let exports = export_repository::find_user_exports(pool, user_id)
+        .await
+        .map_err(internal)?;
+
+    for key in exports
+        .iter()
+        .filter_map(|export| export.object_key.as_deref())
+    {
+        s3_client
+            .delete_object()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| internal(format!("Failed to delete export archive: {}", e)))?;
+    }
+
+    Ok(())
+}
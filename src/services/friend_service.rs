@@ -1,4 +1,8 @@
-use crate::{error::AppError, models::friend::FriendshipModel, repository::friend_repository};
+use crate::{
+    error::AppError,
+    models::friend::FriendshipModel,
+    repository::{friend_repository, user_repository},
+};
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -11,6 +15,14 @@ pub async fn request_friend(
         return Err(AppError::BadRequest("Cannot add yourself".into()));
     }
 
+    // Deleted accounts are hidden from friend lists, so they cannot be befriended either
+    let target = user_repository::find_user_by_id(pool, target_id)
+        .await
+        .map_err(|e| AppError::InternalError(e.to_string().into()))?;
+    if target.is_none_or(|user| user.is_deleted) {
+        return Err(AppError::BadRequest("User not found".into()));
+    }
+
     // Check existing
     let existing = friend_repository::find_friendship(pool, user_id, target_id)
         .await
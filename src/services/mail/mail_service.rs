@@ -0,0 +1,93 @@
+use crate::{
+    constant::auth::{
+        ACCOUNT_DELETION_TOKEN_DURATION_MINUTES, EMAIL_VERIFICATION_TOKEN_DURATION_HOURS,
+        MAGIC_LINK_TOKEN_DURATION_MINUTES, PASSWORD_RESET_TOKEN_DURATION_MINUTES,
+    },
+    services::mail::sender::{EmailMessage, MailError, MailSender},
+};
+
+/// Sends the "verify your email" message containing a single-use link
+pub async fn send_verification_email(
+    mailer: &dyn MailSender,
+    app_base_url: &str,
+    to: &str,
+    token: &str,
+) -> Result<(), MailError> {
+    let link = format!("{}/verify-email?token={}", app_base_url, token);
+    let message = EmailMessage {
+        to: to.to_string(),
+        subject: "Verify your email address".to_string(),
+        body: format!(
+            "Welcome!\n\nPlease confirm your email address by opening the link below:\n\n{}\n\nThis link expires in {} hours. If you did not create an account, you can ignore this email.",
+            link, EMAIL_VERIFICATION_TOKEN_DURATION_HOURS
+        ),
+    };
+
+    mailer.send(&message).await
+}
+
+/// Sends the password reset message containing a single-use link
+pub async fn send_password_reset_email(
+    mailer: &dyn MailSender,
+    app_base_url: &str,
+    to: &str,
+    token: &str,
+) -> Result<(), MailError> {
+    let link = format!("{}/reset-password?token={}", app_base_url, token);
+    let message = EmailMessage {
+        to: to.to_string(),
+        subject: "Reset your password".to_string(),
+        body: format!(
+            "We received a request to reset your password.\n\nOpen the link below to choose a new password:\n\n{}\n\nThis link expires in {} minutes. If you did not request a password reset, you can ignore this email.",
+            link, PASSWORD_RESET_TOKEN_DURATION_MINUTES
+        ),
+    };
+
+    mailer.send(&message).await
+}
+
+/// Frontend page that asks the user to confirm a sign-in link before it is consumed,
+/// so mail scanners opening the link do not use it up
+pub fn magic_link_url(app_base_url: &str, token: &str) -> String {
+    format!("{}/magic-link?token={}", app_base_url, token)
+}
+
+/// Sends the passwordless sign-in message containing a single-use link
+pub async fn send_magic_link_email(
+    mailer: &dyn MailSender,
+    app_base_url: &str,
+    to: &str,
+    token: &str,
+) -> Result<(), MailError> {
+    let link = magic_link_url(app_base_url, token);
+    let message = EmailMessage {
+        to: to.to_string(),
+        subject: "Your sign-in link".to_string(),
+        body: format!(
+            "Open the link below to sign in:\n\n{}\n\nThis link expires in {} minutes and can only be used once. If you did not request it, you can ignore this email.",
+            link, MAGIC_LINK_TOKEN_DURATION_MINUTES
+        ),
+    };
+
+    mailer.send(&message).await
+}
+
+/// Sends the message containing a single-use link that confirms deleting the account
+pub async fn send_account_deletion_email(
+    mailer: &dyn MailSender,
+    app_base_url: &str,
+    to: &str,
+    token: &str,
+) -> Result<(), MailError> {
+    let link = format!("{}/delete-account?token={}", app_base_url, token);
+    let message = EmailMessage {
+        to: to.to_string(),
+        subject: "Confirm deleting your account".to_string(),
+        body: format!(
+            "We received a request to delete your account.\n\nOpen the link below while signed in to confirm:\n\n{}\n\nThis link expires in {} minutes. If you did not request this, sign in and review your active sessions.",
+            link, ACCOUNT_DELETION_TOKEN_DURATION_MINUTES
+        ),
+    };
+
+    mailer.send(&message).await
+}
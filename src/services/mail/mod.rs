@@ -0,0 +1,2 @@
+pub mod mail_service;
+pub mod sender;
@@ -0,0 +1,107 @@
+use crate::config::{MailConfig, MailTransport};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::path::PathBuf;
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error)]
+pub enum MailError {
+    #[error("Failed to deliver mail: {0}")]
+    DeliveryFailed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Pluggable mail delivery backend.
+/// Implement this trait to plug in a real provider (SMTP, SES, ...).
+#[async_trait]
+pub trait MailSender: Send + Sync {
+    async fn send(&self, message: &EmailMessage) -> Result<(), MailError>;
+}
+
+/// Shared mail sender stored in AppState
+pub type SharedMailSender = Arc<dyn MailSender>;
+
+/// Writes outgoing mail to the application log. Intended for local development only.
+pub struct LogMailSender {
+    from_address: String,
+}
+
+impl LogMailSender {
+    pub fn new(from_address: impl Into<String>) -> Self {
+        Self {
+            from_address: from_address.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl MailSender for LogMailSender {
+    async fn send(&self, message: &EmailMessage) -> Result<(), MailError> {
+        tracing::info!(
+            from = %self.from_address,
+            to = %message.to,
+            subject = %message.subject,
+            "Outgoing mail:\n{}",
+            message.body
+        );
+        Ok(())
+    }
+}
+
+/// Writes each outgoing mail as an .eml file into a directory so it can be opened locally
+pub struct FileMailSender {
+    from_address: String,
+    outbox_dir: PathBuf,
+}
+
+impl FileMailSender {
+    pub fn new(from_address: impl Into<String>, outbox_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            from_address: from_address.into(),
+            outbox_dir: outbox_dir.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl MailSender for FileMailSender {
+    async fn send(&self, message: &EmailMessage) -> Result<(), MailError> {
+        tokio::fs::create_dir_all(&self.outbox_dir)
+            .await
+            .map_err(|e| MailError::DeliveryFailed(e.to_string()))?;
+
+        let now = Utc::now();
+        let file_name = format!("{}-{}.eml", now.format("%Y%m%dT%H%M%S"), Uuid::new_v4());
+        let contents = format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n",
+            self.from_address,
+            message.to,
+            message.subject,
+            now.to_rfc2822(),
+            message.body
+        );
+
+        tokio::fs::write(self.outbox_dir.join(file_name), contents)
+            .await
+            .map_err(|e| MailError::DeliveryFailed(e.to_string()))
+    }
+}
+
+/// Builds the mail sender selected by configuration
+pub fn build_mail_sender(config: &MailConfig) -> SharedMailSender {
+    match config.transport {
+        MailTransport::Log => Arc::new(LogMailSender::new(config.from_address.clone())),
+        MailTransport::File => Arc::new(FileMailSender::new(
+            config.from_address.clone(),
+            config.outbox_dir.clone(),
+        )),
+    }
+}
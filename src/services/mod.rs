@@ -1,4 +1,9 @@
+pub mod admin_service;
+pub mod api_key_service;
+pub mod audit_service;
 pub mod auth;
+pub mod export_service;
 pub mod friend_service;
+pub mod mail;
 pub mod profile_service;
 pub mod scheduler;
@@ -1,6 +1,10 @@
+use crate::config::R2Config;
+use crate::constant::image::{ALLOWED_CONTENT_TYPES, MAX_AVATAR_SIZE};
 use crate::error::AppError;
+use crate::utils::image::strip_metadata;
 use aws_sdk_s3::Client as S3Client;
 use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{Delete, ObjectIdentifier};
 use uuid::Uuid;
 
 /// Uploads an avatar to R2 and returns the public URL.
@@ -40,3 +44,139 @@ pub async fn upload_avatar(
     let avatar_url = format!("{}/{}", public_url.trim_end_matches('/'), key);
     Ok(avatar_url)
 }
+
+/// Helper function to process and upload avatar image
+/// Validates file type/size, strips metadata, uploads to R2, and returns the URL
+pub async fn process_and_upload_avatar(
+    s3_client: &S3Client,
+    r2: &R2Config,
+    user_id: Uuid,
+    file_bytes: Vec<u8>,
+    content_type: &str,
+) -> Result<String, AppError> {
+    // Validate file type
+    if !ALLOWED_CONTENT_TYPES.contains(&content_type) {
+        return Err(AppError::BadRequest(
+            "Invalid file type. Allowed: JPEG, PNG, GIF, WebP".into(),
+        ));
+    }
+
+    // Validate file size
+    if file_bytes.len() > MAX_AVATAR_SIZE {
+        return Err(AppError::BadRequest(
+            "File too large. Maximum size is 5MB".into(),
+        ));
+    }
+
+    // Strip EXIF/metadata from image for privacy
+    // Use spawn_blocking because image processing is CPU-intensive
+    let ct = content_type.to_string();
+    let cleaned_bytes =
+        match tokio::task::spawn_blocking(move || strip_metadata(&file_bytes, &ct)).await {
+            Ok(Ok(bytes)) => bytes,
+            Ok(Err(e)) => {
+                tracing::error!("Failed to strip metadata: {:?}", e);
+                return Err(AppError::BadRequest("Failed to process image".into()));
+            }
+            Err(e) => {
+                tracing::error!("Task join error: {:?}", e);
+                return Err(AppError::InternalError("Internal error".into()));
+            }
+        };
+
+    // Upload to R2
+    let avatar_url = upload_avatar(
+        s3_client,
+        &r2.bucket_name,
+        &r2.public_url,
+        user_id,
+        cleaned_bytes,
+        content_type,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Avatar upload failed: {:?}", e);
+        AppError::InternalError("Failed to upload avatar".into())
+    })?;
+
+    Ok(avatar_url)
+}
+
+/// Lists the keys of every object under `avatars/{user_id}/`
+pub async fn list_user_avatar_keys(
+    s3_client: &S3Client,
+    bucket: &str,
+    user_id: Uuid,
+) -> Result<Vec<String>, AppError> {
+    let prefix = format!("avatars/{}/", user_id);
+    let mut keys = Vec::new();
+    let mut continuation_token: Option<String> = None;
+
+    loop {
+        let page = s3_client
+            .list_objects_v2()
+            .bucket(bucket)
+            .prefix(&prefix)
+            .set_continuation_token(continuation_token.take())
+            .send()
+            .await
+            .map_err(|e| {
+                AppError::InternalError(format!("Failed to list avatars: {}", e).into())
+            })?;
+
+        keys.extend(
+            page.contents()
+                .iter()
+                .filter_map(|object| object.key())
+                .map(str::to_string),
+        );
+
+        match page.next_continuation_token() {
+            Some(token) if page.is_truncated() == Some(true) => {
+                continuation_token = Some(token.to_string())
+            }
+            _ => break,
+        }
+    }
+
+    Ok(keys)
+}
+
+/// Deletes every object under `avatars/{user_id}/`.
+/// Returns the number of deleted objects.
+pub async fn delete_user_avatars(
+    s3_client: &S3Client,
+    bucket: &str,
+    user_id: Uuid,
+) -> Result<usize, AppError> {
+    let keys = list_user_avatar_keys(s3_client, bucket, user_id).await?;
+
+    // DeleteObjects accepts at most 1000 keys per request
+    for chunk in keys.chunks(1000) {
+        let objects = chunk
+            .iter()
+            .map(|key| ObjectIdentifier::builder().key(key).build())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::InternalError(format!("Invalid avatar key: {}", e).into()))?;
+
+        let delete = Delete::builder()
+            .set_objects(Some(objects))
+            .quiet(true)
+            .build()
+            .map_err(|e| {
+                AppError::InternalError(format!("Invalid delete request: {}", e).into())
+            })?;
+
+        s3_client
+            .delete_objects()
+            .bucket(bucket)
+            .delete(delete)
+            .send()
+            .await
+            .map_err(|e| {
+                AppError::InternalError(format!("Failed to delete avatars: {}", e).into())
+            })?;
+    }
+
+    Ok(keys.len())
+}
@@ -1,13 +1,19 @@
-use crate::repository::token_repository;
-use sqlx::PgPool;
+use crate::repository::{
+    account_deletion_repository, identity_repository, magic_link_repository,
+    password_reset_repository, token_repository, verification_repository, webauthn_repository,
+};
+use crate::services::auth::{account_deletion_service, lockout_service, magic_link_service};
+use crate::services::{audit_service, export_service};
+use crate::state::AppState;
 use tokio_cron_scheduler::{Job, JobScheduler, JobSchedulerError};
 use tracing::{error, info};
 
-pub async fn init_scheduler(pool: PgPool) -> Result<JobScheduler, JobSchedulerError> {
+pub async fn init_scheduler(state: AppState) -> Result<JobScheduler, JobSchedulerError> {
     let sched = JobScheduler::new().await?;
 
     // Schedule: 2:00 AM daily
     // Cron format: sec min hour day_of_month month day_of_week year
+    let pool = state.pool.clone();
     let job = Job::new_async("0 0 2 * * * *", move |_uuid, _l| {
         let pool = pool.clone();
         Box::pin(async move {
@@ -16,9 +22,80 @@ pub async fn init_scheduler(pool: PgPool) -> Result<JobScheduler, JobSchedulerEr
                 Ok(count) => info!("Deleted {} expired refresh tokens.", count),
                 Err(e) => error!("Failed to delete expired tokens: {}", e),
             }
+            match token_repository::delete_expired_revoked_access_tokens(&pool).await {
+                Ok(count) => info!("Deleted {} expired access token revocations.", count),
+                Err(e) => error!("Failed to delete access token revocations: {}", e),
+            }
+            match verification_repository::delete_expired_tokens(&pool).await {
+                Ok(count) => info!("Deleted {} stale email verification tokens.", count),
+                Err(e) => error!("Failed to delete email verification tokens: {}", e),
+            }
+            match password_reset_repository::delete_expired_tokens(&pool).await {
+                Ok(count) => info!("Deleted {} stale password reset tokens.", count),
+                Err(e) => error!("Failed to delete password reset tokens: {}", e),
+            }
+            match magic_link_repository::delete_expired_tokens(&pool).await {
+                Ok(count) => info!("Deleted {} stale magic link tokens.", count),
+                Err(e) => error!("Failed to delete magic link tokens: {}", e),
+            }
+            match account_deletion_repository::delete_expired_tokens(&pool).await {
+                Ok(count) => info!("Deleted {} stale account deletion tokens.", count),
+                Err(e) => error!("Failed to delete account deletion tokens: {}", e),
+            }
+            match magic_link_service::delete_stale_requests(&pool).await {
+                Ok(count) => info!("Deleted {} old magic link requests.", count),
+                Err(e) => error!("Failed to delete magic link requests: {}", e),
+            }
+            match lockout_service::delete_stale_throttles(&pool).await {
+                Ok(count) => info!("Deleted {} stale login throttle entries.", count),
+                Err(e) => error!("Failed to delete login throttle entries: {}", e),
+            }
+            match identity_repository::delete_expired_auth_requests(&pool).await {
+                Ok(count) => info!("Deleted {} expired OIDC authorization requests.", count),
+                Err(e) => error!("Failed to delete OIDC authorization requests: {}", e),
+            }
+            match webauthn_repository::delete_expired_challenges(&pool).await {
+                Ok(count) => info!("Deleted {} expired passkey challenges.", count),
+                Err(e) => error!("Failed to delete passkey challenges: {}", e),
+            }
+            match audit_service::delete_expired_events(&pool).await {
+                Ok(count) => info!("Deleted {} expired audit events.", count),
+                Err(e) => error!("Failed to delete audit events: {}", e),
+            }
         })
     })?;
 
     sched.add(job).await?;
+
+    // Schedule: 3:00 AM daily, purge accounts past their deletion grace period and expired exports
+    let purge_job = Job::new_async("0 0 3 * * * *", move |_uuid, _l| {
+        let state = state.clone();
+        Box::pin(async move {
+            info!("Starting scheduled data purge...");
+            match account_deletion_service::purge_expired_accounts(
+                &state.pool,
+                &state.s3_client,
+                &state.config.r2.bucket_name,
+                state.config.account_deletion_grace_days,
+            )
+            .await
+            {
+                Ok(count) => info!("Purged {} deleted accounts.", count),
+                Err(e) => error!("Failed to purge deleted accounts: {}", e),
+            }
+            match export_service::delete_expired_exports(
+                &state.pool,
+                &state.s3_client,
+                &state.config.r2.bucket_name,
+            )
+            .await
+            {
+                Ok(count) => info!("Deleted {} expired data exports.", count),
+                Err(e) => error!("Failed to delete expired data exports: {}", e),
+            }
+        })
+    })?;
+
+    sched.add(purge_job).await?;
     Ok(sched)
 }
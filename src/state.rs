@@ -1,4 +1,8 @@
 use crate::config::Config;
+use crate::services::audit_service::AuditService;
+use crate::services::auth::account_status_service::UserStatusCache;
+use crate::services::auth::oidc_service::OidcClient;
+use crate::services::mail::sender::SharedMailSender;
 use aws_sdk_s3::Client as S3Client;
 use governor::clock::QuantaInstant;
 use governor::middleware::NoOpMiddleware;
@@ -17,4 +21,12 @@ pub struct AppState {
     pub s3_client: S3Client,
     /// Shared rate limit config (per docs: do not create config multiple times!)
     pub rate_limit_config: RateLimitConfig,
+    /// Outgoing mail backend (log/file in development)
+    pub mailer: SharedMailSender,
+    /// Cached is_active / is_deleted flags checked by the auth middleware
+    pub user_status_cache: UserStatusCache,
+    /// Background writer for the security audit log
+    pub audit: AuditService,
+    /// Client for external OpenID Connect sign-in providers
+    pub oidc: OidcClient,
 }
@@ -0,0 +1,65 @@
+use sha1::{Digest, Sha1};
+use std::{io, path::Path};
+
+/// Length of the SHA-1 prefix that names a range file
+const RANGE_PREFIX_LEN: usize = 5;
+
+/// Splits the uppercase hex SHA-1 of a password into its range prefix and the remaining suffix
+fn range_key(password: &str) -> (String, String) {
+    let digest = format!("{:X}", Sha1::digest(password.as_bytes()));
+    let (prefix, suffix) = digest.split_at(RANGE_PREFIX_LEN);
+    (prefix.to_string(), suffix.to_string())
+}
+
+/// Whether a range file lists the suffix. Lines are `SUFFIX:COUNT`; padding entries with a
+/// count of 0 (added by the online API to hide response sizes) are not breaches.
+fn range_contains(range: &str, suffix: &str) -> bool {
+    range.lines().any(|line| {
+        let Some((entry, count)) = line.trim().split_once(':') else {
+            return false;
+        };
+        entry.eq_ignore_ascii_case(suffix) && count.trim().parse::<u64>().is_ok_and(|c| c > 0)
+    })
+}
+
+/// Looks a password up in a local copy of a breach corpus stored as k-anonymity range files,
+/// the layout of the Pwned Passwords downloads: one `{PREFIX}.txt` per 5 hex digit SHA-1 prefix.
+/// Only the one file for the password's prefix is read. A missing file means no entries.
+pub async fn is_breached(ranges_dir: &Path, password: &str) -> io::Result<bool> {
+    let (prefix, suffix) = range_key(password);
+    match tokio::fs::read_to_string(ranges_dir.join(format!("{}.txt", prefix))).await {
+        Ok(range) => Ok(range_contains(&range, &suffix)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_key_splits_sha1() {
+        // SHA-1("password") = 5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8
+        let (prefix, suffix) = range_key("password");
+        assert_eq!(prefix, "5BAA6");
+        assert_eq!(suffix, "1E4C9B93F3F0682250B6CF8331B7EE68FD8");
+    }
+
+    #[test]
+    fn test_range_contains_matches_suffix_with_count() {
+        let range = "0018A45C4D1DEF81644B54AB7F969B88D65:10\r\n\
+                     1E4C9B93F3F0682250B6CF8331B7EE68FD8:9659365\r\n\
+                     011053FD0102E94D6AE2F8B83D76FAF94F6:0\r\n";
+        assert!(range_contains(range, "1E4C9B93F3F0682250B6CF8331B7EE68FD8"));
+        assert!(range_contains(range, "1e4c9b93f3f0682250b6cf8331b7ee68fd8"));
+        assert!(!range_contains(
+            range,
+            "011053FD0102E94D6AE2F8B83D76FAF94F6"
+        ));
+        assert!(!range_contains(
+            range,
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF"
+        ));
+    }
+}
@@ -0,0 +1,174 @@
+use crate::constant::auth::{
+    CLIENT_TYPE_HEADER, NATIVE_CLIENT_TYPE, REFRESH_TOKEN_COOKIE_NAME, REFRESH_TOKEN_HEADER,
+};
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{HeaderMap, Request, header::AUTHORIZATION, request::Parts},
+};
+use axum_extra::extract::cookie::CookieJar;
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
+use tower_governor::key_extractor::{KeyExtractor, SmartIpKeyExtractor};
+
+/// How the rate limiter and per-client limits tell clients apart behind the reverse proxy:
+/// the forwarding headers set by the proxy, falling back to the peer address
+pub const CLIENT_IP_KEY_EXTRACTOR: SmartIpKeyExtractor = SmartIpKeyExtractor;
+
+/// Client address resolved like the rate limiter does, for keying per-client limits.
+/// Sessions and audit records keep the peer address from `DeviceInfo`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(ClientIp(client_ip(parts)))
+    }
+}
+
+fn client_ip(parts: &Parts) -> Option<IpAddr> {
+    // The extractor reads a request; only the headers and the peer address matter to it
+    let mut request = Request::new(());
+    *request.headers_mut() = parts.headers.clone();
+    if let Some(connect_info) = parts.extensions.get::<ConnectInfo<SocketAddr>>() {
+        request.extensions_mut().insert(*connect_info);
+    }
+    CLIENT_IP_KEY_EXTRACTOR.extract(&request).ok()
+}
+
+/// How a client receives and presents its tokens.
+/// Browsers use HttpOnly cookies; native apps and CLI tools opt in to receiving tokens
+/// in the response body by sending `X-Client-Type: native`, and present them as
+/// `Authorization: Bearer` (access token) or in the request body (refresh token).
+/// Account requests that need to know the current session take the refresh token from
+/// the `X-Refresh-Token` header instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClientType {
+    #[default]
+    Browser,
+    Native,
+}
+
+impl<S> FromRequestParts<S> for ClientType
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let is_native = parts
+            .headers
+            .get(CLIENT_TYPE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.trim().eq_ignore_ascii_case(NATIVE_CLIENT_TYPE));
+
+        Ok(if is_native {
+            ClientType::Native
+        } else {
+            ClientType::Browser
+        })
+    }
+}
+
+/// Token from an `Authorization: Bearer <token>` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.trim().split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+/// Refresh token identifying the current session on an authenticated request:
+/// the cookie for browsers, the `X-Refresh-Token` header for native clients
+pub fn session_refresh_token<'a>(
+    client: ClientType,
+    jar: &'a CookieJar,
+    headers: &'a HeaderMap,
+) -> Option<&'a str> {
+    match client {
+        ClientType::Browser => jar.get(REFRESH_TOKEN_COOKIE_NAME).map(|c| c.value()),
+        ClientType::Native => headers
+            .get(REFRESH_TOKEN_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    }
+    .map(str::trim)
+    .filter(|token| !token.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use axum_extra::extract::cookie::Cookie;
+
+    fn headers(authorization: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(authorization).expect("Invalid header"),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_bearer_token_is_extracted() {
+        assert_eq!(bearer_token(&headers("Bearer abc.def")), Some("abc.def"));
+        assert_eq!(bearer_token(&headers("bearer  abc.def ")), Some("abc.def"));
+    }
+
+    #[test]
+    fn test_other_schemes_are_ignored() {
+        assert_eq!(bearer_token(&headers("Basic dXNlcjpwYXNz")), None);
+        assert_eq!(bearer_token(&headers("Bearer")), None);
+        assert_eq!(bearer_token(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_client_ip_prefers_forwarded_address() {
+        let peer = ConnectInfo(SocketAddr::from(([10, 0, 0, 2], 443)));
+        let (forwarded, _) = Request::builder()
+            .header("x-forwarded-for", "203.0.113.7, 10.0.0.1")
+            .extension(peer)
+            .body(())
+            .unwrap()
+            .into_parts();
+        let (direct, _) = Request::builder()
+            .extension(peer)
+            .body(())
+            .unwrap()
+            .into_parts();
+
+        assert_eq!(client_ip(&forwarded), Some([203, 0, 113, 7].into()));
+        assert_eq!(client_ip(&direct), Some([10, 0, 0, 2].into()));
+    }
+
+    #[test]
+    fn test_session_refresh_token_by_client_type() {
+        let jar = CookieJar::new().add(Cookie::new(REFRESH_TOKEN_COOKIE_NAME, "from-cookie"));
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            REFRESH_TOKEN_HEADER,
+            HeaderValue::from_static(" from-header "),
+        );
+
+        assert_eq!(
+            session_refresh_token(ClientType::Browser, &jar, &headers),
+            Some("from-cookie")
+        );
+        assert_eq!(
+            session_refresh_token(ClientType::Native, &jar, &headers),
+            Some("from-header")
+        );
+        // Native clients hold no cookies; a stray one does not identify their session
+        assert_eq!(
+            session_refresh_token(ClientType::Native, &jar, &HeaderMap::new()),
+            None
+        );
+    }
+}
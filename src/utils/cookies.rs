@@ -1,24 +1,54 @@
 use crate::constant::auth::{
-    ACCESS_TOKEN_COOKIE_NAME, REFRESH_TOKEN_COOKIE_NAME, REFRESH_TOKEN_DURATION_DAYS,
+    ACCESS_TOKEN_COOKIE_NAME, CSRF_COOKIE_NAME, REFRESH_TOKEN_COOKIE_NAME,
+    REFRESH_TOKEN_DURATION_DAYS,
 };
+use crate::utils::token::generate_opaque_token;
 use axum_extra::extract::cookie::{Cookie, SameSite};
 
+/// Double-submit CSRF cookie. Unlike the token cookies it is readable by scripts,
+/// which must echo its value in the `X-CSRF-Token` header of unsafe requests.
+fn csrf_cookie(
+    value: String,
+    csrf_cookie_domain: Option<&str>,
+    max_age: Option<time::Duration>,
+) -> Cookie<'static> {
+    let mut cookie = Cookie::build((CSRF_COOKIE_NAME, value))
+        .http_only(false)
+        .secure(true)
+        .same_site(SameSite::Strict)
+        .path("/")
+        .build();
+
+    if let Some(domain) = csrf_cookie_domain {
+        cookie.set_domain(domain.to_string());
+    }
+    if let Some(max_age) = max_age {
+        cookie.set_max_age(max_age);
+    }
+    cookie
+}
+
+/// Access token cookie, also sent on its own when the current session gets a new access token
+pub fn create_access_cookie(access_token: String) -> Cookie<'static> {
+    // Expires in 1 hour (same as JWT)
+    Cookie::build((ACCESS_TOKEN_COOKIE_NAME, access_token))
+        .http_only(true)
+        .secure(true) // Set to false if not running on HTTPS locally, but true is recommended
+        .same_site(SameSite::Strict)
+        .path("/")
+        .build()
+}
+
 pub fn create_auth_cookies(
     access_token: String,
     refresh_token: String,
+    csrf_cookie_domain: Option<&str>,
     remember_me: bool,
 ) -> Vec<Cookie<'static>> {
     let mut cookies = Vec::new();
 
     // Access Token Cookie
-    // Expires in 1 hour (same as JWT)
-    let access_cookie = Cookie::build((ACCESS_TOKEN_COOKIE_NAME, access_token))
-        .http_only(true)
-        .secure(true) // Set to false if not running on HTTPS locally, but true is recommended
-        .same_site(SameSite::Strict)
-        .path("/")
-        .build();
-    cookies.push(access_cookie);
+    cookies.push(create_access_cookie(access_token));
 
     // Refresh Token Cookie
     let mut refresh_cookie_builder = Cookie::build((REFRESH_TOKEN_COOKIE_NAME, refresh_token))
@@ -37,10 +67,18 @@ pub fn create_auth_cookies(
     let refresh_cookie = refresh_cookie_builder.build();
     cookies.push(refresh_cookie);
 
+    // A new CSRF token comes with every login and refresh, and lives as long as the session
+    let csrf_cookie = csrf_cookie(
+        generate_opaque_token(),
+        csrf_cookie_domain,
+        remember_me.then(|| time::Duration::days(REFRESH_TOKEN_DURATION_DAYS)),
+    );
+    cookies.push(csrf_cookie);
+
     cookies
 }
 
-pub fn remove_auth_cookies() -> Vec<Cookie<'static>> {
+pub fn remove_auth_cookies(csrf_cookie_domain: Option<&str>) -> Vec<Cookie<'static>> {
     let mut cookies = Vec::new();
 
     let access_cookie = Cookie::build((ACCESS_TOKEN_COOKIE_NAME, ""))
@@ -61,5 +99,12 @@ pub fn remove_auth_cookies() -> Vec<Cookie<'static>> {
         .build();
     cookies.push(refresh_cookie);
 
+    let csrf_cookie = csrf_cookie(
+        String::new(),
+        csrf_cookie_domain,
+        Some(time::Duration::seconds(0)),
+    );
+    cookies.push(csrf_cookie);
+
     cookies
 }
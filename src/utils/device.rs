@@ -0,0 +1,44 @@
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header::USER_AGENT, request::Parts},
+};
+use std::{convert::Infallible, net::SocketAddr};
+
+/// Maximum stored length of a User-Agent header (matches the DB column)
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+/// Describes the device a session was opened from.
+/// Extracted from the request; `device_name` is optionally supplied by the client.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub device_name: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for DeviceInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let user_agent = parts
+            .headers
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|ua| ua.chars().take(MAX_USER_AGENT_LENGTH).collect());
+
+        // Available because the server is started with into_make_service_with_connect_info
+        let ip_address = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string());
+
+        Ok(DeviceInfo {
+            user_agent,
+            ip_address,
+            device_name: None,
+        })
+    }
+}
@@ -0,0 +1,114 @@
+/// Domains that deliver `user+tag@` to the `user@` mailbox
+const SUBADDRESS_DOMAINS: &[&str] = &[
+    "gmail.com",
+    "outlook.com",
+    "hotmail.com",
+    "live.com",
+    "icloud.com",
+    "me.com",
+    "fastmail.com",
+    "proton.me",
+    "protonmail.com",
+];
+
+/// Domains whose mailboxes ignore dots in the local part
+const DOT_INSENSITIVE_DOMAINS: &[&str] = &["gmail.com"];
+
+/// Alternative domains that reach the same mailboxes as the canonical domain
+const DOMAIN_ALIASES: &[(&str, &str)] = &[("googlemail.com", "gmail.com")];
+
+/// Canonical form of an email address, used to look accounts up and to keep addresses unique:
+/// trimmed, lowercased and with an internationalized domain converted to punycode.
+/// With `provider_rules`, spellings that a known provider delivers to the same mailbox
+/// (`+tags`, dots in Gmail addresses, `googlemail.com`) collapse into one address as well.
+pub fn normalize_email(email: &str, provider_rules: bool) -> Result<String, String> {
+    let (local, domain) = email
+        .trim()
+        .rsplit_once('@')
+        .ok_or_else(|| "missing @".to_string())?;
+    if local.is_empty() {
+        return Err("empty local part".to_string());
+    }
+
+    // Also lowercases ASCII domains
+    let mut domain = idna::domain_to_ascii(domain.trim_end_matches('.'))
+        .map_err(|e| format!("invalid domain: {}", e))?;
+    if domain.is_empty() {
+        return Err("empty domain".to_string());
+    }
+    let mut local = local.to_lowercase();
+
+    if provider_rules {
+        if let Some((_, canonical)) = DOMAIN_ALIASES.iter().find(|(alias, _)| *alias == domain) {
+            domain = canonical.to_string();
+        }
+        if SUBADDRESS_DOMAINS.contains(&domain.as_str())
+            && let Some((base, _)) = local.split_once('+')
+            && !base.is_empty()
+        {
+            local = base.to_string();
+        }
+        if DOT_INSENSITIVE_DOMAINS.contains(&domain.as_str()) {
+            local.retain(|c| c != '.');
+        }
+    }
+
+    Ok(format!("{}@{}", local, domain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lowercases_and_trims() {
+        assert_eq!(
+            normalize_email("  Alice@Example.COM ", false).unwrap(),
+            "alice@example.com"
+        );
+    }
+
+    #[test]
+    fn test_converts_idn_domain_to_punycode() {
+        assert_eq!(
+            normalize_email("user@Bücher.example", false).unwrap(),
+            "user@xn--bcher-kva.example"
+        );
+    }
+
+    #[test]
+    fn test_keeps_tags_and_dots_without_provider_rules() {
+        assert_eq!(
+            normalize_email("John.Doe+news@gmail.com", false).unwrap(),
+            "john.doe+news@gmail.com"
+        );
+    }
+
+    #[test]
+    fn test_provider_rules() {
+        assert_eq!(
+            normalize_email("John.Doe+news@googlemail.com", true).unwrap(),
+            "johndoe@gmail.com"
+        );
+        assert_eq!(
+            normalize_email("jane.doe+x@outlook.com", true).unwrap(),
+            "jane.doe@outlook.com"
+        );
+        // Other providers may treat `+` and dots as part of the mailbox name
+        assert_eq!(
+            normalize_email("jane.doe+x@example.com", true).unwrap(),
+            "jane.doe+x@example.com"
+        );
+        assert_eq!(
+            normalize_email("+x@gmail.com", true).unwrap(),
+            "+x@gmail.com"
+        );
+    }
+
+    #[test]
+    fn test_rejects_malformed_addresses() {
+        assert!(normalize_email("no-at-sign", false).is_err());
+        assert!(normalize_email("@example.com", false).is_err());
+        assert!(normalize_email("user@", false).is_err());
+    }
+}
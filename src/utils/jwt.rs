@@ -1,12 +1,21 @@
+use crate::config::JwtConfig;
 use crate::error::AuthError;
+use crate::models::{api_key::ApiKeyScope, role::Role};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{EncodingKey, Header, encode};
+use jsonwebtoken::{Validation, encode};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Copy)]
 pub enum TokenType {
     Access,
     Refresh,
+    /// Proves the password step of a login succeeded; only accepted by the second-factor step
+    MfaPending,
+    /// Proves the password of a deleted account was entered; only accepted by account restoration
+    AccountRestore,
+    /// Not a token: marks claims built for a request authenticated with an API key
+    ApiKey,
 }
 
 impl TokenType {
@@ -14,6 +23,9 @@ impl TokenType {
         match self {
             TokenType::Access => "access",
             TokenType::Refresh => "refresh",
+            TokenType::MfaPending => "mfa_pending",
+            TokenType::AccountRestore => "account_restore",
+            TokenType::ApiKey => "api_key",
         }
     }
 }
@@ -21,67 +33,181 @@ impl TokenType {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub sub: String,
+    pub iss: String,
+    pub aud: String,
+    /// Unique per token, so a single token can be denylisted
+    pub jti: String,
     pub iat: usize,
+    pub nbf: usize,
     pub exp: usize,
     pub token_type: String,
+    /// Only present in access tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+    /// `users_auth.token_version` at issuance; only present in access tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_version: Option<i32>,
+    /// Only present for API keys, which are limited to these scopes.
+    /// Interactive sessions have full access.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<ApiKeyScope>>,
 }
 
-pub fn create_jwt(user_id: &str, secret: &str) -> Result<String, AuthError> {
-    let expiration = Utc::now()
-        .checked_add_signed(Duration::hours(1))
-        .ok_or_else(|| AuthError::TokenCreationError("Invalid timestamp calculation".to_string()))?
-        .timestamp();
+impl Claims {
+    /// Role granted by the token; tokens without one only get user access
+    pub fn role(&self) -> Role {
+        self.role.unwrap_or_default()
+    }
 
-    let claims = Claims {
-        sub: user_id.to_owned(),
-        iat: Utc::now().timestamp() as usize,
-        exp: expiration as usize,
-        token_type: TokenType::Access.as_str().to_string(),
-    };
+    /// Whether the token predates the user's latest revocation
+    pub fn is_revoked(&self, current_token_version: i32) -> bool {
+        self.token_version.unwrap_or(0) < current_token_version
+    }
 
-    Ok(encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_bytes()),
-    )?)
+    pub fn is_api_key(&self) -> bool {
+        self.token_type == TokenType::ApiKey.as_str()
+    }
+
+    /// Whether the request may use something that requires `required`
+    pub fn has_scope(&self, required: ApiKeyScope) -> bool {
+        match &self.scopes {
+            None => true,
+            Some(scopes) => scopes.iter().any(|scope| scope.satisfies(required)),
+        }
+    }
+}
+
+/// Principal for a request authenticated with an API key, shaped like the claims of an access
+/// token so handlers need not tell them apart. Never signed; valid only for the current request.
+/// API keys never grant more than the user role.
+pub fn api_key_claims(
+    user_id: Uuid,
+    key_id: Uuid,
+    scopes: Vec<ApiKeyScope>,
+    jwt: &JwtConfig,
+) -> Claims {
+    let now = Utc::now().timestamp() as usize;
+    Claims {
+        sub: user_id.to_string(),
+        iss: jwt.issuer.clone(),
+        aud: jwt.audience.clone(),
+        jti: key_id.to_string(),
+        iat: now,
+        nbf: now,
+        exp: now,
+        token_type: TokenType::ApiKey.as_str().to_string(),
+        role: Some(Role::User),
+        token_version: None,
+        scopes: Some(scopes),
+    }
 }
 
-pub fn create_refresh_token(user_id: &str, secret: &str) -> Result<String, AuthError> {
-    let expiration = Utc::now()
-        .checked_add_signed(Duration::days(
-            crate::constant::auth::REFRESH_TOKEN_DURATION_DAYS,
-        ))
+/// Signs a token of the given type that is valid from now for `lifetime`
+fn issue_token(
+    user_id: &str,
+    token_type: TokenType,
+    lifetime: Duration,
+    role: Option<Role>,
+    token_version: Option<i32>,
+    jwt: &JwtConfig,
+) -> Result<String, AuthError> {
+    let now = Utc::now();
+    let expiration = now
+        .checked_add_signed(lifetime)
         .ok_or_else(|| AuthError::TokenCreationError("Invalid timestamp calculation".to_string()))?
         .timestamp();
 
     let claims = Claims {
         sub: user_id.to_owned(),
-        iat: Utc::now().timestamp() as usize,
+        iss: jwt.issuer.clone(),
+        aud: jwt.audience.clone(),
+        jti: Uuid::new_v4().to_string(),
+        iat: now.timestamp() as usize,
+        nbf: now.timestamp() as usize,
         exp: expiration as usize,
-        token_type: TokenType::Refresh.as_str().to_string(),
+        token_type: token_type.as_str().to_string(),
+        role,
+        token_version,
+        scopes: None,
     };
 
     Ok(encode(
-        &Header::default(),
+        &jwt.keys.header(),
         &claims,
-        &EncodingKey::from_secret(secret.as_bytes()),
+        jwt.keys.encoding_key(),
     )?)
 }
-pub fn decode_jwt(token: &str, secret: &str) -> Result<Claims, AuthError> {
-    Ok(jsonwebtoken::decode::<Claims>(
-        token,
-        &jsonwebtoken::DecodingKey::from_secret(secret.as_bytes()),
-        &jsonwebtoken::Validation::default(),
-    )?
-    .claims)
+
+pub fn create_jwt(
+    user_id: &str,
+    role: Role,
+    token_version: i32,
+    jwt: &JwtConfig,
+) -> Result<String, AuthError> {
+    issue_token(
+        user_id,
+        TokenType::Access,
+        Duration::minutes(crate::constant::auth::ACCESS_TOKEN_DURATION_MINUTES),
+        Some(role),
+        Some(token_version),
+        jwt,
+    )
+}
+
+pub fn create_refresh_token(user_id: &str, jwt: &JwtConfig) -> Result<String, AuthError> {
+    issue_token(
+        user_id,
+        TokenType::Refresh,
+        Duration::days(crate::constant::auth::REFRESH_TOKEN_DURATION_DAYS),
+        None,
+        None,
+        jwt,
+    )
+}
+
+pub fn create_mfa_pending_token(user_id: &str, jwt: &JwtConfig) -> Result<String, AuthError> {
+    issue_token(
+        user_id,
+        TokenType::MfaPending,
+        Duration::minutes(crate::constant::auth::MFA_PENDING_TOKEN_DURATION_MINUTES),
+        None,
+        None,
+        jwt,
+    )
+}
+
+pub fn create_account_restore_token(user_id: &str, jwt: &JwtConfig) -> Result<String, AuthError> {
+    issue_token(
+        user_id,
+        TokenType::AccountRestore,
+        Duration::minutes(crate::constant::auth::ACCOUNT_RESTORE_TOKEN_DURATION_MINUTES),
+        None,
+        None,
+        jwt,
+    )
+}
+
+/// Tokens must be current and minted for this deployment's issuer and audience
+fn validation(jwt: &JwtConfig) -> Validation {
+    let mut validation = Validation::default();
+    validation.set_issuer(&[&jwt.issuer]);
+    validation.set_audience(&[&jwt.audience]);
+    validation.set_required_spec_claims(&["exp", "nbf", "iss", "aud", "sub"]);
+    validation.validate_nbf = true;
+    validation.leeway = jwt.leeway_seconds;
+    validation
+}
+
+pub fn decode_jwt(token: &str, jwt: &JwtConfig) -> Result<Claims, AuthError> {
+    Ok(jwt.keys.decode::<Claims>(token, &validation(jwt))?.claims)
 }
 
 pub fn decode_jwt_with_type(
     token: &str,
-    secret: &str,
+    jwt: &JwtConfig,
     expected_type: TokenType,
 ) -> Result<Claims, AuthError> {
-    let claims = decode_jwt(token, secret)?;
+    let claims = decode_jwt(token, jwt)?;
 
     if claims.token_type != expected_type.as_str() {
         return Err(AuthError::InvalidTokenType);
@@ -94,14 +220,28 @@ pub fn decode_jwt_with_type(
 mod tests {
     use super::*;
     use crate::error::AuthError;
+    use crate::utils::jwt_keys::JwtKeys;
     use chrono::Utc;
 
     const SECRET: &str = "test_secret";
     const USER_ID: &str = "user_123";
 
+    fn keys() -> JwtConfig {
+        config(SECRET, "test_issuer", "test_audience")
+    }
+
+    fn config(secret: &str, issuer: &str, audience: &str) -> JwtConfig {
+        JwtConfig {
+            keys: JwtKeys::hs256(secret),
+            issuer: issuer.to_string(),
+            audience: audience.to_string(),
+            leeway_seconds: 0,
+        }
+    }
+
     #[test]
     fn test_create_jwt_happy_path() {
-        let token_result = create_jwt(USER_ID, SECRET);
+        let token_result = create_jwt(USER_ID, Role::User, 0, &keys());
         assert!(token_result.is_ok());
         let token = token_result.unwrap();
         assert!(!token.is_empty());
@@ -112,8 +252,8 @@ mod tests {
 
     #[test]
     fn test_decode_jwt_happy_path() {
-        let token = create_jwt(USER_ID, SECRET).expect("Failed to create token");
-        let claims_result = decode_jwt(&token, SECRET);
+        let token = create_jwt(USER_ID, Role::User, 0, &keys()).expect("Failed to create token");
+        let claims_result = decode_jwt(&token, &keys());
 
         assert!(claims_result.is_ok());
         let claims = claims_result.unwrap();
@@ -126,7 +266,7 @@ mod tests {
 
     #[test]
     fn test_create_refresh_token_happy_path() {
-        let token_result = create_refresh_token(USER_ID, SECRET);
+        let token_result = create_refresh_token(USER_ID, &keys());
         assert!(token_result.is_ok());
         let token = token_result.unwrap();
         assert!(!token.is_empty());
@@ -135,36 +275,36 @@ mod tests {
     #[test]
     fn test_decode_jwt_invalid_token() {
         let invalid_token = "invalid.token.string";
-        let result = decode_jwt(invalid_token, SECRET);
+        let result = decode_jwt(invalid_token, &keys());
         assert!(matches!(result, Err(AuthError::TokenCreationError(_))));
     }
 
     #[test]
     fn test_decode_jwt_wrong_secret() {
-        let token = create_jwt(USER_ID, SECRET).expect("Failed to create token");
-        let wrong_secret = "wrong_secret";
-        let result = decode_jwt(&token, wrong_secret);
+        let token = create_jwt(USER_ID, Role::User, 0, &keys()).expect("Failed to create token");
+        let wrong_keys = config("wrong_secret", "test_issuer", "test_audience");
+        let result = decode_jwt(&token, &wrong_keys);
         assert!(matches!(result, Err(AuthError::TokenCreationError(_))));
     }
 
     #[test]
     fn test_access_token_has_correct_type() {
-        let token = create_jwt(USER_ID, SECRET).expect("Failed to create token");
-        let claims = decode_jwt(&token, SECRET).expect("Failed to decode token");
+        let token = create_jwt(USER_ID, Role::User, 0, &keys()).expect("Failed to create token");
+        let claims = decode_jwt(&token, &keys()).expect("Failed to decode token");
         assert_eq!(claims.token_type, "access");
     }
 
     #[test]
     fn test_refresh_token_has_correct_type() {
-        let token = create_refresh_token(USER_ID, SECRET).expect("Failed to create token");
-        let claims = decode_jwt(&token, SECRET).expect("Failed to decode token");
+        let token = create_refresh_token(USER_ID, &keys()).expect("Failed to create token");
+        let claims = decode_jwt(&token, &keys()).expect("Failed to decode token");
         assert_eq!(claims.token_type, "refresh");
     }
 
     #[test]
     fn test_decode_access_token_with_correct_type() {
-        let token = create_jwt(USER_ID, SECRET).expect("Failed to create token");
-        let result = decode_jwt_with_type(&token, SECRET, TokenType::Access);
+        let token = create_jwt(USER_ID, Role::User, 0, &keys()).expect("Failed to create token");
+        let result = decode_jwt_with_type(&token, &keys(), TokenType::Access);
         assert!(result.is_ok());
         let claims = result.unwrap();
         assert_eq!(claims.sub, USER_ID);
@@ -172,8 +312,8 @@ mod tests {
 
     #[test]
     fn test_decode_refresh_token_with_correct_type() {
-        let token = create_refresh_token(USER_ID, SECRET).expect("Failed to create token");
-        let result = decode_jwt_with_type(&token, SECRET, TokenType::Refresh);
+        let token = create_refresh_token(USER_ID, &keys()).expect("Failed to create token");
+        let result = decode_jwt_with_type(&token, &keys(), TokenType::Refresh);
         assert!(result.is_ok());
         let claims = result.unwrap();
         assert_eq!(claims.sub, USER_ID);
@@ -181,15 +321,125 @@ mod tests {
 
     #[test]
     fn test_token_substitution_access_as_refresh_fails() {
-        let access_token = create_jwt(USER_ID, SECRET).expect("Failed to create token");
-        let result = decode_jwt_with_type(&access_token, SECRET, TokenType::Refresh);
+        let access_token =
+            create_jwt(USER_ID, Role::User, 0, &keys()).expect("Failed to create token");
+        let result = decode_jwt_with_type(&access_token, &keys(), TokenType::Refresh);
         assert!(matches!(result, Err(AuthError::InvalidTokenType)));
     }
 
     #[test]
     fn test_token_substitution_refresh_as_access_fails() {
-        let refresh_token = create_refresh_token(USER_ID, SECRET).expect("Failed to create token");
-        let result = decode_jwt_with_type(&refresh_token, SECRET, TokenType::Access);
+        let refresh_token = create_refresh_token(USER_ID, &keys()).expect("Failed to create token");
+        let result = decode_jwt_with_type(&refresh_token, &keys(), TokenType::Access);
         assert!(matches!(result, Err(AuthError::InvalidTokenType)));
     }
+
+    #[test]
+    fn test_mfa_pending_token_is_not_an_access_token() {
+        let token = create_mfa_pending_token(USER_ID, &keys()).expect("Failed to create token");
+        let result = decode_jwt_with_type(&token, &keys(), TokenType::Access);
+        assert!(matches!(result, Err(AuthError::InvalidTokenType)));
+
+        let claims = decode_jwt_with_type(&token, &keys(), TokenType::MfaPending)
+            .expect("Failed to decode token");
+        assert_eq!(claims.sub, USER_ID);
+    }
+
+    #[test]
+    fn test_access_token_carries_role() {
+        let token = create_jwt(USER_ID, Role::Admin, 0, &keys()).expect("Failed to create token");
+        let claims = decode_jwt_with_type(&token, &keys(), TokenType::Access)
+            .expect("Failed to decode token");
+        assert_eq!(claims.role(), Role::Admin);
+    }
+
+    #[test]
+    fn test_refresh_token_grants_no_role() {
+        let token = create_refresh_token(USER_ID, &keys()).expect("Failed to create token");
+        let claims = decode_jwt(&token, &keys()).expect("Failed to decode token");
+        assert_eq!(claims.role, None);
+        assert_eq!(claims.role(), Role::User);
+    }
+
+    #[test]
+    fn test_tokens_have_unique_ids() {
+        let first = create_jwt(USER_ID, Role::User, 0, &keys()).expect("Failed to create token");
+        let second = create_jwt(USER_ID, Role::User, 0, &keys()).expect("Failed to create token");
+        let first = decode_jwt(&first, &keys()).expect("Failed to decode token");
+        let second = decode_jwt(&second, &keys()).expect("Failed to decode token");
+        assert_ne!(first.jti, second.jti);
+        assert_eq!(first.iss, "test_issuer");
+        assert_eq!(first.aud, "test_audience");
+    }
+
+    #[test]
+    fn test_token_from_other_environment_is_rejected() {
+        let token = create_jwt(USER_ID, Role::User, 0, &keys()).expect("Failed to create token");
+
+        let other_audience = config(SECRET, "test_issuer", "other_audience");
+        assert!(decode_jwt(&token, &other_audience).is_err());
+
+        let other_issuer = config(SECRET, "other_issuer", "test_audience");
+        assert!(decode_jwt(&token, &other_issuer).is_err());
+    }
+
+    #[test]
+    fn test_token_not_yet_valid_is_rejected() {
+        let jwt = keys();
+        let now = Utc::now().timestamp() as usize;
+        let claims = Claims {
+            sub: USER_ID.to_string(),
+            iss: jwt.issuer.clone(),
+            aud: jwt.audience.clone(),
+            jti: "jti".to_string(),
+            iat: now,
+            nbf: now + 120,
+            exp: now + 3600,
+            token_type: TokenType::Access.as_str().to_string(),
+            role: None,
+            token_version: None,
+            scopes: None,
+        };
+        let token = encode(&jwt.keys.header(), &claims, jwt.keys.encoding_key())
+            .expect("Failed to create token");
+        assert!(decode_jwt(&token, &jwt).is_err());
+
+        let lenient = JwtConfig {
+            leeway_seconds: 300,
+            ..keys()
+        };
+        assert!(decode_jwt(&token, &lenient).is_ok());
+    }
+
+    #[test]
+    fn test_token_version_revokes_older_access_tokens() {
+        let token = create_jwt(USER_ID, Role::User, 3, &keys()).expect("Failed to create token");
+        let claims = decode_jwt_with_type(&token, &keys(), TokenType::Access)
+            .expect("Failed to decode token");
+        assert!(!claims.is_revoked(3));
+        assert!(claims.is_revoked(4));
+    }
+
+    #[test]
+    fn test_api_key_claims_are_limited_to_their_scopes() {
+        let claims = api_key_claims(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            vec![ApiKeyScope::FriendsWrite],
+            &keys(),
+        );
+        assert!(claims.is_api_key());
+        assert_eq!(claims.role(), Role::User);
+        assert!(claims.has_scope(ApiKeyScope::FriendsRead));
+        assert!(claims.has_scope(ApiKeyScope::FriendsWrite));
+        assert!(!claims.has_scope(ApiKeyScope::ProfileRead));
+    }
+
+    #[test]
+    fn test_session_claims_have_every_scope() {
+        let token = create_jwt(USER_ID, Role::User, 0, &keys()).expect("Failed to create token");
+        let claims = decode_jwt(&token, &keys()).expect("Failed to decode token");
+        assert!(!claims.is_api_key());
+        assert!(claims.has_scope(ApiKeyScope::ProfileWrite));
+    }
 }
@@ -1,6 +1,17 @@
+pub mod breached_password;
+pub mod client;
 pub mod cookies;
 pub mod cursor;
+pub mod device;
+pub mod email;
 pub mod image;
 pub mod jwt;
+pub mod jwt_keys;
+pub mod password;
+pub mod pkce;
+pub mod public_host;
 pub mod s3;
+pub mod token;
+pub mod totp;
 pub mod validation;
+pub mod webauthn;
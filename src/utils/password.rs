@@ -0,0 +1,137 @@
+use crate::config::PasswordHashConfig;
+use argon2::{
+    Algorithm, Argon2, KeyId, Params, ParamsBuilder, Version,
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
+};
+
+/// Recorded as `keyid` in the PHC string of hashes computed with the pepper,
+/// so hashes from before the pepper was configured can still be verified
+const PEPPER_KEY_ID: &[u8] = b"pepper";
+
+fn hasher<'a>(config: &'a PasswordHashConfig, params: Params) -> Result<Argon2<'a>, String> {
+    match params.keyid() {
+        [] => Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params)),
+        PEPPER_KEY_ID => {
+            let pepper = config
+                .pepper
+                .as_deref()
+                .ok_or("hash was computed with a pepper, but PASSWORD_PEPPER is not set")?;
+            Argon2::new_with_secret(
+                pepper.as_bytes(),
+                Algorithm::Argon2id,
+                Version::V0x13,
+                params,
+            )
+            .map_err(|e| e.to_string())
+        }
+        _ => Err("hash was computed with an unknown key".to_string()),
+    }
+}
+
+/// Hashes a password with the configured parameters and pepper
+pub fn hash_password(password: &str, config: &PasswordHashConfig) -> Result<String, String> {
+    let mut params = ParamsBuilder::new();
+    params
+        .m_cost(config.params.m_cost())
+        .t_cost(config.params.t_cost())
+        .p_cost(config.params.p_cost());
+    if config.pepper.is_some() {
+        params.keyid(KeyId::new(PEPPER_KEY_ID).map_err(|e| e.to_string())?);
+    }
+    let params = params.build().map_err(|e| e.to_string())?;
+
+    let salt = SaltString::generate(&mut OsRng);
+    hasher(config, params)?
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Checks a password against a stored PHC string. The parameters recorded in the hash are used,
+/// so hashes created with older settings keep working. Errors mean the hash itself is unusable.
+pub fn verify_password(
+    password: &str,
+    password_hash: &str,
+    config: &PasswordHashConfig,
+) -> Result<bool, String> {
+    let parsed_hash = PasswordHash::new(password_hash).map_err(|e| e.to_string())?;
+    let params = Params::try_from(&parsed_hash).map_err(|e| e.to_string())?;
+
+    Ok(hasher(config, params)?
+        .verify_password(password.as_bytes(), &parsed_hash)
+        .is_ok())
+}
+
+/// Whether a stored hash was computed with other settings than the configured ones
+/// and should be replaced once the password is known
+pub fn needs_rehash(password_hash: &str, config: &PasswordHashConfig) -> bool {
+    let Ok(parsed_hash) = PasswordHash::new(password_hash) else {
+        return false;
+    };
+    let Ok(params) = Params::try_from(&parsed_hash) else {
+        return false;
+    };
+
+    parsed_hash.algorithm != Algorithm::Argon2id.ident()
+        || parsed_hash.version != Some(Version::V0x13.into())
+        || params.m_cost() != config.params.m_cost()
+        || params.t_cost() != config.params.t_cost()
+        || params.p_cost() != config.params.p_cost()
+        || params.keyid().is_empty() == config.pepper.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(m_cost: u32, t_cost: u32, pepper: Option<&str>) -> PasswordHashConfig {
+        PasswordHashConfig {
+            params: Params::new(m_cost, t_cost, 1, None).unwrap(),
+            pepper: pepper.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_hash_and_verify_round_trip() {
+        let config = config(1024, 1, None);
+        let hash = hash_password("correct horse", &config).unwrap();
+        assert!(hash.starts_with("$argon2id$v=19$m=1024,t=1,p=1$"));
+        assert!(verify_password("correct horse", &hash, &config).unwrap());
+        assert!(!verify_password("wrong horse", &hash, &config).unwrap());
+        assert!(!needs_rehash(&hash, &config));
+    }
+
+    #[test]
+    fn test_needs_rehash_when_parameters_change() {
+        let hash = hash_password("correct horse", &config(1024, 1, None)).unwrap();
+        let stronger = config(2048, 2, None);
+        assert!(needs_rehash(&hash, &stronger));
+        // Old parameters are read from the hash, so verification still succeeds
+        assert!(verify_password("correct horse", &hash, &stronger).unwrap());
+    }
+
+    #[test]
+    fn test_pepper_is_required_to_verify() {
+        let peppered = config(1024, 1, Some("secret"));
+        let hash = hash_password("correct horse", &peppered).unwrap();
+        assert!(hash.contains("keyid="));
+        assert!(verify_password("correct horse", &hash, &peppered).unwrap());
+        assert!(!verify_password("correct horse", &hash, &config(1024, 1, Some("other"))).unwrap());
+        assert!(verify_password("correct horse", &hash, &config(1024, 1, None)).is_err());
+    }
+
+    #[test]
+    fn test_unpeppered_hash_is_upgraded_once_pepper_is_set() {
+        let hash = hash_password("correct horse", &config(1024, 1, None)).unwrap();
+        let peppered = config(1024, 1, Some("secret"));
+        assert!(verify_password("correct horse", &hash, &peppered).unwrap());
+        assert!(needs_rehash(&hash, &peppered));
+    }
+
+    #[test]
+    fn test_malformed_hash_is_an_error() {
+        let config = config(1024, 1, None);
+        assert!(verify_password("password", "not a hash", &config).is_err());
+        assert!(!needs_rehash("not a hash", &config));
+    }
+}
@@ -0,0 +1,124 @@
+use reqwest::Url;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        // Includes the cloud metadata endpoint 169.254.169.254
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0
+        // Shared address space (carrier-grade NAT), 100.64.0.0/10
+        || (a == 100 && (b & 0xc0) == 64)
+        // IETF protocol assignments, 192.0.0.0/24
+        || (a == 192 && b == 0 && c == 0)
+        // Benchmarking, 198.18.0.0/15
+        || (a == 198 && (b & 0xfe) == 18)
+        // Reserved, 240.0.0.0/4
+        || a >= 240)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    if let Some(ipv4) = ip.to_ipv4_mapped() {
+        return is_public_ipv4(ipv4);
+    }
+    let segments = ip.segments();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // Unique local, fc00::/7
+        || (segments[0] & 0xfe00) == 0xfc00
+        // Link-local, fe80::/10
+        || (segments[0] & 0xffc0) == 0xfe80
+        // NAT64, 64:ff9b::/96, which can reach any IPv4 address
+        || (segments[0] == 0x64 && segments[1] == 0xff9b)
+        // Documentation, 2001:db8::/32
+        || (segments[0] == 0x2001 && segments[1] == 0xdb8))
+}
+
+/// Whether an address is reachable on the public internet,
+/// as opposed to loopback, private, link-local or otherwise reserved ranges
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => is_public_ipv6(ip),
+    }
+}
+
+/// Resolves the host of an https URL supplied by a third party and returns its addresses,
+/// if all of them are public. Connecting only to these addresses keeps the request from being
+/// pointed at internal services, including by a DNS answer that changes between check and use.
+pub async fn resolve_public_host(url: &Url) -> Result<(String, Vec<SocketAddr>), String> {
+    if url.scheme() != "https" {
+        return Err(format!("unsupported URL scheme '{}'", url.scheme()));
+    }
+    let host = url.host_str().ok_or("URL has no host")?;
+    let port = url.port_or_known_default().unwrap_or(443);
+
+    // IPv6 literals are bracketed in URLs
+    let addrs = match host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+    {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| format!("cannot resolve '{}': {}", host, e))?
+            .collect(),
+    };
+
+    if addrs.is_empty() {
+        return Err(format!("'{}' has no addresses", host));
+    }
+    if let Some(addr) = addrs.iter().find(|addr| !is_public_ip(addr.ip())) {
+        return Err(format!(
+            "'{}' resolves to non-public address {}",
+            host,
+            addr.ip()
+        ));
+    }
+    Ok((host.to_string(), addrs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(ip: &str) -> bool {
+        is_public_ip(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn test_public_addresses() {
+        assert!(public("93.184.216.34"));
+        assert!(public("8.8.8.8"));
+        assert!(public("2606:2800:220:1:248:1893:25c8:1946"));
+    }
+
+    #[test]
+    fn test_internal_addresses_are_not_public() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fd00:ec2::254",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "64:ff9b::a00:1",
+        ] {
+            assert!(!public(ip), "{} should not be public", ip);
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::{Engine as _, engine::general_purpose};
+use sha2::{Digest, Sha256};
+
+/// Number of random bytes in an opaque token (256 bits of entropy)
+const OPAQUE_TOKEN_BYTES: usize = 32;
+
+/// Generates a random URL-safe token for links sent by email.
+/// Only the SHA-256 hash of the token should ever be persisted.
+pub fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; OPAQUE_TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Hashes a token with SHA-256 so it can be stored and looked up without keeping the raw value
+pub fn hash_token(token: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(token.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Compares secrets without leaking the position of the first difference through timing
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_opaque_token_is_unique_and_url_safe() {
+        let a = generate_opaque_token();
+        let b = generate_opaque_token();
+        assert_ne!(a, b);
+        assert_eq!(a.len(), 43);
+        assert!(
+            a.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        );
+    }
+
+    #[test]
+    fn test_hash_token_is_deterministic_hex() {
+        let hash = hash_token("abc");
+        assert_eq!(hash, hash_token("abc"));
+        assert_eq!(
+            hash,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"token", b"token"));
+        assert!(!constant_time_eq(b"token", b"tokem"));
+        assert!(!constant_time_eq(b"token", b"token2"));
+        assert!(constant_time_eq(b"", b""));
+    }
+}
@@ -0,0 +1,193 @@
+//! Time-based one-time passwords (RFC 6238) using HMAC-SHA1, 6 digits and a 30 second step,
+//! which is what every common authenticator app expects.
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+use crate::constant::auth::{TOTP_DIGITS, TOTP_SKEW_STEPS, TOTP_STEP_SECONDS};
+use crate::utils::token::constant_time_eq;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Number of random bytes in a TOTP secret (160 bits, as recommended by RFC 4226)
+const SECRET_BYTES: usize = 20;
+
+/// Generates a new random secret, base32 encoded for authenticator apps
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    base32_encode(&bytes)
+}
+
+/// Builds the `otpauth://` URI that authenticator apps import (usually via QR code)
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={digits}&period={period}",
+        issuer = percent_encode(issuer),
+        account = percent_encode(account),
+        secret = secret,
+        digits = TOTP_DIGITS,
+        period = TOTP_STEP_SECONDS,
+    )
+}
+
+/// Returns the time step number for a Unix timestamp
+pub fn time_step(unix_seconds: u64) -> u64 {
+    unix_seconds / TOTP_STEP_SECONDS
+}
+
+/// Computes the code for a given time step, or None if the secret is not valid base32
+pub fn code_at_step(secret: &str, step: u64) -> Option<String> {
+    let key = base32_decode(secret)?;
+    Some(hotp(&key, step))
+}
+
+/// Verifies a code against the current time, allowing a small clock skew.
+/// Returns the matched time step so callers can reject replays of the same code.
+pub fn verify_code(secret: &str, code: &str, unix_seconds: u64) -> Option<u64> {
+    let code = code.trim();
+    if code.len() != TOTP_DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let key = base32_decode(secret)?;
+    let current = time_step(unix_seconds);
+
+    (current.saturating_sub(TOTP_SKEW_STEPS)..=current + TOTP_SKEW_STEPS)
+        .find(|&step| constant_time_eq(hotp(&key, step).as_bytes(), code.as_bytes()))
+}
+
+/// HOTP (RFC 4226) with dynamic truncation
+fn hotp(key: &[u8], counter: u64) -> String {
+    // HMAC accepts keys of any length, so this cannot fail
+    let mut mac = match Hmac::<Sha1>::new_from_slice(key) {
+        Ok(mac) => mac,
+        Err(_) => return String::new(),
+    };
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = ((digest[offset] as u32 & 0x7f) << 24)
+        | ((digest[offset + 1] as u32) << 16)
+        | ((digest[offset + 2] as u32) << 8)
+        | (digest[offset + 3] as u32);
+
+    let code = binary % 10u32.pow(TOTP_DIGITS);
+    format!("{:0width$}", code, width = TOTP_DIGITS as usize)
+}
+
+/// RFC 4648 base32 without padding
+fn base32_encode(data: &[u8]) -> String {
+    let mut output = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            output.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        output.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    output
+}
+
+/// Decodes base32, ignoring padding, spaces and case (as users may type secrets by hand)
+fn base32_decode(input: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for c in input.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a as char == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            output.push(((buffer >> bits) & 0xff) as u8);
+        }
+    }
+
+    if output.is_empty() {
+        None
+    } else {
+        Some(output)
+    }
+}
+
+fn percent_encode(input: &str) -> String {
+    input
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'@' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 6238 appendix B SHA1 secret: ASCII "12345678901234567890"
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn test_base32_round_trip() {
+        let data = b"12345678901234567890";
+        let encoded = base32_encode(data);
+        assert_eq!(encoded, RFC_SECRET);
+        assert_eq!(base32_decode(&encoded).unwrap(), data);
+    }
+
+    #[test]
+    fn test_rfc6238_vectors() {
+        // Last 6 digits of the 8-digit values in RFC 6238 appendix B
+        let vectors = [
+            (59, "287082"),
+            (1111111109, "081804"),
+            (1111111111, "050471"),
+            (1234567890, "005924"),
+            (2000000000, "279037"),
+        ];
+        for (time, expected) in vectors {
+            assert_eq!(code_at_step(RFC_SECRET, time_step(time)).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_verify_code_accepts_adjacent_step() {
+        let code = code_at_step(RFC_SECRET, time_step(1111111109)).unwrap();
+        assert_eq!(
+            verify_code(RFC_SECRET, &code, 1111111109 + TOTP_STEP_SECONDS),
+            Some(time_step(1111111109))
+        );
+    }
+
+    #[test]
+    fn test_verify_code_rejects_wrong_or_malformed_code() {
+        assert_eq!(verify_code(RFC_SECRET, "000000", 59), None);
+        assert_eq!(verify_code(RFC_SECRET, "28708", 59), None);
+        assert_eq!(verify_code(RFC_SECRET, "abcdef", 59), None);
+    }
+
+    #[test]
+    fn test_otpauth_uri_encodes_account() {
+        let uri = otpauth_uri("My App", "user+1@example.com", "ABC");
+        assert_eq!(
+            uri,
+            "otpauth://totp/My%20App:user%2B1@example.com?secret=ABC&issuer=My%20App&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+}
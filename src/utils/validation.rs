@@ -1,5 +1,6 @@
-use crate::constant::auth::MIN_PASSWORD_LENGTH;
+use crate::constant::auth::{MIN_PASSWORD_LENGTH, MIN_PASSWORD_SCORE};
 use validator::ValidationError;
+use zxcvbn::zxcvbn;
 
 /// Custom validator for password strength requirements.
 /// Returns Ok if password meets all requirements, otherwise returns ValidationError.
@@ -34,7 +35,7 @@ pub fn validate_password_strength(password: &str) -> Result<(), ValidationError>
         }
 
         if has_upper && has_lower && has_digit && has_special {
-            return Ok(());
+            return validate_password_guessability(password);
         }
     }
 
@@ -43,6 +44,31 @@ pub fn validate_password_strength(password: &str) -> Result<(), ValidationError>
     ))
 }
 
+/// Rejects passwords that satisfy the character rules but are still easy to guess,
+/// such as common passwords with a capital letter and a digit added (`Password1!`).
+/// The estimator's feedback is passed on so users know what to change.
+fn validate_password_guessability(password: &str) -> Result<(), ValidationError> {
+    let entropy = zxcvbn(password, &[]);
+    if u8::from(entropy.score()) >= MIN_PASSWORD_SCORE {
+        return Ok(());
+    }
+
+    let mut message = "Password is too easy to guess.".to_string();
+    if let Some(feedback) = entropy.feedback() {
+        for reason in feedback
+            .warning()
+            .map(|warning| warning.to_string())
+            .into_iter()
+            .chain(feedback.suggestions().iter().map(|s| s.to_string()))
+        {
+            message.push(' ');
+            message.push_str(&reason);
+        }
+    }
+
+    Err(ValidationError::new("guessable_password").with_message(message.into()))
+}
+
 /// Validates that a full name doesn't contain numbers and doesn't exceed 255 characters
 pub fn validate_full_name(name: &str) -> Result<(), String> {
     if name.len() > 255 {
@@ -61,6 +87,23 @@ pub fn validate_bio(bio: &str) -> Result<(), String> {
     }
     Ok(())
 }
+/// Helper function to format validation errors into a readable message
+pub fn format_validation_errors(errors: validator::ValidationErrors) -> String {
+    errors
+        .field_errors()
+        .iter()
+        .flat_map(|(field, errors)| {
+            errors.iter().map(move |e| {
+                e.message
+                    .clone()
+                    .map(|m| m.to_string())
+                    .unwrap_or_else(|| format!("Invalid {}", field))
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,6 +143,15 @@ mod tests {
         assert_eq!(result.unwrap_err().code, "weak_password");
     }
 
+    #[test]
+    fn test_common_password_with_required_characters() {
+        let error = validate_password_strength("Password1!").unwrap_err();
+        assert_eq!(error.code, "guessable_password");
+        let message = error.message.unwrap();
+        assert!(message.starts_with("Password is too easy to guess."));
+        assert!(message.contains("commonly used password"));
+    }
+
     #[test]
     fn test_valid_password() {
         let result = validate_password_strength("StrongPassword1!");
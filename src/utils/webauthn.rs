@@ -0,0 +1,445 @@
+//! Verification of WebAuthn (passkey) ceremonies: client data, authenticator data,
+//! attestation objects and COSE public keys.
+//!
+//! Registrations ask for `none` attestation, so attestation statements are not checked;
+//! a passkey is trusted because a signed-in user registered it, not because of its make.
+
+use aws_lc_rs::signature::{
+    ECDSA_P256_SHA256_ASN1, ED25519, RSA_PKCS1_2048_8192_SHA256, RsaPublicKeyComponents,
+    UnparsedPublicKey,
+};
+use ciborium::Value;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+/// COSE algorithm identifiers (RFC 9053) accepted for passkeys, in order of preference
+pub const ALG_ES256: i64 = -7;
+pub const ALG_EDDSA: i64 = -8;
+pub const ALG_RS256: i64 = -257;
+pub const SUPPORTED_ALGORITHMS: [i64; 3] = [ALG_ES256, ALG_EDDSA, ALG_RS256];
+
+/// `type` of the client data for each ceremony
+pub const CEREMONY_CREATE: &str = "webauthn.create";
+pub const CEREMONY_GET: &str = "webauthn.get";
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+/// rpIdHash (32) + flags (1) + signCount (4)
+const AUTHENTICATOR_DATA_MIN_LENGTH: usize = 37;
+const AAGUID_LENGTH: usize = 16;
+const MAX_CREDENTIAL_ID_LENGTH: usize = 1023;
+
+// COSE_Key map labels and values (RFC 9052, RFC 9053)
+const COSE_KTY: i64 = 1;
+const COSE_ALG: i64 = 3;
+const COSE_KTY_OKP: i64 = 1;
+const COSE_KTY_EC2: i64 = 2;
+const COSE_KTY_RSA: i64 = 3;
+const COSE_CRV_P256: i64 = 1;
+const COSE_CRV_ED25519: i64 = 6;
+const COSE_EC_CRV: i64 = -1;
+const COSE_EC_X: i64 = -2;
+const COSE_EC_Y: i64 = -3;
+const COSE_RSA_N: i64 = -1;
+const COSE_RSA_E: i64 = -2;
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    ceremony: String,
+    challenge: String,
+    origin: String,
+    #[serde(rename = "crossOrigin", default)]
+    cross_origin: bool,
+}
+
+/// A credential created by a registration ceremony
+#[derive(Debug)]
+pub struct RegisteredCredential {
+    pub credential_id: Vec<u8>,
+    /// The COSE_Key exactly as sent by the authenticator
+    pub public_key: Vec<u8>,
+    pub algorithm: i64,
+    pub sign_count: u32,
+}
+
+struct AuthenticatorData<'a> {
+    rp_id_hash: &'a [u8],
+    flags: u8,
+    sign_count: u32,
+    /// Present on registration: the new credential ID and its COSE_Key bytes
+    attested_credential: Option<(&'a [u8], &'a [u8])>,
+}
+
+impl<'a> AuthenticatorData<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self, String> {
+        if data.len() < AUTHENTICATOR_DATA_MIN_LENGTH {
+            return Err("authenticator data is too short".to_string());
+        }
+        let flags = data[32];
+        let sign_count = u32::from_be_bytes([data[33], data[34], data[35], data[36]]);
+
+        let attested_credential = if flags & FLAG_ATTESTED_CREDENTIAL_DATA != 0 {
+            let rest = &data[AUTHENTICATOR_DATA_MIN_LENGTH..];
+            let rest = rest
+                .get(AAGUID_LENGTH..)
+                .ok_or("attested credential data is truncated")?;
+            let (length, rest) = rest
+                .split_first_chunk::<2>()
+                .ok_or("attested credential data is truncated")?;
+            let length = u16::from_be_bytes(*length) as usize;
+            if length == 0 || length > MAX_CREDENTIAL_ID_LENGTH || rest.len() < length {
+                return Err("invalid credential ID length".to_string());
+            }
+            let (credential_id, rest) = rest.split_at(length);
+
+            // The COSE_Key is followed by optional extension data, so measure what CBOR consumes
+            let mut reader = rest;
+            ciborium::from_reader::<Value, _>(&mut reader)
+                .map_err(|e| format!("invalid credential public key: {}", e))?;
+            let public_key = &rest[..rest.len() - reader.len()];
+            Some((credential_id, public_key))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            rp_id_hash: &data[..32],
+            flags,
+            sign_count,
+            attested_credential,
+        })
+    }
+
+    /// The relying party, user presence and (for passwordless use) user verification
+    fn verify(&self, rp_id: &str) -> Result<(), String> {
+        if self.rp_id_hash != Sha256::digest(rp_id.as_bytes()).as_slice() {
+            return Err("credential belongs to a different relying party".to_string());
+        }
+        if self.flags & FLAG_USER_PRESENT == 0 {
+            return Err("user presence was not confirmed".to_string());
+        }
+        if self.flags & FLAG_USER_VERIFIED == 0 {
+            return Err("user verification was not performed".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Checks the ceremony type and origin of `clientDataJSON` and returns its challenge
+pub fn verify_client_data(
+    client_data_json: &[u8],
+    ceremony: &str,
+    allowed_origins: &[String],
+) -> Result<String, String> {
+    let client_data: ClientData = serde_json::from_slice(client_data_json)
+        .map_err(|e| format!("invalid client data: {}", e))?;
+
+    if client_data.ceremony != ceremony {
+        return Err(format!("unexpected ceremony '{}'", client_data.ceremony));
+    }
+    if client_data.cross_origin || !allowed_origins.contains(&client_data.origin) {
+        return Err(format!("origin '{}' is not allowed", client_data.origin));
+    }
+    Ok(client_data.challenge)
+}
+
+/// Verifies the attestation object of a registration and extracts the new credential.
+/// The client data must already have been checked with `verify_client_data`.
+pub fn verify_registration(
+    attestation_object: &[u8],
+    rp_id: &str,
+) -> Result<RegisteredCredential, String> {
+    let attestation: Value = ciborium::from_reader(attestation_object)
+        .map_err(|e| format!("invalid attestation object: {}", e))?;
+    let auth_data = attestation
+        .as_map()
+        .and_then(|entries| {
+            entries
+                .iter()
+                .find(|(key, _)| key.as_text() == Some("authData"))
+        })
+        .and_then(|(_, value)| value.as_bytes())
+        .ok_or("attestation object has no authenticator data")?;
+
+    let auth_data = AuthenticatorData::parse(auth_data)?;
+    auth_data.verify(rp_id)?;
+    let (credential_id, public_key) = auth_data
+        .attested_credential
+        .ok_or("attestation has no credential")?;
+
+    let algorithm = CosePublicKey::parse(public_key)?.algorithm;
+
+    Ok(RegisteredCredential {
+        credential_id: credential_id.to_vec(),
+        public_key: public_key.to_vec(),
+        algorithm,
+        sign_count: auth_data.sign_count,
+    })
+}
+
+/// Verifies an assertion made with a stored credential and returns the authenticator's
+/// new signature counter. The client data must already have been checked with `verify_client_data`.
+pub fn verify_assertion(
+    public_key: &[u8],
+    authenticator_data: &[u8],
+    client_data_json: &[u8],
+    signature: &[u8],
+    rp_id: &str,
+) -> Result<u32, String> {
+    let auth_data = AuthenticatorData::parse(authenticator_data)?;
+    auth_data.verify(rp_id)?;
+
+    // The authenticator signs its data followed by the hash of the client data
+    let mut signed = authenticator_data.to_vec();
+    signed.extend_from_slice(&Sha256::digest(client_data_json));
+    CosePublicKey::parse(public_key)?.verify(&signed, signature)?;
+
+    Ok(auth_data.sign_count)
+}
+
+enum KeyMaterial {
+    /// Uncompressed P-256 point (0x04 || x || y)
+    P256(Vec<u8>),
+    Ed25519(Vec<u8>),
+    Rsa {
+        n: Vec<u8>,
+        e: Vec<u8>,
+    },
+}
+
+struct CosePublicKey {
+    algorithm: i64,
+    key: KeyMaterial,
+}
+
+impl CosePublicKey {
+    fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let value: Value =
+            ciborium::from_reader(bytes).map_err(|e| format!("invalid COSE key: {}", e))?;
+        let entries = value.as_map().ok_or("COSE key is not a map")?;
+
+        let label = |label: i64| {
+            entries
+                .iter()
+                .find(|(key, _)| {
+                    key.as_integer().and_then(|k| i64::try_from(k).ok()) == Some(label)
+                })
+                .map(|(_, value)| value)
+        };
+        let integer = |name: i64| {
+            label(name)
+                .and_then(Value::as_integer)
+                .and_then(|value| i64::try_from(value).ok())
+                .ok_or_else(|| format!("COSE key is missing integer parameter {}", name))
+        };
+        let bytes = |name: i64| {
+            label(name)
+                .and_then(Value::as_bytes)
+                .cloned()
+                .ok_or_else(|| format!("COSE key is missing byte parameter {}", name))
+        };
+
+        let algorithm = integer(COSE_ALG)?;
+        let key_type = integer(COSE_KTY)?;
+        let key = match (algorithm, key_type) {
+            (ALG_ES256, COSE_KTY_EC2) => {
+                let (x, y) = (bytes(COSE_EC_X)?, bytes(COSE_EC_Y)?);
+                if integer(COSE_EC_CRV)? != COSE_CRV_P256 || x.len() != 32 || y.len() != 32 {
+                    return Err("ES256 key is not a P-256 point".to_string());
+                }
+                let mut point = Vec::with_capacity(65);
+                point.push(0x04);
+                point.extend_from_slice(&x);
+                point.extend_from_slice(&y);
+                KeyMaterial::P256(point)
+            }
+            (ALG_EDDSA, COSE_KTY_OKP) => {
+                let x = bytes(COSE_EC_X)?;
+                if integer(COSE_EC_CRV)? != COSE_CRV_ED25519 || x.len() != 32 {
+                    return Err("EdDSA key is not an Ed25519 key".to_string());
+                }
+                KeyMaterial::Ed25519(x)
+            }
+            (ALG_RS256, COSE_KTY_RSA) => KeyMaterial::Rsa {
+                n: bytes(COSE_RSA_N)?,
+                e: bytes(COSE_RSA_E)?,
+            },
+            _ => {
+                return Err(format!(
+                    "unsupported key (algorithm {}, key type {})",
+                    algorithm, key_type
+                ));
+            }
+        };
+
+        Ok(Self { algorithm, key })
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), String> {
+        let verified = match &self.key {
+            KeyMaterial::P256(point) => {
+                UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, point).verify(message, signature)
+            }
+            KeyMaterial::Ed25519(key) => {
+                UnparsedPublicKey::new(&ED25519, key).verify(message, signature)
+            }
+            KeyMaterial::Rsa { n, e } => RsaPublicKeyComponents { n, e }.verify(
+                &RSA_PKCS1_2048_8192_SHA256,
+                message,
+                signature,
+            ),
+        };
+        verified.map_err(|_| "signature verification failed".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_lc_rs::{
+        rand::SystemRandom,
+        signature::{Ed25519KeyPair, KeyPair},
+    };
+
+    const RP_ID: &str = "example.com";
+    const ORIGIN: &str = "https://example.com";
+
+    fn key_pair() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    fn cose_key(key_pair: &Ed25519KeyPair) -> Vec<u8> {
+        let key = Value::Map(vec![
+            (Value::from(COSE_KTY), Value::from(COSE_KTY_OKP)),
+            (Value::from(COSE_ALG), Value::from(ALG_EDDSA)),
+            (Value::from(COSE_EC_CRV), Value::from(COSE_CRV_ED25519)),
+            (
+                Value::from(COSE_EC_X),
+                Value::Bytes(key_pair.public_key().as_ref().to_vec()),
+            ),
+        ]);
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&key, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn authenticator_data(rp_id: &str, flags: u8, sign_count: u32) -> Vec<u8> {
+        let mut data = Sha256::digest(rp_id.as_bytes()).to_vec();
+        data.push(flags);
+        data.extend_from_slice(&sign_count.to_be_bytes());
+        data
+    }
+
+    fn client_data_json(ceremony: &str, origin: &str) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "type": ceremony,
+            "challenge": "challenge",
+            "origin": origin,
+        }))
+        .unwrap()
+    }
+
+    fn sign(key_pair: &Ed25519KeyPair, auth_data: &[u8], client_data: &[u8]) -> Vec<u8> {
+        let mut signed = auth_data.to_vec();
+        signed.extend_from_slice(&Sha256::digest(client_data));
+        key_pair.sign(&signed).as_ref().to_vec()
+    }
+
+    #[test]
+    fn test_verify_registration_extracts_credential() {
+        let key_pair = key_pair();
+        let public_key = cose_key(&key_pair);
+
+        let mut auth_data = authenticator_data(
+            RP_ID,
+            FLAG_USER_PRESENT | FLAG_USER_VERIFIED | FLAG_ATTESTED_CREDENTIAL_DATA,
+            0,
+        );
+        auth_data.extend_from_slice(&[0u8; AAGUID_LENGTH]);
+        auth_data.extend_from_slice(&3u16.to_be_bytes());
+        auth_data.extend_from_slice(b"abc");
+        auth_data.extend_from_slice(&public_key);
+
+        let attestation = Value::Map(vec![
+            (Value::from("fmt"), Value::from("none")),
+            (Value::from("attStmt"), Value::Map(vec![])),
+            (Value::from("authData"), Value::Bytes(auth_data)),
+        ]);
+        let mut attestation_object = Vec::new();
+        ciborium::into_writer(&attestation, &mut attestation_object).unwrap();
+
+        let credential = verify_registration(&attestation_object, RP_ID).unwrap();
+        assert_eq!(credential.credential_id, b"abc");
+        assert_eq!(credential.public_key, public_key);
+        assert_eq!(credential.algorithm, ALG_EDDSA);
+
+        assert!(verify_registration(&attestation_object, "other.com").is_err());
+    }
+
+    #[test]
+    fn test_verify_assertion_checks_signature_and_returns_counter() {
+        let key_pair = key_pair();
+        let public_key = cose_key(&key_pair);
+        let auth_data = authenticator_data(RP_ID, FLAG_USER_PRESENT | FLAG_USER_VERIFIED, 7);
+        let client_data = client_data_json(CEREMONY_GET, ORIGIN);
+        let signature = sign(&key_pair, &auth_data, &client_data);
+
+        assert_eq!(
+            verify_assertion(&public_key, &auth_data, &client_data, &signature, RP_ID),
+            Ok(7)
+        );
+
+        let tampered = client_data_json(CEREMONY_GET, "https://evil.example");
+        assert!(verify_assertion(&public_key, &auth_data, &tampered, &signature, RP_ID).is_err());
+
+        let other_key = cose_key(&self::key_pair());
+        assert!(verify_assertion(&other_key, &auth_data, &client_data, &signature, RP_ID).is_err());
+    }
+
+    #[test]
+    fn test_verify_assertion_requires_user_verification() {
+        let key_pair = key_pair();
+        let public_key = cose_key(&key_pair);
+        let auth_data = authenticator_data(RP_ID, FLAG_USER_PRESENT, 1);
+        let client_data = client_data_json(CEREMONY_GET, ORIGIN);
+        let signature = sign(&key_pair, &auth_data, &client_data);
+
+        assert!(
+            verify_assertion(&public_key, &auth_data, &client_data, &signature, RP_ID).is_err()
+        );
+    }
+
+    #[test]
+    fn test_verify_client_data_checks_type_and_origin() {
+        let origins = vec![ORIGIN.to_string()];
+
+        assert_eq!(
+            verify_client_data(
+                &client_data_json(CEREMONY_GET, ORIGIN),
+                CEREMONY_GET,
+                &origins
+            ),
+            Ok("challenge".to_string())
+        );
+        assert!(
+            verify_client_data(
+                &client_data_json(CEREMONY_CREATE, ORIGIN),
+                CEREMONY_GET,
+                &origins
+            )
+            .is_err()
+        );
+        assert!(
+            verify_client_data(
+                &client_data_json(CEREMONY_GET, "https://evil.example"),
+                CEREMONY_GET,
+                &origins
+            )
+            .is_err()
+        );
+    }
+}